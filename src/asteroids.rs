@@ -0,0 +1,169 @@
+use nalgebra::{Matrix4, Vector3 as Vec3, Vector4, UnitQuaternion, Unit};
+use std::f32::consts::PI;
+use crate::framebuffer::Framebuffer;
+use crate::shaders::{ShaderColor, perlin_noise};
+use crate::instancing::{Instance, InstancedMesh, render_instances};
+use crate::render::{draw_point, WIDTH, HEIGHT};
+use crate::sphere::icosphere;
+
+// Radio proyectado en píxeles a partir del cual se usa la malla detallada, y
+// por debajo del cual la roca se dibuja como un punto
+const LOD_DETAILED_PIXELS: f32 = 8.0;
+const LOD_POINT_PIXELS: f32 = 2.0;
+// Velocidad angular de una órbita de radio 1; cae con r^1.5 como en Kepler
+const ORBIT_SPEED_AT_UNIT_RADIUS: f32 = 5.8;
+// Inclinación máxima de las órbitas, en radianes
+const MAX_INCLINATION: f32 = 0.04;
+const SIZE_RANGE: (f32, f32) = (0.04, 0.16);
+// Cuánto se aparta la superficie de la esfera en la malla compartida
+const ROCK_ROUGHNESS: f32 = 0.35;
+
+/// Valor pseudoaleatorio en [0, 1) fijo para cada roca y cada atributo
+fn hash(index: usize, salt: f32) -> f32 {
+    ((index as f32 * 12.9898 + salt * 78.233).sin() * 43758.547).fract().abs()
+}
+
+/// Parámetros fijos de una roca; su pose se deriva del tiempo
+struct Asteroid {
+    orbit_radius: f32,
+    orbit_speed: f32,
+    initial_angle: f32,
+    inclination: f32,
+    // Ángulo en el que la órbita cruza el plano eclíptico
+    node: f32,
+    spin_axis: Unit<Vec3<f32>>,
+    spin_speed: f32,
+    instance: Instance,
+}
+
+/// Miles de rocas pequeñas entre dos órbitas, cada una con su órbita, giro y
+/// tamaño. Todas comparten dos mallas (detallada y simple) y las lejanas se
+/// dibujan como puntos.
+pub struct AsteroidBelt {
+    asteroids: Vec<Asteroid>,
+    detailed: InstancedMesh,
+    simple: InstancedMesh,
+}
+
+impl AsteroidBelt {
+    pub fn new(count: usize, inner_radius: f32, outer_radius: f32) -> Self {
+        let asteroids = (0..count)
+            .map(|i| {
+                // Más rocas hacia el centro del cinturón
+                let spread = (hash(i, 1.0) + hash(i, 2.0)) * 0.5;
+                let orbit_radius = inner_radius + (outer_radius - inner_radius) * spread;
+                let size = SIZE_RANGE.0 + (SIZE_RANGE.1 - SIZE_RANGE.0) * hash(i, 3.0).powi(3);
+                let axis = Vec3::new(hash(i, 4.0) - 0.5, hash(i, 5.0) - 0.5, hash(i, 6.0) - 0.5);
+                let shade = 0.55 + 0.45 * hash(i, 7.0);
+                let warmth = hash(i, 8.0) * 0.15;
+                Asteroid {
+                    orbit_radius,
+                    orbit_speed: ORBIT_SPEED_AT_UNIT_RADIUS / orbit_radius.powf(1.5),
+                    initial_angle: hash(i, 9.0) * 2.0 * PI,
+                    inclination: (hash(i, 10.0) * 2.0 - 1.0) * MAX_INCLINATION,
+                    node: hash(i, 11.0) * 2.0 * PI,
+                    spin_axis: Unit::try_new(axis, 1e-4).unwrap_or(Vec3::y_axis()),
+                    spin_speed: (hash(i, 12.0) * 2.0 - 1.0) * 2.0,
+                    instance: Instance {
+                        position: Vec3::zeros(),
+                        rotation: UnitQuaternion::identity(),
+                        scale: Vec3::new(
+                            size * (0.7 + 0.6 * hash(i, 13.0)),
+                            size * (0.7 + 0.6 * hash(i, 14.0)),
+                            size * (0.7 + 0.6 * hash(i, 15.0)),
+                        ),
+                        color: ShaderColor::new(
+                            (0.48 + warmth) * shade,
+                            (0.44 + warmth * 0.5) * shade,
+                            0.40 * shade,
+                            1.0,
+                        ),
+                    },
+                }
+            })
+            .collect();
+
+        AsteroidBelt {
+            asteroids,
+            detailed: rock_mesh(1),
+            simple: rock_mesh(0),
+        }
+    }
+
+    /// Coloca y gira cada roca como está en el instante `time`
+    pub fn set_time(&mut self, time: f32) {
+        for rock in &mut self.asteroids {
+            let angle = rock.initial_angle + rock.orbit_speed * time;
+            let height = (angle - rock.node).sin() * rock.inclination * rock.orbit_radius;
+            rock.instance.position = Vec3::new(
+                angle.cos() * rock.orbit_radius,
+                height,
+                angle.sin() * rock.orbit_radius,
+            );
+            rock.instance.rotation = UnitQuaternion::from_axis_angle(&rock.spin_axis, rock.spin_speed * time);
+        }
+    }
+
+    /// Elige el nivel de detalle de cada roca según su tamaño en pantalla.
+    /// `focal_length` es la distancia focal de la proyección en píxeles.
+    pub fn render(
+        &self,
+        framebuffer: &mut Framebuffer,
+        view_proj: &Matrix4<f32>,
+        focal_length: f32,
+        light_direction: Vec3<f32>,
+    ) {
+        let mut detailed = Vec::new();
+        let mut simple = Vec::new();
+
+        for rock in &self.asteroids {
+            let p = rock.instance.position;
+            let clip = view_proj * Vector4::new(p.x, p.y, p.z, 1.0);
+            if clip.w <= 0.0 {
+                continue;
+            }
+            let screen_x = (clip.x / clip.w + 1.0) * 0.5 * WIDTH as f32;
+            let screen_y = (1.0 - clip.y / clip.w) * 0.5 * HEIGHT as f32;
+            let screen_radius = rock.instance.scale.max() * self.detailed.radius * focal_length / clip.w;
+            let outside = screen_x + screen_radius < 0.0
+                || screen_x - screen_radius > WIDTH as f32
+                || screen_y + screen_radius < 0.0
+                || screen_y - screen_radius > HEIGHT as f32;
+            if outside {
+                continue;
+            }
+
+            if screen_radius >= LOD_DETAILED_PIXELS {
+                detailed.push(&rock.instance);
+            } else if screen_radius >= LOD_POINT_PIXELS {
+                simple.push(&rock.instance);
+            } else {
+                let size = if screen_radius >= LOD_POINT_PIXELS * 0.5 { 2 } else { 1 };
+                draw_point(
+                    framebuffer,
+                    (screen_x as i32, screen_y as i32, clip.z / clip.w),
+                    size,
+                    rock.instance.color.to_u32(),
+                );
+            }
+        }
+
+        render_instances(framebuffer, &self.detailed, detailed, view_proj, light_direction);
+        render_instances(framebuffer, &self.simple, simple, view_proj, light_direction);
+    }
+}
+
+/// Icosaedro subdividido `subdivisions` veces con la superficie deformada por
+/// ruido. El ruido depende solo de la dirección, así que todos los niveles de
+/// detalle tienen la misma silueta general.
+fn rock_mesh(subdivisions: usize) -> InstancedMesh {
+    let (vertices, triangles) = icosphere(subdivisions);
+    let vertices = vertices
+        .into_iter()
+        .map(|v| {
+            let bump = perlin_noise(v.x * 1.8 + 3.1, v.y * 1.8 + 7.7, v.z * 1.8 + 1.3);
+            v * (1.0 + bump * ROCK_ROUGHNESS)
+        })
+        .collect();
+    InstancedMesh::new(vertices, triangles)
+}
@@ -1,51 +1,459 @@
-use nalgebra::{Vector3 as Vec3};
+use nalgebra::{Vector3 as Vec3, UnitQuaternion, Unit};
 use crate::matrix::create_view_matrix;
+use crate::camera_transition::{CameraPose, CameraTransition};
+use crate::planet::BoundingSphere;
 use nalgebra::Matrix4;
 
+// Parámetros de vuelo libre
+const THRUST_ACCELERATION: f32 = 18.0;
+const LINEAR_DAMPING: f32 = 1.2;
+const MAX_FLIGHT_SPEED: f32 = 25.0;
+
+// Pasadas de corrección cuando la cámara toca varias esferas a la vez
+const COLLISION_ITERATIONS: usize = 4;
+
+// Cámara de persecución: posición detrás y encima de la nave, punto que
+// mira por delante de ella y rapidez con que alcanza esa pose
+const CHASE_DISTANCE: f32 = 5.0;
+const CHASE_HEIGHT: f32 = 1.5;
+const CHASE_LOOK_AHEAD: f32 = 6.0;
+const CHASE_STIFFNESS: f32 = 6.0;
+
+// Duración por defecto de las transiciones entre vistas, en segundos
+const DEFAULT_TRANSITION_DURATION: f32 = 1.5;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CameraMode {
+    /// Gira alrededor del origen a distancia y altura acotadas
+    Orbit,
+    /// Nave libre de seis grados de libertad con inercia
+    FreeFlight,
+    /// Gira alrededor del cuerpo seleccionado y lo acompaña en su órbita
+    Follow,
+    /// Va detrás de la nave del jugador con algo de retraso
+    Chase,
+}
+
+impl CameraMode {
+    pub fn name(&self) -> &'static str {
+        match self {
+            CameraMode::Orbit => "órbita",
+            CameraMode::FreeFlight => "vuelo libre",
+            CameraMode::Follow => "seguimiento",
+            CameraMode::Chase => "persecución",
+        }
+    }
+
+    /// Nombre corto que se usa en la línea de comandos
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "orbit" => Some(CameraMode::Orbit),
+            "free" => Some(CameraMode::FreeFlight),
+            "follow" => Some(CameraMode::Follow),
+            "chase" => Some(CameraMode::Chase),
+            _ => None,
+        }
+    }
+}
+
+/// Parámetros de una cámara que gira alrededor de un centro
+struct OrbitRig {
+    angle: f32,
+    distance: f32,
+    height: f32,
+    distance_range: (f32, f32),
+    height_range: (f32, f32),
+}
+
+impl OrbitRig {
+    fn offset(&self) -> Vec3<f32> {
+        Vec3::new(
+            self.angle.cos() * self.distance,
+            self.height,
+            self.angle.sin() * self.distance,
+        )
+    }
+
+    /// Ajusta el rig para que la cámara quede lo más cerca posible de `offset`
+    fn adopt_offset(&mut self, offset: Vec3<f32>) {
+        let horizontal = (offset.x * offset.x + offset.z * offset.z).sqrt();
+        if horizontal > 1e-4 {
+            self.angle = offset.z.atan2(offset.x);
+        }
+        self.distance = horizontal.clamp(self.distance_range.0, self.distance_range.1);
+        self.height = offset.y.clamp(self.height_range.0, self.height_range.1);
+    }
+}
+
 pub struct Camera {
     pub position: Vec3<f32>,
     pub target: Vec3<f32>,
     pub up: Vec3<f32>,
-    angle: f32,
-    distance: f32,
-    height: f32,
+    pub mode: CameraMode,
+    orbit_rig: OrbitRig,
+    follow_rig: OrbitRig,
+    orientation: UnitQuaternion<f32>,
+    velocity: Vec3<f32>,
+    // Centro del cuerpo que se sigue
+    focus: Vec3<f32>,
+    // Pose de la nave que persigue el modo persecución
+    chase_anchor: (Vec3<f32>, UnitQuaternion<f32>),
+    transition: Option<CameraTransition>,
+    transition_duration: f32,
 }
 
 impl Camera {
     pub fn new(distance: f32) -> Self {
-        Camera {
+        let mut camera = Camera {
             position: Vec3::new(0.0, 5.0, distance),
             target: Vec3::new(0.0, 0.0, 0.0),
             up: Vec3::new(0.0, 1.0, 0.0),
-            angle: 0.0,
-            distance,
-            height: 5.0,
+            mode: CameraMode::Orbit,
+            orbit_rig: OrbitRig {
+                // Arrancar sobre el eje +Z, mirando al origen
+                angle: std::f32::consts::FRAC_PI_2,
+                distance,
+                height: 5.0,
+                distance_range: (10.0, 100.0),
+                height_range: (2.0, 20.0),
+            },
+            follow_rig: OrbitRig {
+                angle: std::f32::consts::FRAC_PI_2,
+                distance: 8.0,
+                height: 3.0,
+                distance_range: (2.0, 40.0),
+                height_range: (-15.0, 15.0),
+            },
+            orientation: UnitQuaternion::identity(),
+            velocity: Vec3::zeros(),
+            focus: Vec3::zeros(),
+            chase_anchor: (Vec3::zeros(), UnitQuaternion::identity()),
+            transition: None,
+            transition_duration: DEFAULT_TRANSITION_DURATION,
+        };
+        camera.update_position();
+        camera
+    }
+
+    fn active_rig(&mut self) -> &mut OrbitRig {
+        match self.mode {
+            CameraMode::Follow => &mut self.follow_rig,
+            _ => &mut self.orbit_rig,
+        }
+    }
+
+    fn rig_center(&self) -> Vec3<f32> {
+        match self.mode {
+            CameraMode::Follow => self.focus,
+            _ => Vec3::zeros(),
         }
     }
 
+    /// Pose que dicta el rig del modo actual, sin tener en cuenta transiciones
+    fn rig_pose(&self) -> CameraPose {
+        if self.mode == CameraMode::Chase {
+            let (ship, orientation) = self.chase_anchor;
+            return CameraPose::look_at(
+                ship + orientation * Vec3::new(0.0, CHASE_HEIGHT, CHASE_DISTANCE),
+                ship + orientation * Vec3::new(0.0, 0.0, -CHASE_LOOK_AHEAD),
+                orientation * Vec3::y(),
+            );
+        }
+        let center = self.rig_center();
+        let offset = match self.mode {
+            CameraMode::Follow => self.follow_rig.offset(),
+            _ => self.orbit_rig.offset(),
+        };
+        CameraPose::look_at(center + offset, center, Vec3::y())
+    }
+
+    fn apply_pose(&mut self, pose: &CameraPose) {
+        self.position = pose.position;
+        self.target = pose.target();
+        self.up = pose.up();
+    }
+
     fn update_position(&mut self) {
-        self.position.x = self.angle.cos() * self.distance;
-        self.position.z = self.angle.sin() * self.distance;
-        self.position.y = self.height;
+        if self.transition.is_none() {
+            let pose = self.rig_pose();
+            self.apply_pose(&pose);
+        }
     }
 
     pub fn rotate(&mut self, delta_angle: f32) {
-        self.angle += delta_angle;
+        self.cancel_transition();
+        self.active_rig().angle += delta_angle;
         self.update_position();
     }
 
     pub fn zoom(&mut self, delta: f32) {
-        self.distance = (self.distance + delta).max(10.0).min(100.0);
+        self.cancel_transition();
+        let rig = self.active_rig();
+        rig.distance = (rig.distance + delta).clamp(rig.distance_range.0, rig.distance_range.1);
         self.update_position();
     }
 
     pub fn change_height(&mut self, delta: f32) {
-        self.height = (self.height + delta).max(2.0).min(20.0);
+        self.cancel_transition();
+        let rig = self.active_rig();
+        rig.height = (rig.height + delta).clamp(rig.height_range.0, rig.height_range.1);
         self.update_position();
     }
 
+    /// Distancia y altura con las que el modo seguimiento se coloca respecto al cuerpo
+    pub fn set_follow_offset(&mut self, distance: f32, height: f32) {
+        let rig = &mut self.follow_rig;
+        rig.distance = distance.clamp(rig.distance_range.0, rig.distance_range.1);
+        rig.height = height.clamp(rig.height_range.0, rig.height_range.1);
+        if self.mode == CameraMode::Follow {
+            self.update_position();
+        }
+    }
+
+    /// Posición actual del cuerpo que se sigue; se llama cada cuadro
+    pub fn set_follow_goal(&mut self, position: Vec3<f32>) {
+        self.focus = position;
+    }
+
+    /// Posición y orientación actuales de la nave; se llama cada cuadro
+    pub fn set_chase_goal(&mut self, position: Vec3<f32>, orientation: UnitQuaternion<f32>) {
+        self.chase_anchor = (position, orientation);
+    }
+
+    /// Duración de las transiciones animadas; 0 las desactiva
+    pub fn set_transition_duration(&mut self, seconds: f32) {
+        self.transition_duration = seconds.max(0.0);
+    }
+
+    /// Empieza a volar desde la vista actual hacia la que dicte el modo activo
+    fn begin_transition(&mut self) {
+        self.transition = if self.transition_duration > 0.0 {
+            let from = CameraPose::look_at(self.position, self.target, self.up);
+            Some(CameraTransition::new(from, self.transition_duration))
+        } else {
+            None
+        };
+    }
+
+    /// Detiene la transición en curso y deja la cámara donde está, de modo que
+    /// el usuario retome el control sin saltos.
+    pub fn cancel_transition(&mut self) {
+        if self.transition.take().is_none() {
+            return;
+        }
+        match self.mode {
+            CameraMode::FreeFlight => {
+                self.orientation = CameraPose::look_at(self.position, self.target, self.up).orientation;
+            }
+            CameraMode::Orbit | CameraMode::Follow => {
+                let offset = self.position - self.rig_center();
+                self.active_rig().adopt_offset(offset);
+                self.update_position();
+            }
+            // La persecución retoma el suavizado desde donde quedó la cámara
+            CameraMode::Chase => {}
+        }
+    }
+
+    /// Coloca la cámara en una pose dictada desde fuera (por ejemplo, un
+    /// recorrido grabado). Mientras se use no conviene llamar a `update`.
+    pub fn set_pose(&mut self, position: Vec3<f32>, target: Vec3<f32>) {
+        self.transition = None;
+        self.velocity = Vec3::zeros();
+        let pose = CameraPose::look_at(position, target, Vec3::y());
+        self.orientation = pose.orientation;
+        self.apply_pose(&pose);
+    }
+
+    /// Devuelve el control al modo activo tras `set_pose`, volando desde la
+    /// pose actual hasta la del modo
+    pub fn release_pose(&mut self) {
+        match self.mode {
+            CameraMode::FreeFlight => self.sync_look_at(),
+            CameraMode::Orbit | CameraMode::Follow | CameraMode::Chase => self.begin_transition(),
+        }
+    }
+
+    /// Vuela suavemente hacia el cuerpo seguido tras cambiar la selección
+    pub fn retarget(&mut self) {
+        if self.mode == CameraMode::Follow {
+            self.begin_transition();
+        }
+    }
+
+    /// Cambia de modo. Al entrar en vuelo libre se conserva la vista actual;
+    /// en los demás modos la cámara vuela hasta su nueva posición.
+    pub fn set_mode(&mut self, mode: CameraMode) {
+        if mode == self.mode {
+            return;
+        }
+        self.cancel_transition();
+        self.mode = mode;
+        self.velocity = Vec3::zeros();
+
+        match mode {
+            CameraMode::FreeFlight => {
+                self.orientation = CameraPose::look_at(self.position, self.target, self.up).orientation;
+                self.sync_look_at();
+            }
+            CameraMode::Orbit | CameraMode::Follow | CameraMode::Chase => {
+                self.begin_transition();
+                self.update_position();
+            }
+        }
+    }
+
+    /// Entra en `mode` colocándose directamente en su pose, para el arranque
+    pub fn start_in_mode(&mut self, mode: CameraMode) {
+        self.set_mode(mode);
+        self.transition = None;
+        if mode != CameraMode::FreeFlight {
+            self.update_position();
+        }
+    }
+
+    pub fn toggle_mode(&mut self) {
+        let next = match self.mode {
+            CameraMode::Orbit => CameraMode::Follow,
+            CameraMode::Follow => CameraMode::FreeFlight,
+            CameraMode::FreeFlight => CameraMode::Chase,
+            CameraMode::Chase => CameraMode::Orbit,
+        };
+        self.set_mode(next);
+    }
+
+    /// Empuje en ejes locales (x = derecha, y = arriba, z = adelante).
+    /// Los componentes van de -1 a 1.
+    pub fn thrust(&mut self, local_direction: Vec3<f32>, dt: f32) {
+        if self.mode != CameraMode::FreeFlight || local_direction == Vec3::zeros() {
+            return;
+        }
+        self.cancel_transition();
+        // La cámara mira hacia -Z en su espacio local
+        let local = Vec3::new(local_direction.x, local_direction.y, -local_direction.z);
+        self.velocity += self.orientation * local * THRUST_ACCELERATION * dt;
+
+        let speed = self.velocity.norm();
+        if speed > MAX_FLIGHT_SPEED {
+            self.velocity *= MAX_FLIGHT_SPEED / speed;
+        }
+    }
+
+    fn rotate_local(&mut self, axis: Vec3<f32>, angle: f32) {
+        if self.mode != CameraMode::FreeFlight || angle == 0.0 {
+            return;
+        }
+        self.cancel_transition();
+        let rotation = UnitQuaternion::from_axis_angle(&Unit::new_normalize(axis), angle);
+        self.orientation *= rotation;
+        self.orientation.renormalize_fast();
+        self.sync_look_at();
+    }
+
+    /// Cabeceo: ángulos positivos levantan la nariz
+    pub fn pitch(&mut self, angle: f32) {
+        self.rotate_local(Vec3::x(), angle);
+    }
+
+    /// Guiñada: ángulos positivos giran hacia la izquierda
+    pub fn yaw(&mut self, angle: f32) {
+        self.rotate_local(Vec3::y(), angle);
+    }
+
+    /// Alabeo: ángulos positivos inclinan hacia la izquierda
+    pub fn roll(&mut self, angle: f32) {
+        self.rotate_local(Vec3::z(), angle);
+    }
+
+    fn sync_look_at(&mut self) {
+        self.target = self.position + self.orientation * Vec3::new(0.0, 0.0, -1.0);
+        self.up = self.orientation * Vec3::y();
+    }
+
+    /// Saca la cámara de las esferas en las que haya entrado y anula la
+    /// velocidad hacia la superficie, de modo que la cámara se desliza sobre
+    /// ella. Devuelve si hubo que moverla.
+    fn resolve_collisions(&mut self, obstacles: &[BoundingSphere]) -> bool {
+        let mut moved = false;
+        for _ in 0..COLLISION_ITERATIONS {
+            let mut pushed = false;
+            for sphere in obstacles {
+                let offset = self.position - sphere.center;
+                let distance = offset.norm();
+                if distance >= sphere.radius {
+                    continue;
+                }
+                let normal = if distance > 1e-5 { offset / distance } else { Vec3::y() };
+                self.position = sphere.center + normal * sphere.radius;
+                let into_surface = self.velocity.dot(&normal);
+                if into_surface < 0.0 {
+                    self.velocity -= normal * into_surface;
+                }
+                pushed = true;
+            }
+            if !pushed {
+                break;
+            }
+            moved = true;
+        }
+        moved
+    }
+
+    /// Integra la inercia del vuelo libre, acompaña al cuerpo seguido o a la
+    /// nave y avanza la transición en curso esquivando los cuerpos de
+    /// `obstacles`. La cámara nunca queda dentro de una de esas esferas.
+    pub fn update(&mut self, dt: f32, obstacles: &[BoundingSphere]) {
+        if self.mode == CameraMode::FreeFlight {
+            self.position += self.velocity * dt;
+            self.velocity *= (-LINEAR_DAMPING * dt).exp();
+            self.resolve_collisions(obstacles);
+            self.sync_look_at();
+            return;
+        }
+
+        // La persecución alcanza su pose con retraso exponencial en lugar de
+        // pegarse a la nave, pero siempre mira hacia donde apunta
+        if self.mode == CameraMode::Chase && self.transition.is_none() {
+            let goal = self.rig_pose();
+            let blend = 1.0 - (-CHASE_STIFFNESS * dt).exp();
+            self.position += (goal.position - self.position) * blend;
+            self.up = (self.up + (goal.up() - self.up) * blend).normalize();
+            self.target = goal.target();
+            self.resolve_collisions(obstacles);
+            return;
+        }
+
+        let goal = self.rig_pose();
+        let pose = match self.transition.as_mut() {
+            Some(transition) => {
+                transition.advance(dt);
+                if transition.is_finished() {
+                    self.transition = None;
+                    goal
+                } else {
+                    transition.sample(&goal, obstacles)
+                }
+            }
+            None => goal,
+        };
+        self.apply_pose(&pose);
+
+        // Sin transición en curso el rig adopta la posición corregida, así que
+        // seguir girando o acercándose desliza la cámara sobre la superficie
+        let moved = self.resolve_collisions(obstacles);
+        if moved && self.transition.is_none() && self.mode != CameraMode::Chase {
+            let offset = self.position - self.rig_center();
+            self.active_rig().adopt_offset(offset);
+        }
+    }
+
     pub fn get_view_matrix(&self) -> Matrix4<f32> {
         // Usar la función manual de creación de matriz de vista
         create_view_matrix(self.position, self.target, self.up)
     }
+
+    /// Matriz de vista sin traslación, para fondos situados en el infinito
+    pub fn get_rotation_view_matrix(&self) -> Matrix4<f32> {
+        create_view_matrix(Vec3::zeros(), self.target - self.position, self.up)
+    }
 }
@@ -0,0 +1,317 @@
+use nalgebra::Vector3 as Vec3;
+use std::fs;
+
+/// Posición y punto de mira de la cámara en un instante de la simulación
+#[derive(Debug, Clone, Copy)]
+pub struct Keyframe {
+    pub time: f32,
+    pub position: Vec3<f32>,
+    pub target: Vec3<f32>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PathInterpolation {
+    /// Pasa exactamente por cada fotograma clave
+    CatmullRom,
+    /// Curva de Bézier global que usa los fotogramas como puntos de control;
+    /// más suave pero solo toca el primero y el último
+    Bezier,
+}
+
+impl PathInterpolation {
+    pub fn name(&self) -> &'static str {
+        match self {
+            PathInterpolation::CatmullRom => "catmull-rom",
+            PathInterpolation::Bezier => "bezier",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "catmull-rom" => Some(PathInterpolation::CatmullRom),
+            "bezier" => Some(PathInterpolation::Bezier),
+            _ => None,
+        }
+    }
+}
+
+/// Recorrido de cámara definido por fotogramas clave ordenados por tiempo
+#[derive(Debug, Clone)]
+pub struct CameraPath {
+    pub keyframes: Vec<Keyframe>,
+    pub interpolation: PathInterpolation,
+}
+
+// Hermite cúbico con tangentes escaladas al ancho del tramo
+fn hermite(p0: Vec3<f32>, m0: Vec3<f32>, p1: Vec3<f32>, m1: Vec3<f32>, t: f32) -> Vec3<f32> {
+    let t2 = t * t;
+    let t3 = t2 * t;
+    p0 * (2.0 * t3 - 3.0 * t2 + 1.0)
+        + m0 * (t3 - 2.0 * t2 + t)
+        + p1 * (-2.0 * t3 + 3.0 * t2)
+        + m1 * (t3 - t2)
+}
+
+// Algoritmo de De Casteljau
+fn de_casteljau(points: &[Vec3<f32>], t: f32) -> Vec3<f32> {
+    let mut work = points.to_vec();
+    for level in 1..work.len() {
+        for i in 0..work.len() - level {
+            work[i] = work[i] * (1.0 - t) + work[i + 1] * t;
+        }
+    }
+    work[0]
+}
+
+impl CameraPath {
+    pub fn new(interpolation: PathInterpolation) -> Self {
+        CameraPath {
+            keyframes: Vec::new(),
+            interpolation,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.keyframes.is_empty()
+    }
+
+    pub fn start_time(&self) -> f32 {
+        self.keyframes.first().map_or(0.0, |k| k.time)
+    }
+
+    pub fn end_time(&self) -> f32 {
+        self.keyframes.last().map_or(0.0, |k| k.time)
+    }
+
+    /// Inserta un fotograma manteniendo el orden; si ya hay uno en ese
+    /// instante lo reemplaza
+    pub fn add_keyframe(&mut self, keyframe: Keyframe) {
+        match self
+            .keyframes
+            .iter()
+            .position(|k| k.time >= keyframe.time - 1e-4)
+        {
+            Some(i) if (self.keyframes[i].time - keyframe.time).abs() < 1e-4 => {
+                self.keyframes[i] = keyframe;
+            }
+            Some(i) => self.keyframes.insert(i, keyframe),
+            None => self.keyframes.push(keyframe),
+        }
+    }
+
+    fn catmull_rom(&self, time: f32, value: impl Fn(&Keyframe) -> Vec3<f32>) -> Vec3<f32> {
+        let keys = &self.keyframes;
+        let segment = keys
+            .windows(2)
+            .position(|w| time <= w[1].time)
+            .unwrap_or(keys.len() - 2);
+
+        let k1 = &keys[segment];
+        let k2 = &keys[segment + 1];
+        let span = (k2.time - k1.time).max(1e-4);
+
+        // Tangente por diferencias finitas respetando tiempos no uniformes
+        let tangent = |i: usize| -> Vec3<f32> {
+            let prev = &keys[i.saturating_sub(1)];
+            let next = &keys[(i + 1).min(keys.len() - 1)];
+            let dt = (next.time - prev.time).max(1e-4);
+            (value(next) - value(prev)) / dt * span
+        };
+
+        let t = ((time - k1.time) / span).clamp(0.0, 1.0);
+        hermite(value(k1), tangent(segment), value(k2), tangent(segment + 1), t)
+    }
+
+    /// Posición y objetivo en el instante `time`; fuera del rango se mantiene
+    /// el primer o el último fotograma
+    pub fn sample(&self, time: f32) -> Option<(Vec3<f32>, Vec3<f32>)> {
+        match self.keyframes.len() {
+            0 => None,
+            1 => Some((self.keyframes[0].position, self.keyframes[0].target)),
+            _ => {
+                let time = time.clamp(self.start_time(), self.end_time());
+                match self.interpolation {
+                    PathInterpolation::CatmullRom => Some((
+                        self.catmull_rom(time, |k| k.position),
+                        self.catmull_rom(time, |k| k.target),
+                    )),
+                    PathInterpolation::Bezier => {
+                        let span = (self.end_time() - self.start_time()).max(1e-4);
+                        let t = (time - self.start_time()) / span;
+                        let positions: Vec<_> = self.keyframes.iter().map(|k| k.position).collect();
+                        let targets: Vec<_> = self.keyframes.iter().map(|k| k.target).collect();
+                        Some((de_casteljau(&positions, t), de_casteljau(&targets, t)))
+                    }
+                }
+            }
+        }
+    }
+
+    /// Formato de texto: una línea `interpolation <nombre>` y una línea
+    /// `key t px py pz tx ty tz` por fotograma. `#` inicia un comentario.
+    pub fn save(&self, filename: &str) -> Result<(), String> {
+        let mut text = String::from("# Space Travel camera path\n");
+        text.push_str(&format!("interpolation {}\n", self.interpolation.name()));
+        for k in &self.keyframes {
+            text.push_str(&format!(
+                "key {} {} {} {} {} {} {}\n",
+                k.time, k.position.x, k.position.y, k.position.z, k.target.x, k.target.y, k.target.z,
+            ));
+        }
+        fs::write(filename, text)
+            .map_err(|e| format!("No se pudo guardar el recorrido {}: {}", filename, e))
+    }
+
+    pub fn load(filename: &str) -> Result<Self, String> {
+        let text = fs::read_to_string(filename)
+            .map_err(|e| format!("No se pudo abrir el recorrido {}: {}", filename, e))?;
+
+        let mut path = CameraPath::new(PathInterpolation::CatmullRom);
+        for (number, line) in text.lines().enumerate() {
+            let line = line.split('#').next().unwrap_or("").trim();
+            let parts: Vec<&str> = line.split_whitespace().collect();
+            if parts.is_empty() {
+                continue;
+            }
+
+            match parts[0] {
+                "interpolation" if parts.len() == 2 => {
+                    path.interpolation = PathInterpolation::from_name(parts[1]).ok_or_else(|| {
+                        format!("{}:{}: interpolación desconocida '{}'", filename, number + 1, parts[1])
+                    })?;
+                }
+                "key" if parts.len() == 8 => {
+                    let values = parts[1..]
+                        .iter()
+                        .map(|p| p.parse::<f32>())
+                        .collect::<Result<Vec<f32>, _>>()
+                        .map_err(|e| format!("{}:{}: {}", filename, number + 1, e))?;
+                    if values.iter().any(|v| !v.is_finite()) {
+                        return Err(format!("{}:{}: valor no finito", filename, number + 1));
+                    }
+                    path.add_keyframe(Keyframe {
+                        time: values[0],
+                        position: Vec3::new(values[1], values[2], values[3]),
+                        target: Vec3::new(values[4], values[5], values[6]),
+                    });
+                }
+                _ => return Err(format!("{}:{}: línea no reconocida", filename, number + 1)),
+            }
+        }
+        Ok(path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn load_text(name: &str, text: &str) -> Result<CameraPath, String> {
+        let path = std::env::temp_dir().join(format!("camera-path-test-{}-{}.txt", std::process::id(), name));
+        fs::write(&path, text).unwrap();
+        let result = CameraPath::load(path.to_str().unwrap());
+        fs::remove_file(&path).ok();
+        result
+    }
+
+    fn key(time: f32, position: [f32; 3], target: [f32; 3]) -> Keyframe {
+        Keyframe {
+            time,
+            position: Vec3::from(position),
+            target: Vec3::from(target),
+        }
+    }
+
+    fn sample_path(interpolation: PathInterpolation) -> CameraPath {
+        let mut path = CameraPath::new(interpolation);
+        // Tiempos no uniformes y añadidos fuera de orden
+        path.add_keyframe(key(3.0, [4.0, -1.0, 2.0], [0.0, 1.0, 0.0]));
+        path.add_keyframe(key(0.0, [0.0, 0.0, 0.0], [1.0, 0.0, 0.0]));
+        path.add_keyframe(key(1.0, [2.0, 1.0, 0.5], [0.0, 0.0, 1.0]));
+        path.add_keyframe(key(5.5, [-3.0, 2.0, 6.0], [2.0, 2.0, 2.0]));
+        path
+    }
+
+    fn assert_close(a: Vec3<f32>, b: Vec3<f32>) {
+        assert!((a - b).norm() < 1e-4, "{:?} != {:?}", a, b);
+    }
+
+    #[test]
+    fn catmull_rom_passes_through_every_keyframe() {
+        let path = sample_path(PathInterpolation::CatmullRom);
+        for k in &path.keyframes {
+            let (position, target) = path.sample(k.time).unwrap();
+            assert_close(position, k.position);
+            assert_close(target, k.target);
+        }
+    }
+
+    #[test]
+    fn bezier_touches_only_the_endpoints() {
+        let path = sample_path(PathInterpolation::Bezier);
+        let first = path.keyframes[0];
+        let last = path.keyframes[path.keyframes.len() - 1];
+        assert_close(path.sample(first.time).unwrap().0, first.position);
+        assert_close(path.sample(first.time).unwrap().1, first.target);
+        assert_close(path.sample(last.time).unwrap().0, last.position);
+        assert_close(path.sample(last.time).unwrap().1, last.target);
+
+        // Los fotogramas interiores son solo puntos de control
+        let inner = path.keyframes[1];
+        assert!((path.sample(inner.time).unwrap().0 - inner.position).norm() > 0.1);
+    }
+
+    #[test]
+    fn sampling_outside_the_range_holds_the_ends() {
+        for interpolation in [PathInterpolation::CatmullRom, PathInterpolation::Bezier] {
+            let path = sample_path(interpolation);
+            let first = path.keyframes[0];
+            let last = path.keyframes[path.keyframes.len() - 1];
+            for time in [-10.0, first.time - 0.01] {
+                assert_close(path.sample(time).unwrap().0, first.position);
+            }
+            for time in [last.time + 0.01, 1e6] {
+                assert_close(path.sample(time).unwrap().0, last.position);
+            }
+        }
+        assert!(CameraPath::new(PathInterpolation::CatmullRom).sample(0.0).is_none());
+    }
+
+    #[test]
+    fn save_and_load_round_trip() {
+        for interpolation in [PathInterpolation::CatmullRom, PathInterpolation::Bezier] {
+            let path = sample_path(interpolation);
+            let filename = std::env::temp_dir()
+                .join(format!("camera-path-test-{}-ida-vuelta-{}.txt", std::process::id(), interpolation.name()));
+            let filename = filename.to_str().unwrap();
+            path.save(filename).unwrap();
+            let loaded = CameraPath::load(filename);
+            fs::remove_file(filename).ok();
+            let loaded = loaded.unwrap();
+
+            assert_eq!(loaded.interpolation, interpolation);
+            assert_eq!(loaded.keyframes.len(), path.keyframes.len());
+            for (a, b) in loaded.keyframes.iter().zip(&path.keyframes) {
+                assert_eq!((a.time, a.position, a.target), (b.time, b.position, b.target));
+            }
+            for time in [0.0, 0.4, 2.2, 5.0] {
+                assert_eq!(loaded.sample(time), path.sample(time));
+            }
+        }
+    }
+
+    #[test]
+    fn loads_keyframes_in_time_order() {
+        let path = load_text("orden", "interpolation bezier\nkey 2 1 1 1 0 0 0\nkey 0 0 0 0 0 0 0\n").unwrap();
+        assert_eq!(path.interpolation, PathInterpolation::Bezier);
+        assert_eq!((path.start_time(), path.end_time()), (0.0, 2.0));
+    }
+
+    #[test]
+    fn rejects_non_finite_values() {
+        for (name, line) in [("nan", "key NaN 0 0 0 0 0 0"), ("inf", "key inf 0 0 0 0 0 0"), ("pos", "key 1 0 -inf 0 0 0 0")] {
+            let error = load_text(name, &format!("{}\n", line)).unwrap_err();
+            assert!(error.ends_with(":1: valor no finito"), "{}", error);
+        }
+    }
+}
@@ -0,0 +1,220 @@
+use nalgebra::{Vector3 as Vec3, Unit, UnitQuaternion};
+use std::f32::consts::PI;
+use crate::planet::BoundingSphere;
+
+// Margen extra alrededor de cada cuerpo que debe respetar la trayectoria
+const PATH_CLEARANCE: f32 = 1.5;
+const PATH_SAMPLES: usize = 24;
+const PATH_ITERATIONS: usize = 6;
+// Giro entre orientaciones a partir del cual no se usa slerp (170 grados)
+const HALF_TURN_THRESHOLD: f32 = 170.0 * PI / 180.0;
+
+/// Posición y orientación de la cámara en un instante
+#[derive(Debug, Clone, Copy)]
+pub struct CameraPose {
+    pub position: Vec3<f32>,
+    pub orientation: UnitQuaternion<f32>,
+    /// Distancia al punto que se mira, para reconstruir el objetivo
+    pub target_distance: f32,
+}
+
+impl CameraPose {
+    pub fn look_at(position: Vec3<f32>, target: Vec3<f32>, up: Vec3<f32>) -> Self {
+        let forward = target - position;
+        CameraPose {
+            position,
+            orientation: UnitQuaternion::face_towards(&-forward, &up),
+            target_distance: forward.norm().max(1e-3),
+        }
+    }
+
+    pub fn target(&self) -> Vec3<f32> {
+        self.position + self.orientation * Vec3::new(0.0, 0.0, -self.target_distance)
+    }
+
+    pub fn up(&self) -> Vec3<f32> {
+        self.orientation * Vec3::y()
+    }
+}
+
+/// Curva de aceleración y frenado suave (cúbica de entrada y salida)
+pub fn ease_in_out(t: f32) -> f32 {
+    let t = t.clamp(0.0, 1.0);
+    if t < 0.5 {
+        4.0 * t * t * t
+    } else {
+        1.0 - (-2.0 * t + 2.0).powi(3) * 0.5
+    }
+}
+
+fn bezier(a: Vec3<f32>, control: Vec3<f32>, b: Vec3<f32>, t: f32) -> Vec3<f32> {
+    let u = 1.0 - t;
+    a * (u * u) + control * (2.0 * u * t) + b * (t * t)
+}
+
+/// Punto de control de una Bézier cuadrática entre `a` y `b` que rodea los cuerpos.
+/// Se empieza por la recta y se empuja el control hacia fuera de la esfera más invadida.
+fn avoiding_control_point(a: Vec3<f32>, b: Vec3<f32>, obstacles: &[BoundingSphere]) -> Vec3<f32> {
+    let mut control = (a + b) * 0.5;
+
+    // Los cuerpos que ya contienen un extremo no se pueden esquivar
+    let relevant: Vec<&BoundingSphere> = obstacles
+        .iter()
+        .filter(|o| {
+            let clearance = o.radius * PATH_CLEARANCE;
+            (a - o.center).norm() > clearance && (b - o.center).norm() > clearance
+        })
+        .collect();
+
+    for _ in 0..PATH_ITERATIONS {
+        let mut worst: Option<(Vec3<f32>, f32)> = None;
+
+        for i in 1..PATH_SAMPLES {
+            let point = bezier(a, control, b, i as f32 / PATH_SAMPLES as f32);
+            for obstacle in &relevant {
+                let offset = point - obstacle.center;
+                let distance = offset.norm();
+                let depth = obstacle.radius * PATH_CLEARANCE - distance;
+                if depth > worst.map_or(0.0, |w| w.1) {
+                    let direction = if distance > 1e-4 {
+                        offset / distance
+                    } else {
+                        // Trayecto directo a través del centro: salir por arriba
+                        let along = b - a;
+                        let side = along.cross(&Vec3::y());
+                        if side.norm() > 1e-4 { side.normalize() } else { Vec3::y() }
+                    };
+                    worst = Some((direction, depth));
+                }
+            }
+        }
+
+        match worst {
+            // En el punto medio el control pesa 0.5, así que se desplaza el doble
+            Some((direction, depth)) => control += direction * depth * 2.0,
+            None => break,
+        }
+    }
+
+    control
+}
+
+/// Orientación entre `from` y `to`. Cerca de media vuelta el eje de slerp
+/// queda mal definido y la cámara puede rodar o cambiar de lado, así que se
+/// gira media vuelta alrededor del eje vertical o lateral de `from` (el que
+/// deje menos giro pendiente) y lo que falta se reparte en la transición.
+fn interpolate_orientation(from: &UnitQuaternion<f32>, to: &UnitQuaternion<f32>, t: f32) -> UnitQuaternion<f32> {
+    let delta = to * from.inverse();
+    if delta.angle() < HALF_TURN_THRESHOLD {
+        return from.try_slerp(to, t, 1e-6).unwrap_or(*to);
+    }
+
+    let (axis, rest) = [Vec3::y(), Vec3::x()]
+        .into_iter()
+        .map(|local| {
+            let axis = Unit::new_normalize(from * local);
+            // Girar hacia el mismo lado que el giro directo
+            let axis = match delta.axis() {
+                Some(direct) if direct.dot(&axis) < 0.0 => -axis,
+                _ => axis,
+            };
+            let half_turn = UnitQuaternion::from_axis_angle(&axis, PI);
+            (axis, to * (half_turn * from).inverse())
+        })
+        .min_by(|a, b| a.1.angle().total_cmp(&b.1.angle()))
+        .expect("hay dos ejes candidatos");
+    rest.powf(t) * UnitQuaternion::from_axis_angle(&axis, PI * t) * from
+}
+
+/// Animación de la cámara desde una pose inicial hacia una pose objetivo que
+/// puede moverse mientras dura (por ejemplo, un planeta en órbita).
+pub struct CameraTransition {
+    from: CameraPose,
+    elapsed: f32,
+    duration: f32,
+}
+
+impl CameraTransition {
+    pub fn new(from: CameraPose, duration: f32) -> Self {
+        CameraTransition {
+            from,
+            elapsed: 0.0,
+            duration: duration.max(1e-3),
+        }
+    }
+
+    pub fn advance(&mut self, dt: f32) {
+        self.elapsed += dt;
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.elapsed >= self.duration
+    }
+
+    /// Pose intermedia en el instante actual hacia `goal`
+    pub fn sample(&self, goal: &CameraPose, obstacles: &[BoundingSphere]) -> CameraPose {
+        let t = ease_in_out(self.elapsed / self.duration);
+
+        let control = avoiding_control_point(self.from.position, goal.position, obstacles);
+        let position = bezier(self.from.position, control, goal.position, t);
+
+        let orientation = interpolate_orientation(&self.from.orientation, &goal.orientation, t);
+
+        CameraPose {
+            position,
+            orientation,
+            target_distance: self.from.target_distance
+                + (goal.target_distance - self.from.target_distance) * t,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn forward(orientation: &UnitQuaternion<f32>) -> Vec3<f32> {
+        orientation * Vec3::new(0.0, 0.0, -1.0)
+    }
+
+    #[test]
+    fn half_turn_rotates_about_the_vertical() {
+        let from = CameraPose::look_at(Vec3::zeros(), Vec3::new(0.0, 0.0, -1.0), Vec3::y());
+        let to = CameraPose::look_at(Vec3::zeros(), Vec3::new(0.0, 0.0, 1.0), Vec3::y());
+
+        for i in 0..=10 {
+            let orientation = interpolate_orientation(&from.orientation, &to.orientation, i as f32 / 10.0);
+            // Sin rodar ni cabecear: la vertical se mantiene durante todo el giro
+            assert!((orientation * Vec3::y() - Vec3::y()).norm() < 1e-4, "t = {}", i);
+        }
+        let middle = forward(&interpolate_orientation(&from.orientation, &to.orientation, 0.5));
+        assert!(middle.z.abs() < 1e-4 && (middle.x.abs() - 1.0).abs() < 1e-4, "{:?}", middle);
+        let end = interpolate_orientation(&from.orientation, &to.orientation, 1.0);
+        assert!(end.angle_to(&to.orientation) < 1e-4);
+    }
+
+    #[test]
+    fn nearly_half_turn_reaches_the_goal_smoothly() {
+        let from = CameraPose::look_at(Vec3::zeros(), Vec3::new(0.0, 0.0, -1.0), Vec3::y());
+        // Media vuelta y además mirar un poco hacia arriba
+        let to = CameraPose::look_at(Vec3::zeros(), Vec3::new(0.05, 0.2, 1.0), Vec3::y());
+        assert!(from.orientation.angle_to(&to.orientation) > HALF_TURN_THRESHOLD);
+
+        let mut previous = from.orientation;
+        for i in 1..=20 {
+            let orientation = interpolate_orientation(&from.orientation, &to.orientation, i as f32 / 20.0);
+            // Cada paso gira poco más de 1/20 de media vuelta
+            assert!(orientation.angle_to(&previous) < 0.2, "salto en t = {}", i);
+            previous = orientation;
+        }
+        assert!(previous.angle_to(&to.orientation) < 1e-4);
+    }
+
+    #[test]
+    fn small_turns_use_slerp() {
+        let from = UnitQuaternion::from_axis_angle(&Vec3::y_axis(), 0.0);
+        let to = UnitQuaternion::from_axis_angle(&Vec3::y_axis(), 1.0);
+        let middle = interpolate_orientation(&from, &to, 0.5);
+        assert!(middle.angle_to(&UnitQuaternion::from_axis_angle(&Vec3::y_axis(), 0.5)) < 1e-5);
+    }
+}
@@ -0,0 +1,352 @@
+use crate::overlay::{OverlayLayer, OverlayLayers};
+use crate::camera_path::PathInterpolation;
+use crate::hud::ScreenCorner;
+use crate::camera::CameraMode;
+use crate::clock::{MIN_RATE, MAX_RATE};
+use crate::nbody::{Integrator, Scenario};
+use crate::obj_loader::ObjMode;
+use crate::sphere::{SphereKind, MAX_DETAIL};
+
+pub const USAGE: &str = "\
+Uso: proyecto3-space-travel [opciones]
+
+Opciones:
+  --overlays LISTA   Capas visibles al inicio, separadas por comas
+                     (grid, axes, orbits, labels, bounds, all, none)
+  --show CAPA        Activa una capa
+  --hide CAPA        Desactiva una capa
+  --follow N         Cuerpo seleccionado al inicio (0 = sol)
+  --camera-mode MODO Modo de cámara al arrancar
+                     (orbit, follow, free, chase)
+  --follow-offset D,H
+                     Distancia y altura de la cámara de seguimiento
+  --min-altitude [N=]ALT
+                     Distancia mínima de la cámara a la superficie del
+                     cuerpo N, o de todos si no se indica (repetible)
+  --transition SEG   Duración de los vuelos entre vistas (0 = instantáneo)
+  --camera-path ARCHIVO
+                     Recorrido de cámara a cargar y donde se guardan los
+                     fotogramas clave (por defecto camera_path.txt)
+  --path-interpolation catmull-rom|bezier
+                     Interpolación de un recorrido nuevo
+  --play             Reproduce el recorrido al arrancar
+  --ship-gravity     Los cuerpos atraen a la nave desde el arranque
+  --nbody ESCENARIO  Gravedad mutua entre los cuerpos en lugar de órbitas
+                     fijas (circular, unstable, flyby)
+  --integrator NOMBRE
+                     Integrador del modo de N cuerpos
+                     (verlet, leapfrog, rk4; por defecto verlet)
+  --asteroids N      Rocas del cinturón de asteroides (por defecto 3000;
+                     0 lo quita)
+  --sphere uv|ico|cube
+                     Malla generada para los cuerpos (por defecto ico)
+  --sphere-detail N  Subdivisiones de la malla de los cuerpos más cercanos,
+                     de 0 a 7 (por defecto 5); los lejanos usan menos
+  --no-lod           Dibuja todos los cuerpos con --sphere-detail
+  --sphere-obj ARCHIVO
+                     Usa un modelo OBJ como malla de los cuerpos en lugar
+                     de generarla
+  --ship-model ARCHIVO
+                     Modelo de la nave: .obj, .gltf o .glb
+                     (por defecto ship.obj si existe, o la nave que
+                     incluye el programa)
+  --lenient-obj      Carga los modelos OBJ saltando las líneas mal formadas
+                     (con un aviso) en lugar de fallar
+  --time-scale X     Factor de velocidad de la simulación, entre 1/16 y 64
+                     (negativo = atrás)
+  --start-time SEG   Instante de la simulación al arrancar
+  --hud ESQUINA      Muestra el HUD en top-left, top-right, bottom-left o
+                     bottom-right; 'off' lo oculta. Sin esta opción se ve
+                     en la ventana y no en los cuadros exportados
+  --minimap ESQUINA  Igual que --hud para el minimapa (por defecto
+                     bottom-right)
+  --keys ARCHIVO     Asignación de teclas a cargar
+                     (por defecto keybindings.cfg si existe)
+  --export DIR       Renderiza sin ventana y guarda cuadros PPM en DIR
+  --frames N         Cuadros a exportar (por defecto 300)
+  -h, --help         Muestra esta ayuda";
+
+/// Opciones de arranque leídas de la línea de comandos
+#[derive(Debug, Clone)]
+pub struct CliOptions {
+    pub overlays: OverlayLayers,
+    pub follow_body: Option<usize>,
+    pub follow_offset: Option<(f32, f32)>,
+    pub camera_mode: CameraMode,
+    pub transition_duration: Option<f32>,
+    /// Altitud mínima por cuerpo (`None` = todos), en el orden indicado
+    pub min_altitudes: Vec<(Option<usize>, f32)>,
+    pub camera_path: Option<String>,
+    pub path_interpolation: PathInterpolation,
+    pub play_path: bool,
+    pub ship_gravity: bool,
+    pub nbody: Option<Scenario>,
+    pub integrator: Integrator,
+    pub asteroid_count: usize,
+    pub obj_mode: ObjMode,
+    pub sphere_kind: SphereKind,
+    pub sphere_detail: usize,
+    /// Niveles de detalle según el tamaño en pantalla
+    pub sphere_lod: bool,
+    pub sphere_obj: Option<String>,
+    /// `None` usa ship.obj si existe y si no la copia incluida
+    pub ship_model: Option<String>,
+    pub time_scale: f32,
+    pub start_time: f32,
+    pub hud_corner: ScreenCorner,
+    /// `None` deja el valor por defecto de cada modo de ejecución
+    pub hud_visible: Option<bool>,
+    pub minimap_corner: ScreenCorner,
+    pub minimap_visible: Option<bool>,
+    pub key_bindings: Option<String>,
+    pub export_dir: Option<String>,
+    pub export_frames: usize,
+    pub show_help: bool,
+}
+
+impl Default for CliOptions {
+    fn default() -> Self {
+        CliOptions {
+            overlays: OverlayLayers::default(),
+            follow_body: None,
+            follow_offset: None,
+            camera_mode: CameraMode::Orbit,
+            transition_duration: None,
+            min_altitudes: Vec::new(),
+            camera_path: None,
+            path_interpolation: PathInterpolation::CatmullRom,
+            play_path: false,
+            ship_gravity: false,
+            nbody: None,
+            integrator: Integrator::VelocityVerlet,
+            asteroid_count: 3000,
+            obj_mode: ObjMode::Strict,
+            sphere_kind: SphereKind::Ico,
+            sphere_detail: 5,
+            sphere_lod: true,
+            sphere_obj: None,
+            ship_model: None,
+            time_scale: 1.0,
+            start_time: 0.0,
+            hud_corner: ScreenCorner::TopLeft,
+            hud_visible: None,
+            minimap_corner: ScreenCorner::BottomRight,
+            minimap_visible: None,
+            key_bindings: None,
+            export_dir: None,
+            export_frames: 300,
+            show_help: false,
+        }
+    }
+}
+
+fn parse_layer(name: &str) -> Result<OverlayLayer, String> {
+    OverlayLayer::from_name(name.trim())
+        .ok_or_else(|| format!("Capa desconocida: '{}'", name))
+}
+
+fn parse_layer_list(list: &str) -> Result<OverlayLayers, String> {
+    let mut layers = OverlayLayers::none();
+    for name in list.split(',').map(str::trim).filter(|n| !n.is_empty()) {
+        match name {
+            "all" => layers = OverlayLayers::all(),
+            "none" => layers = OverlayLayers::none(),
+            _ => layers.set(parse_layer(name)?, true),
+        }
+    }
+    Ok(layers)
+}
+
+fn parse_number<T: std::str::FromStr>(value: &str, flag: &str) -> Result<T, String> {
+    value
+        .trim()
+        .parse()
+        .map_err(|_| format!("Valor inválido para {}: '{}'", flag, value))
+}
+
+/// Número real finito: `NaN` e `inf` se leen como f32 pero romperían la simulación
+fn parse_float(value: &str, flag: &str) -> Result<f32, String> {
+    let number: f32 = parse_number(value, flag)?;
+    if !number.is_finite() {
+        return Err(format!("{} necesita un número finito: '{}'", flag, value));
+    }
+    Ok(number)
+}
+
+fn parse_pair(value: &str, flag: &str) -> Result<(f32, f32), String> {
+    let (a, b) = value
+        .split_once(',')
+        .ok_or_else(|| format!("{} espera dos valores separados por coma", flag))?;
+    Ok((parse_float(a, flag)?, parse_float(b, flag)?))
+}
+
+/// Esquina de pantalla, o `None` para "off"
+fn parse_corner(value: &str) -> Result<Option<ScreenCorner>, String> {
+    match value.trim() {
+        "off" => Ok(None),
+        name => ScreenCorner::from_name(name)
+            .map(Some)
+            .ok_or_else(|| format!("Esquina desconocida: '{}'", name)),
+    }
+}
+
+impl CliOptions {
+    /// Interpreta los argumentos (sin incluir el nombre del programa)
+    pub fn parse<I: IntoIterator<Item = String>>(args: I) -> Result<Self, String> {
+        let mut options = CliOptions::default();
+        let mut args = args.into_iter();
+
+        while let Some(arg) = args.next() {
+            // Permitir tanto "--opcion valor" como "--opcion=valor"
+            let (flag, inline_value) = match arg.split_once('=') {
+                Some((flag, value)) => (flag.to_string(), Some(value.to_string())),
+                None => (arg.clone(), None),
+            };
+            let mut value = || {
+                inline_value
+                    .clone()
+                    .or_else(|| args.next())
+                    .ok_or_else(|| format!("Falta el valor para {}", flag))
+            };
+
+            match flag.as_str() {
+                "--overlays" => options.overlays = parse_layer_list(&value()?)?,
+                "--show" => options.overlays.set(parse_layer(&value()?)?, true),
+                "--hide" => options.overlays.set(parse_layer(&value()?)?, false),
+                "--follow" => options.follow_body = Some(parse_number(&value()?, &flag)?),
+                "--camera-mode" => {
+                    let name = value()?;
+                    options.camera_mode = CameraMode::from_name(&name)
+                        .ok_or_else(|| format!("Modo de cámara desconocido: '{}'", name))?;
+                }
+                "--follow-offset" => options.follow_offset = Some(parse_pair(&value()?, &flag)?),
+                "--min-altitude" => {
+                    let value = value()?;
+                    let entry = match value.split_once('=') {
+                        Some((body, altitude)) => {
+                            (Some(parse_number(body, &flag)?), parse_float(altitude, &flag)?)
+                        }
+                        None => (None, parse_float(&value, &flag)?),
+                    };
+                    options.min_altitudes.push(entry);
+                }
+                "--transition" => options.transition_duration = Some(parse_float(&value()?, &flag)?),
+                "--camera-path" => options.camera_path = Some(value()?),
+                "--path-interpolation" => {
+                    let name = value()?;
+                    options.path_interpolation = PathInterpolation::from_name(&name)
+                        .ok_or_else(|| format!("Interpolación desconocida: '{}'", name))?;
+                }
+                "--play" => options.play_path = true,
+                "--ship-gravity" => options.ship_gravity = true,
+                "--lenient-obj" => options.obj_mode = ObjMode::Lenient,
+                "--sphere" => {
+                    let name = value()?;
+                    options.sphere_kind = SphereKind::from_name(&name)
+                        .ok_or_else(|| format!("Tipo de esfera desconocido: '{}'", name))?;
+                }
+                "--sphere-detail" => {
+                    let detail = parse_number(&value()?, &flag)?;
+                    if detail > MAX_DETAIL {
+                        return Err(format!("{}: el máximo es {}", flag, MAX_DETAIL));
+                    }
+                    options.sphere_detail = detail;
+                }
+                "--no-lod" => options.sphere_lod = false,
+                "--sphere-obj" => options.sphere_obj = Some(value()?),
+                "--ship-model" => options.ship_model = Some(value()?),
+                "--nbody" => {
+                    let name = value()?;
+                    options.nbody = Some(
+                        Scenario::from_name(&name)
+                            .ok_or_else(|| format!("Escenario desconocido: '{}'", name))?,
+                    );
+                }
+                "--integrator" => {
+                    let name = value()?;
+                    options.integrator = Integrator::from_name(&name)
+                        .ok_or_else(|| format!("Integrador desconocido: '{}'", name))?;
+                }
+                "--asteroids" => options.asteroid_count = parse_number(&value()?, &flag)?,
+                "--time-scale" => {
+                    let value = value()?;
+                    let scale = parse_float(&value, &flag)?;
+                    if !(MIN_RATE..=MAX_RATE).contains(&scale.abs()) {
+                        return Err(format!(
+                            "{} debe estar entre {} y {} en valor absoluto: '{}'",
+                            flag, MIN_RATE, MAX_RATE, value
+                        ));
+                    }
+                    options.time_scale = scale;
+                }
+                "--start-time" => options.start_time = parse_float(&value()?, &flag)?,
+                "--hud" => match parse_corner(&value()?)? {
+                    Some(corner) => {
+                        options.hud_corner = corner;
+                        options.hud_visible = Some(true);
+                    }
+                    None => options.hud_visible = Some(false),
+                },
+                "--minimap" => match parse_corner(&value()?)? {
+                    Some(corner) => {
+                        options.minimap_corner = corner;
+                        options.minimap_visible = Some(true);
+                    }
+                    None => options.minimap_visible = Some(false),
+                },
+                "--keys" => options.key_bindings = Some(value()?),
+                "--export" => options.export_dir = Some(value()?),
+                "--frames" => options.export_frames = parse_number(&value()?, &flag)?,
+                "-h" | "--help" => options.show_help = true,
+                _ => return Err(format!("Opción desconocida: '{}'", arg)),
+            }
+        }
+
+        Ok(options)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<CliOptions, String> {
+        CliOptions::parse(args.iter().map(|arg| arg.to_string()))
+    }
+
+    #[test]
+    fn time_scale_outside_the_clock_limits_is_rejected() {
+        for value in ["0", "0.05", "-0.05", "65", "-100"] {
+            let error = parse(&["--time-scale", value]).unwrap_err();
+            assert!(error.contains("entre"), "{}: {}", value, error);
+        }
+        for value in ["0.0625", "-0.0625", "64", "-64"] {
+            assert_eq!(parse(&["--time-scale", value]).unwrap().time_scale, value.parse::<f32>().unwrap());
+        }
+    }
+
+    #[test]
+    fn float_flags_reject_non_finite_values() {
+        for args in [
+            ["--time-scale", "NaN"],
+            ["--time-scale", "-inf"],
+            ["--start-time", "inf"],
+            ["--transition", "nan"],
+            ["--follow-offset", "3,inf"],
+            ["--follow-offset", "NaN,1"],
+            ["--min-altitude", "NaN"],
+            ["--min-altitude", "2=infinity"],
+        ] {
+            let error = parse(&args).unwrap_err();
+            assert!(error.contains("finito"), "{:?}: {}", args, error);
+        }
+    }
+
+    #[test]
+    fn float_flags_accept_finite_values() {
+        let options = parse(&["--time-scale", "-2.5", "--start-time", "1e3", "--follow-offset", "3,-1"]).unwrap();
+        assert_eq!(options.time_scale, -2.5);
+        assert_eq!(options.start_time, 1000.0);
+        assert_eq!(options.follow_offset, Some((3.0, -1.0)));
+    }
+}
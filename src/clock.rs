@@ -0,0 +1,132 @@
+/// Límites de la aceleración temporal (en valor absoluto)
+pub const MIN_RATE: f32 = 1.0 / 16.0;
+pub const MAX_RATE: f32 = 64.0;
+
+/// Reloj de la simulación. Avanza con el tiempo real multiplicado por un
+/// factor que puede ser negativo, y se puede pausar o mover a mano.
+#[derive(Debug, Clone, Copy)]
+pub struct SimClock {
+    time: f32,
+    // Instante antes del último avance, para interpolar al dibujar
+    previous_time: f32,
+    rate: f32,
+    paused: bool,
+    // Pasos pendientes de avanzar mientras el reloj está en pausa
+    pending_steps: u32,
+}
+
+impl Default for SimClock {
+    fn default() -> Self {
+        SimClock::new(0.0, 1.0)
+    }
+}
+
+impl SimClock {
+    pub fn new(time: f32, rate: f32) -> Self {
+        SimClock {
+            time,
+            previous_time: time,
+            rate,
+            paused: false,
+            pending_steps: 0,
+        }
+    }
+
+    pub fn time(&self) -> f32 {
+        self.time
+    }
+
+    /// Avanza el reloj con `real_dt` segundos reales y devuelve el tiempo
+    /// simulado transcurrido (cero en pausa, salvo que se pidiera un paso)
+    pub fn advance(&mut self, real_dt: f32) -> f32 {
+        let dt = if !self.paused {
+            real_dt * self.rate
+        } else if self.pending_steps > 0 {
+            self.pending_steps -= 1;
+            real_dt * self.rate
+        } else {
+            0.0
+        };
+        self.previous_time = self.time;
+        self.time += dt;
+        dt
+    }
+
+    /// Instante intermedio entre los dos últimos pasos; `alpha` es la fracción
+    /// del paso fijo que queda acumulada
+    pub fn interpolated_time(&self, alpha: f32) -> f32 {
+        self.previous_time + (self.time - self.previous_time) * alpha
+    }
+
+    pub fn toggle_pause(&mut self) {
+        self.paused = !self.paused;
+        self.pending_steps = 0;
+    }
+
+    /// Pausa el reloj y avanza exactamente un cuadro en la siguiente llamada
+    /// a `advance`
+    pub fn step(&mut self) {
+        self.paused = true;
+        self.pending_steps += 1;
+    }
+
+    pub fn speed_up(&mut self) {
+        self.set_rate(self.rate * 2.0);
+    }
+
+    pub fn slow_down(&mut self) {
+        self.set_rate(self.rate / 2.0);
+    }
+
+    pub fn reverse(&mut self) {
+        self.rate = -self.rate;
+    }
+
+    /// Cambia el factor conservando el signo y dentro de los límites; un
+    /// factor NaN se ignora
+    pub fn set_rate(&mut self, rate: f32) {
+        if rate.is_nan() {
+            return;
+        }
+        let magnitude = rate.abs().clamp(MIN_RATE, MAX_RATE);
+        self.rate = if rate < 0.0 { -magnitude } else { magnitude };
+    }
+
+    pub fn jump_to(&mut self, time: f32) {
+        self.time = time;
+        self.previous_time = time;
+    }
+
+    /// Texto corto para mostrar en pantalla, p. ej. "T 12.3 X2" o "T 4.0 X-1 PAUSA"
+    pub fn status(&self) -> String {
+        let status = format!("T {:.1} X{}", self.time, format_rate(self.rate));
+        if self.paused {
+            status + " PAUSA"
+        } else {
+            status
+        }
+    }
+}
+
+fn format_rate(rate: f32) -> String {
+    if rate.abs() >= 1.0 {
+        format!("{}", rate)
+    } else {
+        format!("{:.3}", rate).trim_end_matches('0').to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn nan_rates_are_ignored() {
+        let mut clock = SimClock::new(0.0, 2.0);
+        clock.set_rate(f32::NAN);
+        assert_eq!(clock.advance(1.0), 2.0);
+        clock.set_rate(f32::NEG_INFINITY);
+        assert_eq!(clock.advance(1.0), -MAX_RATE);
+        assert!(clock.time().is_finite());
+    }
+}
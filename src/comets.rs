@@ -0,0 +1,126 @@
+use nalgebra::{Matrix4, Vector3 as Vec3, Vector4};
+use crate::framebuffer::Framebuffer;
+use crate::planet::Planet;
+use crate::shaders::ShaderColor;
+use crate::render::{draw_glow, WIDTH, HEIGHT};
+
+// Distancia al sol a la que la actividad llega al máximo; más lejos cae con
+// el cuadrado de la distancia, como la luz que recibe el núcleo
+const FULL_ACTIVITY_DISTANCE: f32 = 7.0;
+// Por debajo de esta actividad el cometa no tiene cola
+const MIN_ACTIVITY: f32 = 0.04;
+const ION_PARTICLES: usize = 300;
+const DUST_PARTICLES: usize = 450;
+// Fracción de la cola que recorre cada partícula por segundo: el gas sale
+// mucho más rápido que el polvo
+const ION_FLOW: f32 = 0.45;
+const DUST_FLOW: f32 = 0.12;
+// Cuánto se curva la cola de polvo hacia atrás en la órbita, relativo a su largo
+const DUST_CURVE: f32 = 0.45;
+// Radio de cada partícula en unidades de mundo, y límites en píxeles
+const ION_SIZE: f32 = 0.08;
+const DUST_SIZE: f32 = 0.15;
+const MAX_PARTICLE_PIXELS: f32 = 6.0;
+
+/// Valor pseudoaleatorio en [0, 1) fijo para cada partícula y atributo
+fn hash(index: usize, salt: f32) -> f32 {
+    ((index as f32 * 12.9898 + salt * 78.233).sin() * 43758.547).fract().abs()
+}
+
+/// Aspecto de las colas de un cometa
+#[derive(Debug, Clone, Copy)]
+pub struct CometTail {
+    /// Largo de la cola de iones con la actividad al máximo
+    pub length: f32,
+    pub ion_color: ShaderColor,
+    pub dust_color: ShaderColor,
+}
+
+impl Default for CometTail {
+    fn default() -> Self {
+        CometTail {
+            length: 14.0,
+            ion_color: ShaderColor::new(0.35, 0.6, 1.0, 1.0),
+            dust_color: ShaderColor::new(1.0, 0.88, 0.68, 1.0),
+        }
+    }
+}
+
+/// Cuánto gas y polvo suelta el núcleo a esta distancia del sol (0 a 1)
+pub fn activity(distance_to_sun: f32) -> f32 {
+    (FULL_ACTIVITY_DISTANCE / distance_to_sun.max(1e-3)).powi(2).min(1.0)
+}
+
+/// Dibuja la coma y las dos colas de cada cuerpo con `tail`. Las partículas
+/// salen del tiempo y no de un estado guardado, así que las colas también
+/// funcionan al retroceder. Deben dibujarse después de todo lo opaco.
+pub fn render_tails(
+    framebuffer: &mut Framebuffer,
+    bodies: &[Planet],
+    sun_position: Vec3<f32>,
+    mesh_radius: f32,
+    view_proj: &Matrix4<f32>,
+    focal_length: f32,
+    time: f32,
+) {
+    let mut glow = |point: Vec3<f32>, size: f32, color: ShaderColor, intensity: f32| {
+        let clip = view_proj * Vector4::new(point.x, point.y, point.z, 1.0);
+        if clip.w <= 0.0 {
+            return;
+        }
+        let radius = (size * focal_length / clip.w).min(MAX_PARTICLE_PIXELS);
+        let x = (clip.x / clip.w + 1.0) * 0.5 * WIDTH as f32;
+        let y = (1.0 - clip.y / clip.w) * 0.5 * HEIGHT as f32;
+        let outside = x + radius < 0.0 || x - radius > WIDTH as f32 || y + radius < 0.0 || y - radius > HEIGHT as f32;
+        if !outside {
+            draw_glow(framebuffer, (x, y, clip.z / clip.w), radius, color, intensity);
+        }
+    };
+
+    for body in bodies {
+        let Some(tail) = &body.tail else { continue };
+        let offset = body.position - sun_position;
+        let distance = offset.norm();
+        let activity = activity(distance);
+        if activity < MIN_ACTIVITY || distance < 1e-3 {
+            continue;
+        }
+
+        // La cola de iones la empuja el viento solar: sale recta, lejos del sol
+        let away = offset / distance;
+        // El polvo se queda atrás en la órbita: la curva va contra la velocidad
+        let lag = body.velocity - away * body.velocity.dot(&away);
+        let trailing = if lag.norm() > 1e-4 { -lag.normalize() } else { Vec3::zeros() };
+        let side = if trailing != Vec3::zeros() { away.cross(&trailing) } else { away.cross(&Vec3::y()).normalize() };
+        let up = away.cross(&side);
+
+        let nucleus_radius = body.scale * mesh_radius;
+        let ion_length = tail.length * activity;
+        let dust_length = ion_length * 0.65;
+
+        // La coma envuelve al núcleo
+        glow(body.position, nucleus_radius * (1.5 + 2.5 * activity), tail.dust_color, 0.35 * activity);
+
+        for i in 0..ION_PARTICLES {
+            let s = (hash(i, 1.0) + time * ION_FLOW).rem_euclid(1.0);
+            let width = 0.05 + 0.2 * s;
+            let point = body.position
+                + away * (nucleus_radius + s * ion_length)
+                + side * ((hash(i, 2.0) - 0.5) * width)
+                + up * ((hash(i, 3.0) - 0.5) * width);
+            glow(point, ION_SIZE, tail.ion_color, 0.3 * activity * (1.0 - s));
+        }
+
+        for i in 0..DUST_PARTICLES {
+            let s = (hash(i, 4.0) + time * DUST_FLOW).rem_euclid(1.0);
+            let width = 0.15 + 1.6 * s;
+            let point = body.position
+                + away * (nucleus_radius + s * dust_length)
+                + trailing * (s * s * dust_length * DUST_CURVE)
+                + side * ((hash(i, 5.0) - 0.5) * width * 0.4)
+                + up * ((hash(i, 6.0) - 0.5) * width * 0.25)
+                + trailing * ((hash(i, 7.0) - 0.5) * width);
+            glow(point, DUST_SIZE * (1.0 + 2.0 * s), tail.dust_color, 0.16 * activity * (1.0 - s));
+        }
+    }
+}
@@ -0,0 +1,23 @@
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use crate::framebuffer::Framebuffer;
+
+/// Guarda el framebuffer como imagen PPM binaria (P6)
+pub fn write_ppm(filename: &str, framebuffer: &Framebuffer, width: usize, height: usize) -> Result<(), String> {
+    let file = File::create(filename)
+        .map_err(|e| format!("No se pudo crear {}: {}", filename, e))?;
+    let mut writer = BufWriter::new(file);
+
+    let mut data = Vec::with_capacity(width * height * 3 + 32);
+    data.extend_from_slice(format!("P6\n{} {}\n255\n", width, height).as_bytes());
+    for &pixel in framebuffer.get_buffer() {
+        data.push(((pixel >> 16) & 0xFF) as u8);
+        data.push(((pixel >> 8) & 0xFF) as u8);
+        data.push((pixel & 0xFF) as u8);
+    }
+
+    writer
+        .write_all(&data)
+        .and_then(|_| writer.flush())
+        .map_err(|e| format!("No se pudo escribir {}: {}", filename, e))
+}
@@ -1,6 +1,18 @@
+/// Contadores del cuadro en curso, para el HUD
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RenderStats {
+    /// Triángulos que llegaron al rasterizador
+    pub triangles: usize,
+    /// Píxeles de triángulos que pasaron la prueba de profundidad
+    pub pixels: usize,
+    /// Objetos dibujados como un punto en lugar de con su malla
+    pub points: usize,
+}
+
 pub struct Framebuffer {
     pub buffer: Vec<u32>,
     pub depth_buffer: Vec<f32>,
+    pub stats: RenderStats,
 }
 
 
@@ -10,6 +22,7 @@ impl Framebuffer {
         Framebuffer {
             buffer: vec![space_color; width * height],
             depth_buffer: vec![f32::INFINITY; width * height],
+            stats: RenderStats::default(),
         }
     }
 
@@ -17,6 +30,7 @@ impl Framebuffer {
         let space_color = rgb_to_u32(5, 10, 30);
         self.buffer.fill(space_color);
         self.depth_buffer.fill(f32::INFINITY);
+        self.stats = RenderStats::default();
     }
 
     pub fn get_buffer(&self) -> &[u32] {
@@ -27,3 +41,28 @@ impl Framebuffer {
 pub fn rgb_to_u32(r: u8, g: u8, b: u8) -> u32 {
     ((r as u32) << 16) | ((g as u32) << 8) | (b as u32)
 }
+
+/// Suma luz a `dst`; cada canal se satura en 255
+pub fn blend_add(dst: u32, r: f32, g: f32, b: f32) -> u32 {
+    let add = |shift: u32, value: f32| {
+        let d = ((dst >> shift) & 0xFF) as f32;
+        ((d + value * 255.0).round().min(255.0) as u32) << shift
+    };
+    add(16, r) | add(8, g) | add(0, b)
+}
+
+/// Mezcla `src` sobre `dst` con opacidad `alpha` (0 = dst, 1 = src)
+pub fn blend(dst: u32, src: u32, alpha: f32) -> u32 {
+    if alpha >= 1.0 {
+        return src;
+    }
+    if alpha <= 0.0 {
+        return dst;
+    }
+    let mix = |shift: u32| {
+        let d = ((dst >> shift) & 0xFF) as f32;
+        let s = ((src >> shift) & 0xFF) as f32;
+        ((d + (s - d) * alpha).round() as u32) << shift
+    };
+    mix(16) | mix(8) | mix(0)
+}
@@ -0,0 +1,671 @@
+use nalgebra::{Matrix3, Matrix4, Quaternion, UnitQuaternion, Vector3 as Vec3, Vector4};
+use std::fmt;
+use std::fs;
+use std::path::Path;
+use crate::json::{Json, JsonError};
+use crate::material::{Material, Texture};
+use crate::obj_loader::{MaterialGroup, ObjModel, spherical_uv};
+use crate::shaders::ShaderColor;
+use crate::vector::Vector3;
+
+const GLB_MAGIC: &[u8; 4] = b"glTF";
+const GLB_JSON_CHUNK: u32 = 0x4E4F_534A;
+const GLB_BIN_CHUNK: u32 = 0x004E_4942;
+// Profundidad máxima de la jerarquía de nodos, para cortar ciclos
+const MAX_NODE_DEPTH: usize = 64;
+// Sin reflejos del entorno un metal puro saldría negro: conserva esta
+// fracción del color difuso
+const METAL_DIFFUSE: f32 = 0.3;
+// Valores máximos de un accessor sin bufferView: no ocupa nada en el archivo,
+// así que su `count` no está limitado por el tamaño de ningún buffer
+const MAX_EMPTY_ACCESSOR_VALUES: usize = 1 << 24;
+
+#[derive(Debug)]
+pub enum GltfError {
+    Io { file: String, error: std::io::Error },
+    Json { file: String, error: JsonError },
+    /// El archivo no sigue la especificación
+    Invalid { file: String, reason: String },
+    /// Parte válida que el cargador no sabe usar; se salta con un aviso
+    Unsupported { file: String, reason: String },
+}
+
+impl fmt::Display for GltfError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            GltfError::Io { file, error } => write!(f, "{}: no se pudo leer: {}", file, error),
+            GltfError::Json { file, error } => write!(f, "{}:{}", file, error),
+            GltfError::Invalid { file, reason } => write!(f, "{}: glTF no válido: {}", file, reason),
+            GltfError::Unsupported { file, reason } => write!(f, "{}: no admitido: {}", file, reason),
+        }
+    }
+}
+
+impl std::error::Error for GltfError {}
+
+/// Valores de un accessor, un elemento de `components` números tras otro
+struct Accessor {
+    components: usize,
+    values: Vec<f64>,
+}
+
+impl Accessor {
+    fn count(&self) -> usize {
+        self.values.len() / self.components
+    }
+
+    fn get<const N: usize>(&self, index: usize) -> [f32; N] {
+        let mut result = [0.0; N];
+        let start = index * self.components;
+        for (slot, value) in result.iter_mut().zip(&self.values[start..start + self.components.min(N)]) {
+            *slot = *value as f32;
+        }
+        result
+    }
+}
+
+/// Archivo glTF ya leído, con sus buffers cargados
+struct Document<'a> {
+    file: &'a str,
+    directory: &'a Path,
+    json: Json,
+    buffers: Vec<Vec<u8>>,
+}
+
+/// Carga un `.gltf` (con buffers externos o embebidos en URIs `data:`) o un
+/// `.glb`. Las mallas de la escena se juntan en un solo modelo con las
+/// transformaciones de sus nodos aplicadas, y cada primitiva es un grupo con
+/// su material. Devuelve también los avisos de lo que se saltó.
+pub fn load(filename: &str) -> Result<(ObjModel, Vec<GltfError>), GltfError> {
+    let invalid = |reason: &str| GltfError::Invalid { file: filename.to_string(), reason: reason.to_string() };
+    let data = fs::read(filename).map_err(|error| GltfError::Io { file: filename.to_string(), error })?;
+
+    // Un .glb es un bloque JSON seguido de un bloque binario opcional
+    let (text, binary) = if data.starts_with(GLB_MAGIC) {
+        let (json, binary) = split_glb(&data).map_err(|reason| invalid(&reason))?;
+        (json, binary)
+    } else {
+        (&data[..], None)
+    };
+    let text = std::str::from_utf8(text).map_err(|_| invalid("el JSON no es UTF-8"))?;
+    let json = Json::parse(text.trim_start_matches('\u{FEFF}'))
+        .map_err(|error| GltfError::Json { file: filename.to_string(), error })?;
+    let version = json.get("asset").and_then(|asset| asset.get("version")).and_then(Json::as_str);
+    if !version.is_some_and(|version| version.starts_with("2.")) {
+        return Err(invalid("falta asset.version o no es 2.x"));
+    }
+
+    let directory = Path::new(filename).parent().unwrap_or(Path::new(""));
+    let mut buffers = Vec::new();
+    for (index, buffer) in array(&json, "buffers").iter().enumerate() {
+        let length = buffer.get("byteLength").and_then(Json::as_usize).ok_or_else(|| invalid("buffer sin byteLength"))?;
+        let bytes = match buffer.get("uri").and_then(Json::as_str) {
+            Some(uri) => read_uri(uri, directory).map_err(|reason| invalid(&format!("buffer {}: {}", index, reason)))?,
+            None if index == 0 => binary.ok_or_else(|| invalid("buffer 0 sin uri y sin bloque binario"))?.to_vec(),
+            None => return Err(invalid(&format!("buffer {} sin uri", index))),
+        };
+        if bytes.len() < length {
+            return Err(invalid(&format!("buffer {} tiene {} bytes y declara {}", index, bytes.len(), length)));
+        }
+        buffers.push(bytes);
+    }
+
+    let document = Document { file: filename, directory, json, buffers };
+    let mut warnings = Vec::new();
+    let materials = array(&document.json, "materials")
+        .iter()
+        .enumerate()
+        .map(|(index, material)| document.material(index, material, &mut warnings))
+        .collect();
+    let mut model = ObjModel {
+        vertices: Vec::new(),
+        normals: Vec::new(),
+        uvs: Vec::new(),
+        indices: Vec::new(),
+        materials,
+        groups: Vec::new(),
+    };
+
+    for (node, transform) in document.scene_nodes()? {
+        let Some(mesh) = node.get("mesh") else { continue };
+        let mesh = mesh
+            .as_usize()
+            .and_then(|index| array(&document.json, "meshes").get(index))
+            .ok_or_else(|| invalid("nodo con una malla que no existe"))?;
+        for primitive in array(mesh, "primitives") {
+            match document.add_primitive(&mut model, primitive, &transform) {
+                Ok(()) => {}
+                Err(warning @ GltfError::Unsupported { .. }) => warnings.push(warning),
+                Err(error) => return Err(error),
+            }
+        }
+    }
+
+    model.validate().map_err(|reason| invalid(&format!("malla no válida: {}", reason)))?;
+    Ok((model, warnings))
+}
+
+/// Lista `key` de un objeto, vacía si no está
+fn array<'a>(json: &'a Json, key: &str) -> &'a [Json] {
+    json.get(key).and_then(Json::as_array).unwrap_or(&[])
+}
+
+/// Separa los bloques JSON y binario de un .glb
+fn split_glb(data: &[u8]) -> Result<(&[u8], Option<&[u8]>), String> {
+    let word = |offset: usize| -> Option<u32> {
+        data.get(offset..offset + 4).map(|bytes| u32::from_le_bytes(bytes.try_into().unwrap()))
+    };
+    if word(4) != Some(2) {
+        return Err("solo se admite GLB versión 2".to_string());
+    }
+    let total = (word(8).ok_or("cabecera GLB cortada")? as usize).min(data.len());
+    let mut chunks = Vec::new();
+    let mut offset = 12;
+    while offset + 8 <= total {
+        let length = word(offset).ok_or("cabecera de bloque GLB cortada")? as usize;
+        let kind = word(offset + 4).ok_or("cabecera de bloque GLB cortada")?;
+        let end = (offset + 8).checked_add(length).ok_or("bloque GLB demasiado largo")?;
+        let body = data.get(offset + 8..end).ok_or("bloque GLB cortado")?;
+        chunks.push((kind, body));
+        offset = end;
+    }
+    match chunks.first() {
+        Some(&(GLB_JSON_CHUNK, json)) => {
+            let binary = chunks.get(1).filter(|(kind, _)| *kind == GLB_BIN_CHUNK).map(|&(_, body)| body);
+            Ok((json, binary))
+        }
+        _ => Err("el primer bloque GLB no es JSON".to_string()),
+    }
+}
+
+/// Contenido de una URI: `data:` en base64 o un archivo junto al glTF
+fn read_uri(uri: &str, directory: &Path) -> Result<Vec<u8>, String> {
+    if let Some(rest) = uri.strip_prefix("data:") {
+        let (_, encoded) = rest.split_once(";base64,").ok_or("URI data: que no es base64")?;
+        return decode_base64(encoded);
+    }
+    let path = directory.join(percent_decode(uri));
+    fs::read(&path).map_err(|error| format!("{}: {}", path.display(), error))
+}
+
+fn decode_base64(text: &str) -> Result<Vec<u8>, String> {
+    let mut output = Vec::with_capacity(text.len() * 3 / 4);
+    let (mut accumulator, mut bits) = (0u32, 0);
+    for byte in text.bytes().filter(|byte| !byte.is_ascii_whitespace() && *byte != b'=') {
+        let value = match byte {
+            b'A'..=b'Z' => byte - b'A',
+            b'a'..=b'z' => byte - b'a' + 26,
+            b'0'..=b'9' => byte - b'0' + 52,
+            b'+' | b'-' => 62,
+            b'/' | b'_' => 63,
+            _ => return Err(format!("carácter '{}' no válido en base64", byte as char)),
+        };
+        accumulator = (accumulator << 6) | value as u32;
+        bits += 6;
+        if bits >= 8 {
+            bits -= 8;
+            output.push((accumulator >> bits) as u8);
+        }
+    }
+    Ok(output)
+}
+
+/// Deshace los `%XX` de una URI relativa
+fn percent_decode(uri: &str) -> String {
+    let bytes = uri.as_bytes();
+    let mut output = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let hex = bytes.get(i + 1..i + 3).and_then(|hex| u8::from_str_radix(std::str::from_utf8(hex).ok()?, 16).ok());
+        match (bytes[i], hex) {
+            (b'%', Some(value)) => {
+                output.push(value);
+                i += 3;
+            }
+            (byte, _) => {
+                output.push(byte);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&output).into_owned()
+}
+
+/// Transformación local de un nodo: `matrix` o traslación, rotación y escala
+fn node_transform(node: &Json) -> Matrix4<f32> {
+    if let Some(matrix) = node.get("matrix").and_then(Json::as_floats::<16>) {
+        // glTF guarda las matrices por columnas
+        return Matrix4::from_column_slice(&matrix);
+    }
+    let [tx, ty, tz] = node.get("translation").and_then(Json::as_floats).unwrap_or([0.0; 3]);
+    let [x, y, z, w] = node.get("rotation").and_then(Json::as_floats).unwrap_or([0.0, 0.0, 0.0, 1.0]);
+    let [sx, sy, sz] = node.get("scale").and_then(Json::as_floats).unwrap_or([1.0; 3]);
+    Matrix4::new_translation(&Vec3::new(tx, ty, tz))
+        * UnitQuaternion::from_quaternion(Quaternion::new(w, x, y, z)).to_homogeneous()
+        * Matrix4::new_nonuniform_scaling(&Vec3::new(sx, sy, sz))
+}
+
+impl Document<'_> {
+    fn invalid(&self, reason: &str) -> GltfError {
+        GltfError::Invalid { file: self.file.to_string(), reason: reason.to_string() }
+    }
+
+    fn unsupported(&self, reason: &str) -> GltfError {
+        GltfError::Unsupported { file: self.file.to_string(), reason: reason.to_string() }
+    }
+
+    /// Nodos de la escena por defecto con su transformación en el mundo. Sin
+    /// escenas se usan los nodos que no son hijos de otro.
+    fn scene_nodes(&self) -> Result<Vec<(&Json, Matrix4<f32>)>, GltfError> {
+        let nodes = array(&self.json, "nodes");
+        let scenes = array(&self.json, "scenes");
+        let roots: Vec<usize> = if scenes.is_empty() {
+            let children: Vec<usize> = nodes
+                .iter()
+                .flat_map(|node| array(node, "children").iter().filter_map(Json::as_usize))
+                .collect();
+            (0..nodes.len()).filter(|index| !children.contains(index)).collect()
+        } else {
+            let scene = self.json.get("scene").and_then(Json::as_usize).unwrap_or(0);
+            let scene = scenes.get(scene).ok_or_else(|| self.invalid("la escena por defecto no existe"))?;
+            array(scene, "nodes").iter().filter_map(Json::as_usize).collect()
+        };
+
+        let mut result = Vec::new();
+        let mut pending: Vec<(usize, Matrix4<f32>, usize)> =
+            roots.into_iter().rev().map(|index| (index, Matrix4::identity(), 0)).collect();
+        while let Some((index, parent, depth)) = pending.pop() {
+            let node = nodes.get(index).ok_or_else(|| self.invalid(&format!("el nodo {} no existe", index)))?;
+            if depth > MAX_NODE_DEPTH {
+                return Err(self.invalid("la jerarquía de nodos tiene un ciclo"));
+            }
+            let transform = parent * node_transform(node);
+            for child in array(node, "children").iter().rev() {
+                let child = child.as_usize().ok_or_else(|| self.invalid("hijo de nodo no válido"))?;
+                pending.push((child, transform, depth + 1));
+            }
+            result.push((node, transform));
+        }
+        Ok(result)
+    }
+
+    /// Lee un accessor convirtiendo cada componente a número
+    fn accessor(&self, index: usize) -> Result<Accessor, GltfError> {
+        let accessor = array(&self.json, "accessors")
+            .get(index)
+            .ok_or_else(|| self.invalid(&format!("el accessor {} no existe", index)))?;
+        if accessor.get("sparse").is_some() {
+            return Err(self.unsupported("accessors dispersos (sparse)"));
+        }
+        let count = accessor.get("count").and_then(Json::as_usize).ok_or_else(|| self.invalid("accessor sin count"))?;
+        let components = match accessor.get("type").and_then(Json::as_str) {
+            Some("SCALAR") => 1,
+            Some("VEC2") => 2,
+            Some("VEC3") => 3,
+            Some("VEC4") => 4,
+            Some("MAT2") => 4,
+            Some("MAT3") => 9,
+            Some("MAT4") => 16,
+            _ => return Err(self.invalid("tipo de accessor desconocido")),
+        };
+        let component_type = accessor.get("componentType").and_then(Json::as_usize).unwrap_or(0);
+        let size = match component_type {
+            5120 | 5121 => 1,
+            5122 | 5123 => 2,
+            5125 | 5126 => 4,
+            _ => return Err(self.invalid(&format!("componentType {} desconocido", component_type))),
+        };
+        let normalized = accessor.get("normalized") == Some(&Json::Bool(true));
+
+        // Sin bufferView todos los valores son cero
+        let Some(view) = accessor.get("bufferView").and_then(Json::as_usize) else {
+            let length = count
+                .checked_mul(components)
+                .filter(|&length| length <= MAX_EMPTY_ACCESSOR_VALUES)
+                .ok_or_else(|| self.invalid("accessor sin bufferView demasiado grande"))?;
+            return Ok(Accessor { components, values: vec![0.0; length] });
+        };
+        let view = array(&self.json, "bufferViews")
+            .get(view)
+            .ok_or_else(|| self.invalid(&format!("el bufferView {} no existe", view)))?;
+        let buffer = view
+            .get("buffer")
+            .and_then(Json::as_usize)
+            .and_then(|buffer| self.buffers.get(buffer))
+            .ok_or_else(|| self.invalid("bufferView con un buffer que no existe"))?;
+        let view_offset = view.get("byteOffset").and_then(Json::as_usize).unwrap_or(0);
+        let view_length = view.get("byteLength").and_then(Json::as_usize).unwrap_or(0);
+        let bytes = view_offset
+            .checked_add(view_length)
+            .and_then(|end| buffer.get(view_offset..end))
+            .ok_or_else(|| self.invalid("bufferView fuera de su buffer"))?;
+        let element = components * size;
+        let stride = view.get("byteStride").and_then(Json::as_usize).unwrap_or(element).max(element);
+        let offset = accessor.get("byteOffset").and_then(Json::as_usize).unwrap_or(0);
+        // Los valores vienen del archivo: un `count` enorme no debe desbordar
+        let end = match count.checked_sub(1) {
+            Some(last) => stride.checked_mul(last).and_then(|span| span.checked_add(offset)).and_then(|start| start.checked_add(element)),
+            None => Some(0),
+        };
+        if end.is_none_or(|end| end > bytes.len()) {
+            return Err(self.invalid("accessor fuera de su bufferView"));
+        }
+
+        let mut values = Vec::with_capacity(count * components);
+        for i in 0..count {
+            for c in 0..components {
+                let at = offset + i * stride + c * size;
+                let b = &bytes[at..at + size];
+                let value = match component_type {
+                    5120 => b[0] as i8 as f64,
+                    5121 => b[0] as f64,
+                    5122 => i16::from_le_bytes([b[0], b[1]]) as f64,
+                    5123 => u16::from_le_bytes([b[0], b[1]]) as f64,
+                    5125 => u32::from_le_bytes([b[0], b[1], b[2], b[3]]) as f64,
+                    _ => f32::from_le_bytes([b[0], b[1], b[2], b[3]]) as f64,
+                };
+                values.push(match (normalized, component_type) {
+                    (true, 5120) => (value / 127.0).max(-1.0),
+                    (true, 5121) => value / 255.0,
+                    (true, 5122) => (value / 32767.0).max(-1.0),
+                    (true, 5123) => value / 65535.0,
+                    _ => value,
+                });
+            }
+        }
+        Ok(Accessor { components, values })
+    }
+
+    /// Material PBR llevado al modelo Blinn-Phong del shader de materiales:
+    /// el metal tiñe el brillo especular y la rugosidad lo abre
+    fn material(&self, index: usize, json: &Json, warnings: &mut Vec<GltfError>) -> Material {
+        let name = json.get("name").and_then(Json::as_str).map_or(format!("material {}", index), str::to_string);
+        let mut material = Material::new(&name);
+        let pbr = json.get("pbrMetallicRoughness");
+        let setting = |key: &str| pbr.and_then(|pbr| pbr.get(key));
+        let [r, g, b, a] = setting("baseColorFactor").and_then(Json::as_floats).unwrap_or([1.0; 4]);
+        let metallic = setting("metallicFactor").and_then(Json::as_f32).unwrap_or(1.0).clamp(0.0, 1.0);
+        let roughness = setting("roughnessFactor").and_then(Json::as_f32).unwrap_or(1.0).clamp(0.0, 1.0);
+
+        let diffuse = 1.0 - (1.0 - METAL_DIFFUSE) * metallic;
+        material.diffuse = ShaderColor::new(r * diffuse, g * diffuse, b * diffuse, 1.0);
+        // Reflejo de un dieléctrico (4 %) o del color del metal
+        let reflect = |channel: f32| 0.04 + (channel - 0.04) * metallic;
+        material.specular = ShaderColor::new(reflect(r), reflect(g), reflect(b), 1.0);
+        // Exponente de Blinn-Phong equivalente a la rugosidad de GGX
+        let alpha = (roughness * roughness).max(0.03);
+        material.shininess = 2.0 / (alpha * alpha) - 2.0;
+        let [er, eg, eb] = json.get("emissiveFactor").and_then(Json::as_floats).unwrap_or([0.0; 3]);
+        material.emission = ShaderColor::new(er, eg, eb, 1.0);
+        if json.get("alphaMode").and_then(Json::as_str) == Some("BLEND") {
+            material.opacity = a.clamp(0.0, 1.0);
+        }
+
+        if let Some(texture) = setting("baseColorTexture").and_then(|info| info.get("index")).and_then(Json::as_usize) {
+            match self.texture(texture) {
+                Ok(texture) => material.diffuse_map = Some(texture),
+                Err(reason) => warnings.push(self.unsupported(&format!("textura de '{}': {}", name, reason))),
+            }
+        }
+        material
+    }
+
+    /// Imagen de una textura, en un archivo, una URI `data:` o un bufferView
+    fn texture(&self, index: usize) -> Result<Texture, String> {
+        let image = array(&self.json, "textures")
+            .get(index)
+            .and_then(|texture| texture.get("source"))
+            .and_then(Json::as_usize)
+            .and_then(|source| array(&self.json, "images").get(source))
+            .ok_or("la textura no tiene imagen")?;
+        let bytes = if let Some(uri) = image.get("uri").and_then(Json::as_str) {
+            read_uri(uri, self.directory)?
+        } else {
+            let view = image
+                .get("bufferView")
+                .and_then(Json::as_usize)
+                .and_then(|view| array(&self.json, "bufferViews").get(view))
+                .ok_or("la imagen no tiene uri ni bufferView")?;
+            let buffer = view.get("buffer").and_then(Json::as_usize).and_then(|b| self.buffers.get(b)).ok_or("buffer inexistente")?;
+            let offset = view.get("byteOffset").and_then(Json::as_usize).unwrap_or(0);
+            let length = view.get("byteLength").and_then(Json::as_usize).unwrap_or(0);
+            offset
+                .checked_add(length)
+                .and_then(|end| buffer.get(offset..end))
+                .ok_or("bufferView fuera de su buffer")?
+                .to_vec()
+        };
+        Texture::decode(&bytes)
+    }
+
+    /// Añade una primitiva al modelo como un grupo con su material
+    fn add_primitive(&self, model: &mut ObjModel, primitive: &Json, transform: &Matrix4<f32>) -> Result<(), GltfError> {
+        let mode = primitive.get("mode").and_then(Json::as_usize).unwrap_or(4);
+        if !(4..=6).contains(&mode) {
+            return Err(self.unsupported("primitiva de puntos o líneas"));
+        }
+        let attribute = |name: &str| primitive.get("attributes").and_then(|a| a.get(name)).and_then(Json::as_usize);
+        let positions = self.accessor(attribute("POSITION").ok_or_else(|| self.unsupported("primitiva sin POSITION"))?)?;
+        let normals = attribute("NORMAL").map(|index| self.accessor(index)).transpose()?;
+        let uvs = attribute("TEXCOORD_0").map(|index| self.accessor(index)).transpose()?;
+        let count = positions.count();
+        if normals.as_ref().is_some_and(|n| n.count() != count) || uvs.as_ref().is_some_and(|t| t.count() != count) {
+            return Err(self.invalid("atributos con distinto número de elementos"));
+        }
+        let material = match primitive.get("material") {
+            Some(index) => Some(index.as_usize().filter(|&i| i < model.materials.len()).ok_or_else(|| self.invalid("material inexistente"))?),
+            None => None,
+        };
+
+        // Índices de la primitiva como lista de triángulos
+        let order: Vec<usize> = match primitive.get("indices").and_then(Json::as_usize) {
+            Some(index) => self.accessor(index)?.values.iter().map(|&i| i as usize).collect(),
+            None => (0..count).collect(),
+        };
+        if order.iter().any(|&i| i >= count) {
+            return Err(self.invalid("índice de vértice fuera de rango"));
+        }
+        let mut triangles: Vec<[usize; 3]> = match mode {
+            4 => order.chunks_exact(3).map(|t| [t[0], t[1], t[2]]).collect(),
+            // Tira: los triángulos impares cambian de sentido
+            5 => (0..order.len().saturating_sub(2))
+                .map(|i| if i % 2 == 0 { [order[i], order[i + 1], order[i + 2]] } else { [order[i + 1], order[i], order[i + 2]] })
+                .collect(),
+            _ => (1..order.len().saturating_sub(1)).map(|i| [order[0], order[i], order[i + 1]]).collect(),
+        };
+        // Una escala negativa da la vuelta a las caras
+        let normal_matrix: Matrix3<f32> = transform.fixed_view::<3, 3>(0, 0).into_owned();
+        if normal_matrix.determinant() < 0.0 {
+            triangles.iter_mut().for_each(|t| t.swap(1, 2));
+        }
+        let normal_matrix = normal_matrix.try_inverse().map(|m| m.transpose()).unwrap_or(normal_matrix);
+
+        let point = |i: usize| {
+            let [x, y, z] = positions.get(i);
+            let p = transform * Vector4::new(x, y, z, 1.0);
+            Vector3::new(p.x, p.y, p.z)
+        };
+        // Las UV de glTF empiezan arriba; las de OBJ y `Texture`, abajo
+        let uv = |i: usize, position: Vector3| match &uvs {
+            Some(uvs) => {
+                let [u, v] = uvs.get(i);
+                (u, 1.0 - v)
+            }
+            None => spherical_uv(position),
+        };
+
+        let start = model.indices.len();
+        match &normals {
+            Some(normals) => {
+                let base = model.vertices.len();
+                for i in 0..count {
+                    let position = point(i);
+                    let [x, y, z] = normals.get(i);
+                    let n = (normal_matrix * Vec3::new(x, y, z)).try_normalize(1e-12).unwrap_or_else(Vec3::zeros);
+                    model.vertices.push(position);
+                    model.normals.push(Vector3::new(n.x, n.y, n.z));
+                    model.uvs.push(uv(i, position));
+                }
+                model.indices.extend(triangles.iter().map(|t| (base + t[0], base + t[1], base + t[2])));
+            }
+            None => {
+                // Sin normales la especificación pide sombreado plano: cada
+                // triángulo lleva sus propios vértices
+                for t in &triangles {
+                    let corners = t.map(point);
+                    // Un triángulo sin área no tiene normal: se deja en cero
+                    let [a, b, c] = corners.map(|p| Vec3::new(p.x, p.y, p.z));
+                    let n = (b - a).cross(&(c - a)).try_normalize(1e-12).unwrap_or_else(Vec3::zeros);
+                    let normal = Vector3::new(n.x, n.y, n.z);
+                    let base = model.vertices.len();
+                    for (&i, &position) in t.iter().zip(&corners) {
+                        model.vertices.push(position);
+                        model.normals.push(normal);
+                        model.uvs.push(uv(i, position));
+                    }
+                    model.indices.push((base, base + 1, base + 2));
+                }
+            }
+        }
+        if model.indices.len() > start {
+            model.groups.push(MaterialGroup { material, triangles: start..model.indices.len() });
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Un triángulo con índices de 16 bits, movido una unidad en z por su nodo
+    const DOCUMENT: &str = r#"{
+        "asset": {"version": "2.0"},
+        "scene": 0,
+        "scenes": [{"nodes": [0]}],
+        "nodes": [{"mesh": 0, "translation": [0, 0, 1]}],
+        "meshes": [{"primitives": [{"attributes": {"POSITION": 0}, "indices": 1}]}],
+        "buffers": [{"byteLength": 44}],
+        "bufferViews": [
+            {"buffer": 0, "byteOffset": 0, "byteLength": 36},
+            {"buffer": 0, "byteOffset": 36, "byteLength": 6}
+        ],
+        "accessors": [
+            {"bufferView": 0, "componentType": 5126, "count": 3, "type": "VEC3"},
+            {"bufferView": 1, "componentType": 5123, "count": 3, "type": "SCALAR"}
+        ]
+    }"#;
+
+    fn binary(positions: [[f32; 3]; 3], indices: [u16; 3]) -> Vec<u8> {
+        let mut data: Vec<u8> = positions.iter().flatten().flat_map(|v| v.to_le_bytes()).collect();
+        data.extend(indices.iter().flat_map(|i| i.to_le_bytes()));
+        data
+    }
+
+    fn triangle() -> Vec<u8> {
+        binary([[0.0, 0.0, 0.0], [1.0, 0.0, 0.0], [0.0, 1.0, 0.0]], [0, 1, 2])
+    }
+
+    /// Archivo .glb con un bloque JSON y uno binario, rellenos hasta 4 bytes
+    fn glb(json: &str, binary: &[u8]) -> Vec<u8> {
+        let mut json = json.as_bytes().to_vec();
+        json.resize(json.len().div_ceil(4) * 4, b' ');
+        let mut binary = binary.to_vec();
+        binary.resize(binary.len().div_ceil(4) * 4, 0);
+        let mut data = GLB_MAGIC.to_vec();
+        data.extend_from_slice(&2u32.to_le_bytes());
+        data.extend_from_slice(&((12 + 8 + json.len() + 8 + binary.len()) as u32).to_le_bytes());
+        for (kind, body) in [(GLB_JSON_CHUNK, json), (GLB_BIN_CHUNK, binary)] {
+            data.extend_from_slice(&(body.len() as u32).to_le_bytes());
+            data.extend_from_slice(&kind.to_le_bytes());
+            data.extend_from_slice(&body);
+        }
+        data
+    }
+
+    fn load_bytes(name: &str, data: &[u8]) -> Result<(ObjModel, Vec<GltfError>), GltfError> {
+        let path = std::env::temp_dir().join(format!("gltf-test-{}-{}.glb", std::process::id(), name));
+        fs::write(&path, data).unwrap();
+        let result = load(path.to_str().unwrap());
+        fs::remove_file(&path).ok();
+        result
+    }
+
+    /// Motivo por el que no se carga un archivo que debe ser `Invalid`
+    fn invalid_reason_bytes(name: &str, data: &[u8]) -> String {
+        match load_bytes(name, data) {
+            Err(GltfError::Invalid { reason, .. }) => reason,
+            Err(other) => panic!("error inesperado: {}", other),
+            Ok(_) => panic!("{} se cargó", name),
+        }
+    }
+
+    fn invalid_reason(name: &str, json: &str, binary: &[u8]) -> String {
+        invalid_reason_bytes(name, &glb(json, binary))
+    }
+
+    #[test]
+    fn loads_a_minimal_glb() {
+        let (model, warnings) = load_bytes("minimo", &glb(DOCUMENT, &triangle())).unwrap();
+        assert!(warnings.is_empty());
+        assert_eq!(model.indices, vec![(0, 1, 2)]);
+        let z: Vec<f32> = model.vertices.iter().map(|v| v.z).collect();
+        assert_eq!(z, vec![1.0; 3]);
+        assert_eq!((model.vertices[1].x, model.vertices[2].y), (1.0, 1.0));
+        // Sin normales se sombrea plano con la normal de la cara
+        assert!(model.normals.iter().all(|n| (n.x, n.y, n.z) == (0.0, 0.0, 1.0)));
+        assert_eq!(model.groups.len(), 1);
+        assert_eq!(model.groups[0].material, None);
+    }
+
+    #[test]
+    fn degenerate_triangles_get_finite_normals() {
+        let flat = binary([[1.0, 2.0, 3.0]; 3], [0, 1, 2]);
+        let (model, _) = load_bytes("degenerado", &glb(DOCUMENT, &flat)).unwrap();
+        assert!(model.normals.iter().all(|n| n.x.is_finite() && n.y.is_finite() && n.z.is_finite()));
+    }
+
+    #[test]
+    fn out_of_range_accessors_are_invalid() {
+        let too_many = DOCUMENT.replacen(r#""count": 3, "type": "VEC3""#, r#""count": 4, "type": "VEC3""#, 1);
+        assert_eq!(invalid_reason("cuenta", &too_many, &triangle()), "accessor fuera de su bufferView");
+
+        // Valores que desbordarían la cuenta del final del accessor
+        let huge = DOCUMENT.replacen(r#""count": 3, "type": "VEC3""#, r#""count": 4611686018427387904, "type": "VEC3""#, 1);
+        assert_eq!(invalid_reason("enorme", &huge, &triangle()), "accessor fuera de su bufferView");
+        let offset = DOCUMENT.replacen(r#""byteOffset": 0, "byteLength": 36"#, r#""byteOffset": 18446744073709551615, "byteLength": 36"#, 1);
+        assert_eq!(invalid_reason("desplazamiento", &offset, &triangle()), "bufferView fuera de su buffer");
+        let empty = DOCUMENT.replacen(r#""bufferView": 0, "componentType": 5126, "count": 3"#, r#""componentType": 5126, "count": 4611686018427387904"#, 1);
+        assert_eq!(invalid_reason("vacio", &empty, &triangle()), "accessor sin bufferView demasiado grande");
+
+        let missing = DOCUMENT.replacen(r#""POSITION": 0"#, r#""POSITION": 7"#, 1);
+        assert_eq!(invalid_reason("inexistente", &missing, &triangle()), "el accessor 7 no existe");
+    }
+
+    #[test]
+    fn out_of_range_indices_are_invalid() {
+        let data = binary([[0.0, 0.0, 0.0], [1.0, 0.0, 0.0], [0.0, 1.0, 0.0]], [0, 1, 3]);
+        assert_eq!(invalid_reason("indices", DOCUMENT, &data), "índice de vértice fuera de rango");
+        let material = DOCUMENT.replacen(r#""indices": 1"#, r#""indices": 1, "material": 0"#, 1);
+        assert_eq!(invalid_reason("material", &material, &triangle()), "material inexistente");
+    }
+
+    #[test]
+    fn broken_containers_are_reported() {
+        let mut data = glb(DOCUMENT, &triangle());
+        data.truncate(data.len() - 10);
+        assert_eq!(invalid_reason_bytes("cortado", &data), "bloque GLB cortado");
+
+        let mut data = glb(DOCUMENT, &triangle());
+        data[4] = 1;
+        assert_eq!(invalid_reason_bytes("version", &data), "solo se admite GLB versión 2");
+
+        let old = DOCUMENT.replacen(r#""version": "2.0""#, r#""version": "1.0""#, 1);
+        assert_eq!(invalid_reason("antiguo", &old, &triangle()), "falta asset.version o no es 2.x");
+
+        match load_bytes("json", &glb("{\"asset\": }", &[])) {
+            Err(GltfError::Json { error, .. }) => assert_eq!((error.line, error.column), (1, 11)),
+            _ => panic!("se esperaba un error de JSON"),
+        }
+    }
+}
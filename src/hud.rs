@@ -0,0 +1,212 @@
+use std::f32::consts::PI;
+use crate::framebuffer::{Framebuffer, rgb_to_u32};
+use crate::camera::Camera;
+use crate::clock::SimClock;
+use crate::scene::Scene;
+use crate::comets;
+use crate::render::{WIDTH, HEIGHT};
+use crate::text::{self, Align, TextStyle};
+
+// Separación del panel respecto al borde de la ventana y margen interior
+const HUD_MARGIN: i32 = 10;
+const HUD_PADDING: usize = 6;
+// Peso de cada cuadro nuevo en el promedio del tiempo de cuadro
+const FRAME_TIME_SMOOTHING: f32 = 0.1;
+
+/// Esquina de la pantalla donde se anclan el HUD y el minimapa
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScreenCorner {
+    TopLeft,
+    TopRight,
+    BottomLeft,
+    BottomRight,
+}
+
+impl ScreenCorner {
+    pub const ALL: [ScreenCorner; 4] = [
+        ScreenCorner::TopLeft,
+        ScreenCorner::TopRight,
+        ScreenCorner::BottomLeft,
+        ScreenCorner::BottomRight,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            ScreenCorner::TopLeft => "top-left",
+            ScreenCorner::TopRight => "top-right",
+            ScreenCorner::BottomLeft => "bottom-left",
+            ScreenCorner::BottomRight => "bottom-right",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        ScreenCorner::ALL.iter().copied().find(|c| c.name() == name)
+    }
+
+    pub fn is_right(&self) -> bool {
+        matches!(self, ScreenCorner::TopRight | ScreenCorner::BottomRight)
+    }
+
+    pub fn is_bottom(&self) -> bool {
+        matches!(self, ScreenCorner::BottomLeft | ScreenCorner::BottomRight)
+    }
+}
+
+/// Panel de depuración con rendimiento, tiempo, cámara y selección
+pub struct Hud {
+    pub visible: bool,
+    pub corner: ScreenCorner,
+    // Tiempo de cuadro suavizado, en segundos
+    frame_time: f32,
+}
+
+impl Hud {
+    pub fn new(corner: ScreenCorner, visible: bool) -> Self {
+        Hud {
+            visible,
+            corner,
+            frame_time: 0.0,
+        }
+    }
+
+    pub fn toggle(&mut self) {
+        self.visible = !self.visible;
+    }
+
+    /// Registra la duración real del último cuadro
+    pub fn record_frame(&mut self, seconds: f32) {
+        self.frame_time = if self.frame_time == 0.0 {
+            seconds
+        } else {
+            self.frame_time + (seconds - self.frame_time) * FRAME_TIME_SMOOTHING
+        };
+    }
+
+    fn lines(&self, framebuffer: &Framebuffer, scene: &Scene, camera: &Camera, clock: &SimClock) -> Vec<String> {
+        let fps = if self.frame_time > 0.0 { 1.0 / self.frame_time } else { 0.0 };
+        let p = camera.position;
+        let mut lines = vec![
+            format!("FPS {:.1}  ({:.1} ms)", fps, self.frame_time * 1000.0),
+            format!("Sim  {}", clock.status()),
+            format!(
+                "Triángulos {}  Píxeles {}  Puntos {}",
+                framebuffer.stats.triangles, framebuffer.stats.pixels, framebuffer.stats.points
+            ),
+            format!("Partículas {}", scene.particles.particle_count()),
+            format!("Cámara {}", camera.mode.name()),
+            format!("  Pos ({:.1}, {:.1}, {:.1})", p.x, p.y, p.z),
+            format!(
+                "  Distancia {:.1}  Altura {:.1}",
+                (camera.target - camera.position).norm(),
+                p.y
+            ),
+        ];
+
+        let yes_no = |value: bool| if value { "sí" } else { "no" };
+        lines.push(format!(
+            "Nave  Vel {:.1}  Gravedad {}  Estabilidad {}",
+            scene.ship.speed(),
+            yes_no(scene.ship.gravity),
+            yes_no(scene.ship.stability_assist)
+        ));
+
+        if let Some(system) = &scene.nbody {
+            lines.push(format!(
+                "N cuerpos ({})  dE {:+.2e}  dp {:.2e}",
+                system.integrator().name(),
+                system.energy_drift(),
+                system.momentum_drift()
+            ));
+        }
+
+        match scene.selected.and_then(|i| scene.bodies.get(i).map(|b| (i, b))) {
+            Some((index, body)) => {
+                let angle = body.orbit_angle.to_degrees().rem_euclid(360.0);
+                let sun = &scene.bodies[0];
+                let distance = (body.position - sun.position).norm();
+                lines.push(format!("{} (#{})", body.name, index));
+                if scene.nbody.is_some() {
+                    // Con gravedad mutua la órbita fija ya no describe el
+                    // movimiento: se muestra el estado integrado respecto al sol
+                    lines.push(format!(
+                        "  Al sol {:.2}  Vel {:.2}",
+                        distance,
+                        (body.velocity - sun.velocity).norm()
+                    ));
+                    lines.push(format!("  Ángulo {:.0} grados", angle));
+                    if body.kepler.is_some() {
+                        lines.push(format!("  Actividad {:.0}%", comets::activity(distance) * 100.0));
+                    }
+                } else {
+                    let period = if body.orbit_speed != 0.0 {
+                        format!("{:.1} s", 2.0 * PI / body.orbit_speed.abs())
+                    } else {
+                        "-".to_string()
+                    };
+                    lines.push(format!("  Radio orbital {:.2}  Periodo {}", body.orbit_radius, period));
+                    lines.push(format!("  Ángulo {:.0} grados  Vel {:.2} rad/s", angle, body.orbit_speed));
+                    if let Some(orbit) = &body.kepler {
+                        lines.push(format!(
+                            "  Excentricidad {:.2}  Perihelio {:.1}  Afelio {:.1}",
+                            orbit.eccentricity,
+                            orbit.perihelion(),
+                            orbit.aphelion()
+                        ));
+                        lines.push(format!(
+                            "  Al sol {:.1}  Actividad {:.0}%",
+                            distance,
+                            comets::activity(distance) * 100.0
+                        ));
+                    }
+                }
+                lines.push(format!(
+                    "  Tamaño {:.2}  A la cámara {:.1}",
+                    body.bounding_radius(scene.mesh_radius),
+                    (body.position - camera.position).norm()
+                ));
+                lines.push(format!(
+                    "  Malla nivel {}  {} triángulos",
+                    body.lod_level,
+                    scene.spheres.mesh(body.lod_level).indices.len()
+                ));
+            }
+            None => lines.push("Sin selección".to_string()),
+        }
+        lines
+    }
+
+    pub fn render(&self, framebuffer: &mut Framebuffer, scene: &Scene, camera: &Camera, clock: &SimClock) {
+        if !self.visible {
+            return;
+        }
+
+        let style = TextStyle {
+            color: rgb_to_u32(190, 230, 190),
+            align: if self.corner.is_right() { Align::Right } else { Align::Left },
+            ..TextStyle::default()
+        };
+        let body = self.lines(framebuffer, scene, camera, clock).join("\n");
+        let (width, height) = text::measure(&body, &style);
+        let panel_width = width + HUD_PADDING * 2;
+        let panel_height = height + HUD_PADDING * 2;
+
+        let left = if self.corner.is_right() {
+            WIDTH as i32 - HUD_MARGIN - panel_width as i32
+        } else {
+            HUD_MARGIN
+        };
+        let top = if self.corner.is_bottom() {
+            HEIGHT as i32 - HUD_MARGIN - panel_height as i32
+        } else {
+            HUD_MARGIN
+        };
+
+        text::fill_rect(framebuffer, left, top, panel_width, panel_height, rgb_to_u32(0, 0, 0), 0.6);
+        let x = if self.corner.is_right() {
+            left + (panel_width - HUD_PADDING) as i32
+        } else {
+            left + HUD_PADDING as i32
+        };
+        text::draw_text(framebuffer, &body, x, top + HUD_PADDING as i32, &style);
+    }
+}
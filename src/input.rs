@@ -0,0 +1,504 @@
+use minifb::{Key, KeyRepeat, Window};
+use std::fs;
+
+/// Acciones de la aplicación que se pueden asignar a teclas
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Action {
+    Quit,
+    ToggleHelp,
+    ToggleHud,
+    ToggleMinimap,
+    CycleCameraMode,
+    NextBody,
+    PreviousBody,
+    RotateLeft,
+    RotateRight,
+    ZoomIn,
+    ZoomOut,
+    RaiseCamera,
+    LowerCamera,
+    ThrustForward,
+    ThrustBackward,
+    StrafeLeft,
+    StrafeRight,
+    ThrustUp,
+    ThrustDown,
+    PitchUp,
+    PitchDown,
+    YawLeft,
+    YawRight,
+    RollLeft,
+    RollRight,
+    ToggleGrid,
+    ToggleAxes,
+    ToggleOrbits,
+    ToggleLabels,
+    ToggleBounds,
+    RecordKeyframe,
+    TogglePlayback,
+    TogglePause,
+    StepFrame,
+    SpeedUp,
+    SlowDown,
+    ReverseTime,
+    ResetTime,
+    JumpToTime,
+    ToggleShipGravity,
+    ToggleStabilityAssist,
+    ToggleNBody,
+    CycleIntegrator,
+}
+
+impl Action {
+    pub const ALL: [Action; 43] = [
+        Action::Quit,
+        Action::ToggleHelp,
+        Action::ToggleHud,
+        Action::ToggleMinimap,
+        Action::CycleCameraMode,
+        Action::NextBody,
+        Action::PreviousBody,
+        Action::RotateLeft,
+        Action::RotateRight,
+        Action::ZoomIn,
+        Action::ZoomOut,
+        Action::RaiseCamera,
+        Action::LowerCamera,
+        Action::ThrustForward,
+        Action::ThrustBackward,
+        Action::StrafeLeft,
+        Action::StrafeRight,
+        Action::ThrustUp,
+        Action::ThrustDown,
+        Action::PitchUp,
+        Action::PitchDown,
+        Action::YawLeft,
+        Action::YawRight,
+        Action::RollLeft,
+        Action::RollRight,
+        Action::ToggleGrid,
+        Action::ToggleAxes,
+        Action::ToggleOrbits,
+        Action::ToggleLabels,
+        Action::ToggleBounds,
+        Action::RecordKeyframe,
+        Action::TogglePlayback,
+        Action::TogglePause,
+        Action::StepFrame,
+        Action::SpeedUp,
+        Action::SlowDown,
+        Action::ReverseTime,
+        Action::ResetTime,
+        Action::JumpToTime,
+        Action::ToggleShipGravity,
+        Action::ToggleStabilityAssist,
+        Action::ToggleNBody,
+        Action::CycleIntegrator,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Action::Quit => "quit",
+            Action::ToggleHelp => "toggle_help",
+            Action::ToggleHud => "toggle_hud",
+            Action::ToggleMinimap => "toggle_minimap",
+            Action::CycleCameraMode => "cycle_camera_mode",
+            Action::NextBody => "next_body",
+            Action::PreviousBody => "previous_body",
+            Action::RotateLeft => "rotate_left",
+            Action::RotateRight => "rotate_right",
+            Action::ZoomIn => "zoom_in",
+            Action::ZoomOut => "zoom_out",
+            Action::RaiseCamera => "raise_camera",
+            Action::LowerCamera => "lower_camera",
+            Action::ThrustForward => "thrust_forward",
+            Action::ThrustBackward => "thrust_backward",
+            Action::StrafeLeft => "strafe_left",
+            Action::StrafeRight => "strafe_right",
+            Action::ThrustUp => "thrust_up",
+            Action::ThrustDown => "thrust_down",
+            Action::PitchUp => "pitch_up",
+            Action::PitchDown => "pitch_down",
+            Action::YawLeft => "yaw_left",
+            Action::YawRight => "yaw_right",
+            Action::RollLeft => "roll_left",
+            Action::RollRight => "roll_right",
+            Action::ToggleGrid => "toggle_grid",
+            Action::ToggleAxes => "toggle_axes",
+            Action::ToggleOrbits => "toggle_orbits",
+            Action::ToggleLabels => "toggle_labels",
+            Action::ToggleBounds => "toggle_bounds",
+            Action::RecordKeyframe => "record_keyframe",
+            Action::TogglePlayback => "toggle_playback",
+            Action::TogglePause => "toggle_pause",
+            Action::StepFrame => "step_frame",
+            Action::SpeedUp => "speed_up",
+            Action::SlowDown => "slow_down",
+            Action::ReverseTime => "reverse_time",
+            Action::ResetTime => "reset_time",
+            Action::JumpToTime => "jump_to_time",
+            Action::ToggleShipGravity => "toggle_ship_gravity",
+            Action::ToggleStabilityAssist => "toggle_stability_assist",
+            Action::ToggleNBody => "toggle_nbody",
+            Action::CycleIntegrator => "cycle_integrator",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Action::ALL.iter().copied().find(|a| a.name() == name)
+    }
+}
+
+// Nombres de tecla aceptados en el archivo de configuración
+const KEY_NAMES: &[(&str, Key)] = &[
+    ("A", Key::A), ("B", Key::B), ("C", Key::C), ("D", Key::D), ("E", Key::E),
+    ("F", Key::F), ("G", Key::G), ("H", Key::H), ("I", Key::I), ("J", Key::J),
+    ("K", Key::K), ("L", Key::L), ("M", Key::M), ("N", Key::N), ("O", Key::O),
+    ("P", Key::P), ("Q", Key::Q), ("R", Key::R), ("S", Key::S), ("T", Key::T),
+    ("U", Key::U), ("V", Key::V), ("W", Key::W), ("X", Key::X), ("Y", Key::Y),
+    ("Z", Key::Z),
+    ("0", Key::Key0), ("1", Key::Key1), ("2", Key::Key2), ("3", Key::Key3), ("4", Key::Key4),
+    ("5", Key::Key5), ("6", Key::Key6), ("7", Key::Key7), ("8", Key::Key8), ("9", Key::Key9),
+    ("F1", Key::F1), ("F2", Key::F2), ("F3", Key::F3), ("F4", Key::F4), ("F5", Key::F5),
+    ("F6", Key::F6), ("F7", Key::F7), ("F8", Key::F8), ("F9", Key::F9), ("F10", Key::F10),
+    ("F11", Key::F11), ("F12", Key::F12),
+    ("Up", Key::Up), ("Down", Key::Down), ("Left", Key::Left), ("Right", Key::Right),
+    ("Space", Key::Space), ("Tab", Key::Tab), ("Enter", Key::Enter), ("Escape", Key::Escape),
+    ("Backspace", Key::Backspace), ("Delete", Key::Delete), ("Insert", Key::Insert),
+    ("Home", Key::Home), ("End", Key::End), ("PageUp", Key::PageUp), ("PageDown", Key::PageDown),
+    ("Minus", Key::Minus), ("Equal", Key::Equal), ("Comma", Key::Comma), ("Period", Key::Period),
+    ("Slash", Key::Slash), ("Backslash", Key::Backslash), ("Semicolon", Key::Semicolon),
+    ("Apostrophe", Key::Apostrophe), ("Backquote", Key::Backquote),
+    ("LeftBracket", Key::LeftBracket), ("RightBracket", Key::RightBracket),
+    ("NumPad0", Key::NumPad0), ("NumPad1", Key::NumPad1), ("NumPad2", Key::NumPad2),
+    ("NumPad3", Key::NumPad3), ("NumPad4", Key::NumPad4), ("NumPad5", Key::NumPad5),
+    ("NumPad6", Key::NumPad6), ("NumPad7", Key::NumPad7), ("NumPad8", Key::NumPad8),
+    ("NumPad9", Key::NumPad9), ("NumPadPlus", Key::NumPadPlus), ("NumPadMinus", Key::NumPadMinus),
+    ("NumPadEnter", Key::NumPadEnter),
+];
+
+fn key_from_name(name: &str) -> Option<Key> {
+    KEY_NAMES
+        .iter()
+        .find(|(n, _)| n.eq_ignore_ascii_case(name))
+        .map(|&(_, key)| key)
+}
+
+fn key_name(key: Key) -> &'static str {
+    KEY_NAMES
+        .iter()
+        .find(|&&(_, k)| k == key)
+        .map_or("?", |&(name, _)| name)
+}
+
+/// Tecla con los modificadores que deben estar pulsados junto a ella
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KeyBinding {
+    pub key: Key,
+    pub ctrl: bool,
+    pub shift: bool,
+    pub alt: bool,
+}
+
+impl KeyBinding {
+    pub const fn plain(key: Key) -> Self {
+        KeyBinding { key, ctrl: false, shift: false, alt: false }
+    }
+
+    pub const fn shift(key: Key) -> Self {
+        KeyBinding { key, ctrl: false, shift: true, alt: false }
+    }
+
+    /// Interpreta textos como "W", "Shift+Tab" o "Ctrl+Alt+F5"
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let mut binding = KeyBinding::plain(Key::Unknown);
+        let parts: Vec<&str> = text.split('+').map(str::trim).collect();
+        let (key_part, modifiers) = parts
+            .split_last()
+            .ok_or_else(|| format!("Combinación vacía: '{}'", text))?;
+
+        for modifier in modifiers {
+            match modifier.to_ascii_lowercase().as_str() {
+                "ctrl" | "control" => binding.ctrl = true,
+                "shift" => binding.shift = true,
+                "alt" => binding.alt = true,
+                _ => return Err(format!("Modificador desconocido: '{}'", modifier)),
+            }
+        }
+        binding.key = key_from_name(key_part)
+            .ok_or_else(|| format!("Tecla desconocida: '{}'", key_part))?;
+        Ok(binding)
+    }
+
+    fn modifiers_match(&self, window: &Window) -> bool {
+        let ctrl = window.is_key_down(Key::LeftCtrl) || window.is_key_down(Key::RightCtrl);
+        let shift = window.is_key_down(Key::LeftShift) || window.is_key_down(Key::RightShift);
+        let alt = window.is_key_down(Key::LeftAlt) || window.is_key_down(Key::RightAlt);
+        ctrl == self.ctrl && shift == self.shift && alt == self.alt
+    }
+
+    pub fn describe(&self) -> String {
+        let mut text = String::new();
+        if self.ctrl {
+            text.push_str("Ctrl+");
+        }
+        if self.alt {
+            text.push_str("Alt+");
+        }
+        if self.shift {
+            text.push_str("Shift+");
+        }
+        text.push_str(key_name(self.key));
+        text
+    }
+}
+
+/// Tabla de asignaciones acción → teclas
+pub struct InputMap {
+    bindings: Vec<(Action, Vec<KeyBinding>)>,
+    // Mientras se escribe en un campo de texto ninguna acción se dispara
+    suspended: bool,
+}
+
+impl Default for InputMap {
+    fn default() -> Self {
+        use KeyBinding as B;
+        let table: [(Action, &[KeyBinding]); 43] = [
+            (Action::Quit, &[B::plain(Key::Escape)]),
+            (Action::ToggleHelp, &[B::plain(Key::F1)]),
+            (Action::ToggleHud, &[B::plain(Key::H)]),
+            (Action::ToggleMinimap, &[B::plain(Key::M)]),
+            (Action::CycleCameraMode, &[B::plain(Key::C)]),
+            (Action::NextBody, &[B::plain(Key::Tab)]),
+            (Action::PreviousBody, &[B::shift(Key::Tab)]),
+            (Action::RotateLeft, &[B::plain(Key::Left)]),
+            (Action::RotateRight, &[B::plain(Key::Right)]),
+            (Action::ZoomIn, &[B::plain(Key::Up)]),
+            (Action::ZoomOut, &[B::plain(Key::Down)]),
+            (Action::RaiseCamera, &[B::plain(Key::W)]),
+            (Action::LowerCamera, &[B::plain(Key::S)]),
+            (Action::ThrustForward, &[B::plain(Key::W)]),
+            (Action::ThrustBackward, &[B::plain(Key::S)]),
+            (Action::StrafeLeft, &[B::plain(Key::A)]),
+            (Action::StrafeRight, &[B::plain(Key::D)]),
+            (Action::ThrustUp, &[B::plain(Key::R)]),
+            (Action::ThrustDown, &[B::plain(Key::F)]),
+            (Action::PitchUp, &[B::plain(Key::Up)]),
+            (Action::PitchDown, &[B::plain(Key::Down)]),
+            (Action::YawLeft, &[B::plain(Key::Left)]),
+            (Action::YawRight, &[B::plain(Key::Right)]),
+            (Action::RollLeft, &[B::plain(Key::Q)]),
+            (Action::RollRight, &[B::plain(Key::E)]),
+            (Action::ToggleGrid, &[B::plain(Key::G)]),
+            (Action::ToggleAxes, &[B::plain(Key::X)]),
+            (Action::ToggleOrbits, &[B::plain(Key::O)]),
+            (Action::ToggleLabels, &[B::plain(Key::L)]),
+            (Action::ToggleBounds, &[B::plain(Key::B)]),
+            (Action::RecordKeyframe, &[B::plain(Key::K)]),
+            (Action::TogglePlayback, &[B::plain(Key::P)]),
+            (Action::TogglePause, &[B::plain(Key::Space)]),
+            (Action::StepFrame, &[B::plain(Key::Period)]),
+            (Action::SpeedUp, &[B::plain(Key::Equal), B::plain(Key::NumPadPlus)]),
+            (Action::SlowDown, &[B::plain(Key::Minus), B::plain(Key::NumPadMinus)]),
+            (Action::ReverseTime, &[B::plain(Key::Backspace)]),
+            (Action::ResetTime, &[B::plain(Key::Home)]),
+            (Action::JumpToTime, &[B::plain(Key::T)]),
+            (Action::ToggleShipGravity, &[B::plain(Key::N)]),
+            (Action::ToggleStabilityAssist, &[B::plain(Key::V)]),
+            (Action::ToggleNBody, &[B::plain(Key::J)]),
+            (Action::CycleIntegrator, &[B::plain(Key::I)]),
+        ];
+
+        InputMap {
+            bindings: table
+                .iter()
+                .map(|(action, keys)| (*action, keys.to_vec()))
+                .collect(),
+            suspended: false,
+        }
+    }
+}
+
+impl InputMap {
+    fn keys_for(&self, action: Action) -> &[KeyBinding] {
+        self.bindings
+            .iter()
+            .find(|(a, _)| *a == action)
+            .map_or(&[], |(_, keys)| keys.as_slice())
+    }
+
+    /// Lee un archivo con líneas `acción = Tecla[, Otra]`. Cada acción
+    /// mencionada reemplaza sus teclas por defecto; `acción =` la deja libre.
+    pub fn load_overrides(&mut self, filename: &str) -> Result<(), String> {
+        let text = fs::read_to_string(filename)
+            .map_err(|e| format!("No se pudo abrir {}: {}", filename, e))?;
+
+        for (number, line) in text.lines().enumerate() {
+            let line = line.split('#').next().unwrap_or("").trim();
+            if line.is_empty() {
+                continue;
+            }
+            let location = format!("{}:{}", filename, number + 1);
+
+            let (name, keys) = line
+                .split_once('=')
+                .ok_or_else(|| format!("{}: se esperaba 'acción = tecla'", location))?;
+            let action = Action::from_name(name.trim())
+                .ok_or_else(|| format!("{}: acción desconocida '{}'", location, name.trim()))?;
+            let keys = keys
+                .split(',')
+                .map(str::trim)
+                .filter(|k| !k.is_empty())
+                .map(KeyBinding::parse)
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(|e| format!("{}: {}", location, e))?;
+
+            if let Some(entry) = self.bindings.iter_mut().find(|(a, _)| *a == action) {
+                entry.1 = keys;
+            }
+        }
+        Ok(())
+    }
+
+    /// Desactiva (o vuelve a activar) todas las acciones de teclado
+    pub fn set_suspended(&mut self, suspended: bool) {
+        self.suspended = suspended;
+    }
+
+    /// La acción está activa mientras se mantenga alguna de sus teclas
+    pub fn is_down(&self, window: &Window, action: Action) -> bool {
+        !self.suspended
+            && self
+                .keys_for(action)
+                .iter()
+                .any(|b| window.is_key_down(b.key) && b.modifiers_match(window))
+    }
+
+    /// La acción se disparó en este cuadro (sin repetición automática)
+    pub fn is_pressed(&self, window: &Window, action: Action) -> bool {
+        !self.suspended
+            && self
+                .keys_for(action)
+                .iter()
+                .any(|b| window.is_key_pressed(b.key, KeyRepeat::No) && b.modifiers_match(window))
+    }
+
+    /// Una línea por acción con sus teclas, para la pantalla de ayuda
+    pub fn help_lines(&self) -> Vec<String> {
+        // Columna de teclas alineada tras el nombre de acción más largo
+        let width = Action::ALL.iter().map(|a| a.name().len()).max().unwrap_or(0);
+        self.bindings
+            .iter()
+            .map(|(action, keys)| {
+                let keys = if keys.is_empty() {
+                    "-".to_string()
+                } else {
+                    keys.iter().map(KeyBinding::describe).collect::<Vec<_>>().join(", ")
+                };
+                format!("{:<width$} {}", action.name(), keys)
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write_temp(name: &str, contents: &str) -> String {
+        let path = std::env::temp_dir().join(format!("input-{}-{}", std::process::id(), name));
+        fs::write(&path, contents).unwrap();
+        path.to_string_lossy().into_owned()
+    }
+
+    #[test]
+    fn parse_reads_keys_and_modifiers() {
+        assert_eq!(KeyBinding::parse("W"), Ok(KeyBinding::plain(Key::W)));
+        assert_eq!(KeyBinding::parse("numpad5"), Ok(KeyBinding::plain(Key::NumPad5)));
+        assert_eq!(KeyBinding::parse("Shift+Tab"), Ok(KeyBinding::shift(Key::Tab)));
+        assert_eq!(
+            KeyBinding::parse("Ctrl + Alt + F5"),
+            Ok(KeyBinding { key: Key::F5, ctrl: true, shift: false, alt: true })
+        );
+        assert_eq!(
+            KeyBinding::parse("control+shift+alt+Home"),
+            Ok(KeyBinding { key: Key::Home, ctrl: true, shift: true, alt: true })
+        );
+    }
+
+    #[test]
+    fn describe_round_trips_through_parse() {
+        for text in ["W", "Shift+Tab", "Ctrl+Alt+F5", "Ctrl+Alt+Shift+PageDown"] {
+            let binding = KeyBinding::parse(text).unwrap();
+            assert_eq!(binding.describe(), text);
+            assert_eq!(KeyBinding::parse(&binding.describe()), Ok(binding));
+        }
+    }
+
+    #[test]
+    fn parse_rejects_unknown_keys_and_modifiers() {
+        assert_eq!(KeyBinding::parse("Foo"), Err("Tecla desconocida: 'Foo'".to_string()));
+        assert_eq!(KeyBinding::parse("Ctrl+"), Err("Tecla desconocida: ''".to_string()));
+        assert_eq!(
+            KeyBinding::parse("Super+W"),
+            Err("Modificador desconocido: 'Super'".to_string())
+        );
+    }
+
+    #[test]
+    fn action_names_round_trip() {
+        for action in Action::ALL {
+            assert_eq!(Action::from_name(action.name()), Some(action));
+        }
+        assert_eq!(Action::from_name("fly_to_the_moon"), None);
+    }
+
+    #[test]
+    fn load_overrides_replaces_and_clears_bindings() {
+        let filename = write_temp(
+            "ok.cfg",
+            "# Comentario\n\ntoggle_pause = P, Shift+Space  # pausa\nquit =\n",
+        );
+        let mut input = InputMap::default();
+        input.load_overrides(&filename).unwrap();
+        fs::remove_file(&filename).ok();
+
+        assert_eq!(
+            input.keys_for(Action::TogglePause),
+            &[KeyBinding::plain(Key::P), KeyBinding::shift(Key::Space)]
+        );
+        assert!(input.keys_for(Action::Quit).is_empty());
+        // Las acciones no mencionadas conservan sus teclas
+        assert_eq!(input.keys_for(Action::ToggleHelp), &[KeyBinding::plain(Key::F1)]);
+    }
+
+    #[test]
+    fn load_overrides_reports_the_bad_line() {
+        let cases = [
+            ("action.cfg", "quit = Escape\nfly = W\n", ":2: acción desconocida 'fly'"),
+            ("key.cfg", "quit = Escapee\n", ":1: Tecla desconocida: 'Escapee'"),
+            ("syntax.cfg", "\n\nquit Escape\n", ":3: se esperaba 'acción = tecla'"),
+        ];
+        for (name, contents, expected) in cases {
+            let filename = write_temp(name, contents);
+            let error = InputMap::default().load_overrides(&filename).unwrap_err();
+            fs::remove_file(&filename).ok();
+            assert_eq!(error, format!("{}{}", filename, expected));
+        }
+
+        let missing = InputMap::default().load_overrides("/no/existe/teclas.cfg").unwrap_err();
+        assert!(missing.starts_with("No se pudo abrir /no/existe/teclas.cfg"), "{}", missing);
+    }
+
+    #[test]
+    fn help_lines_align_the_key_column() {
+        let lines = InputMap::default().help_lines();
+        assert_eq!(lines.len(), Action::ALL.len());
+        let longest = "toggle_stability_assist".len();
+        for line in &lines {
+            // El nombre de la acción siempre va seguido de un espacio antes de la tecla
+            assert_eq!(line.as_bytes()[longest], b' ', "{}", line);
+            assert_ne!(line.as_bytes()[longest + 1], b' ', "{}", line);
+        }
+    }
+}
@@ -0,0 +1,104 @@
+use nalgebra::{Matrix4, Vector3 as Vec3, Vector4, UnitQuaternion};
+use crate::framebuffer::Framebuffer;
+use crate::shaders::ShaderColor;
+use crate::render::{draw_triangle, WIDTH, HEIGHT};
+
+// Luz mínima de las caras que no miran a la luz
+const AMBIENT: f32 = 0.25;
+
+/// Malla preparada para dibujarse muchas veces con sombreado plano: las
+/// normales de cara se calculan una sola vez
+pub struct InstancedMesh {
+    vertices: Vec<Vec3<f32>>,
+    triangles: Vec<[usize; 3]>,
+    face_normals: Vec<Vec3<f32>>,
+    /// Distancia máxima de un vértice al origen del modelo
+    pub radius: f32,
+}
+
+impl InstancedMesh {
+    /// Los triángulos van en sentido antihorario vistos desde fuera
+    pub fn new(vertices: Vec<Vec3<f32>>, triangles: Vec<[usize; 3]>) -> Self {
+        let face_normals = triangles
+            .iter()
+            .map(|&[a, b, c]| (vertices[b] - vertices[a]).cross(&(vertices[c] - vertices[a])).normalize())
+            .collect();
+        let radius = vertices.iter().map(|v| v.norm()).fold(0.0, f32::max);
+        InstancedMesh {
+            vertices,
+            triangles,
+            face_normals,
+            radius,
+        }
+    }
+}
+
+/// Transformación y color de una copia de la malla
+#[derive(Debug, Clone, Copy)]
+pub struct Instance {
+    pub position: Vec3<f32>,
+    pub rotation: UnitQuaternion<f32>,
+    /// Escala por eje, para que copias de la misma malla tengan proporciones distintas
+    pub scale: Vec3<f32>,
+    pub color: ShaderColor,
+}
+
+impl Instance {
+    fn model_matrix(&self) -> Matrix4<f32> {
+        Matrix4::new_translation(&self.position)
+            * self.rotation.to_homogeneous()
+            * Matrix4::new_nonuniform_scaling(&self.scale)
+    }
+}
+
+fn to_screen(clip: &Vector4<f32>) -> (i32, i32, f32) {
+    (
+        ((clip.x / clip.w + 1.0) * 0.5 * WIDTH as f32) as i32,
+        ((1.0 - clip.y / clip.w) * 0.5 * HEIGHT as f32) as i32,
+        clip.z / clip.w,
+    )
+}
+
+/// Dibuja cada instancia de `mesh` sin pasar por los shaders de planeta: una
+/// sola matriz por instancia lleva los vértices a la pantalla y cada cara toma
+/// un color plano. Las instancias que cruzan el plano cercano se omiten.
+pub fn render_instances<'a>(
+    framebuffer: &mut Framebuffer,
+    mesh: &InstancedMesh,
+    instances: impl IntoIterator<Item = &'a Instance>,
+    view_proj: &Matrix4<f32>,
+    light_direction: Vec3<f32>,
+) {
+    let mut screen = Vec::with_capacity(mesh.vertices.len());
+    for instance in instances {
+        let mvp = view_proj * instance.model_matrix();
+
+        screen.clear();
+        let mut visible = true;
+        for v in &mesh.vertices {
+            let clip = mvp * Vector4::new(v.x, v.y, v.z, 1.0);
+            if clip.w <= 0.0 {
+                visible = false;
+                break;
+            }
+            screen.push(to_screen(&clip));
+        }
+        if !visible {
+            continue;
+        }
+
+        for (&[a, b, c], normal) in mesh.triangles.iter().zip(&mesh.face_normals) {
+            // Normal transformada con la inversa de la escala para que siga
+            // siendo perpendicular a la cara
+            let world_normal = (instance.rotation * normal.component_div(&instance.scale)).normalize();
+            let intensity = AMBIENT + (1.0 - AMBIENT) * world_normal.dot(&light_direction).max(0.0);
+            let color = ShaderColor::new(
+                instance.color.r * intensity,
+                instance.color.g * intensity,
+                instance.color.b * intensity,
+                1.0,
+            );
+            draw_triangle(framebuffer, screen[a], screen[b], screen[c], color, color, color);
+        }
+    }
+}
@@ -0,0 +1,344 @@
+use std::fmt;
+
+/// Valor JSON. Los objetos guardan las claves en el orden del archivo.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Json {
+    Null,
+    Bool(bool),
+    Number(f64),
+    String(String),
+    Array(Vec<Json>),
+    Object(Vec<(String, Json)>),
+}
+
+/// Error de sintaxis; línea y columna empiezan en 1
+#[derive(Debug)]
+pub struct JsonError {
+    pub line: usize,
+    pub column: usize,
+    pub reason: String,
+}
+
+impl fmt::Display for JsonError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}:{}: {}", self.line, self.column, self.reason)
+    }
+}
+
+// Anidamiento máximo, para que un archivo malicioso no agote la pila
+const MAX_DEPTH: usize = 128;
+
+impl Json {
+    pub fn parse(text: &str) -> Result<Json, JsonError> {
+        let mut parser = Parser { text, position: 0 };
+        let value = parser.value(0)?;
+        parser.skip_whitespace();
+        if parser.position < text.len() {
+            return Err(parser.error("texto después del valor"));
+        }
+        Ok(value)
+    }
+
+    /// Valor de `key` si es un objeto que la tiene
+    pub fn get(&self, key: &str) -> Option<&Json> {
+        match self {
+            Json::Object(entries) => entries.iter().find(|(name, _)| name == key).map(|(_, value)| value),
+            _ => None,
+        }
+    }
+
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            Json::Number(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    pub fn as_f32(&self) -> Option<f32> {
+        self.as_f64().map(|value| value as f32)
+    }
+
+    /// El número si es un entero no negativo
+    pub fn as_usize(&self) -> Option<usize> {
+        self.as_f64()
+            .filter(|value| *value >= 0.0 && value.fract() == 0.0 && *value <= usize::MAX as f64)
+            .map(|value| value as usize)
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Json::String(text) => Some(text),
+            _ => None,
+        }
+    }
+
+    pub fn as_array(&self) -> Option<&[Json]> {
+        match self {
+            Json::Array(items) => Some(items),
+            _ => None,
+        }
+    }
+
+    /// Lista de números de largo `N`, como los vectores y matrices de glTF
+    pub fn as_floats<const N: usize>(&self) -> Option<[f32; N]> {
+        let items = self.as_array()?;
+        if items.len() != N {
+            return None;
+        }
+        let mut result = [0.0; N];
+        for (slot, item) in result.iter_mut().zip(items) {
+            *slot = item.as_f32()?;
+        }
+        Some(result)
+    }
+}
+
+struct Parser<'a> {
+    text: &'a str,
+    /// Posición en bytes
+    position: usize,
+}
+
+impl Parser<'_> {
+    fn error(&self, reason: &str) -> JsonError {
+        let before = &self.text[..self.position.min(self.text.len())];
+        let line = before.matches('\n').count() + 1;
+        let column = before.rsplit('\n').next().map_or(0, |last| last.chars().count()) + 1;
+        JsonError { line, column, reason: reason.to_string() }
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.text.as_bytes().get(self.position).copied()
+    }
+
+    fn skip_whitespace(&mut self) {
+        while matches!(self.peek(), Some(b' ' | b'\t' | b'\n' | b'\r')) {
+            self.position += 1;
+        }
+    }
+
+    fn expect(&mut self, byte: u8) -> Result<(), JsonError> {
+        self.skip_whitespace();
+        if self.peek() == Some(byte) {
+            self.position += 1;
+            Ok(())
+        } else {
+            Err(self.error(&format!("se esperaba '{}'", byte as char)))
+        }
+    }
+
+    fn value(&mut self, depth: usize) -> Result<Json, JsonError> {
+        if depth > MAX_DEPTH {
+            return Err(self.error("demasiado anidamiento"));
+        }
+        self.skip_whitespace();
+        match self.peek() {
+            Some(b'{') => self.object(depth),
+            Some(b'[') => self.array(depth),
+            Some(b'"') => self.string().map(Json::String),
+            Some(b'-' | b'0'..=b'9') => self.number(),
+            Some(_) => {
+                for (word, value) in [("true", Json::Bool(true)), ("false", Json::Bool(false)), ("null", Json::Null)] {
+                    if self.text[self.position..].starts_with(word) {
+                        self.position += word.len();
+                        return Ok(value);
+                    }
+                }
+                Err(self.error("valor no válido"))
+            }
+            None => Err(self.error("fin inesperado del texto")),
+        }
+    }
+
+    fn object(&mut self, depth: usize) -> Result<Json, JsonError> {
+        self.position += 1;
+        let mut entries = Vec::new();
+        self.skip_whitespace();
+        if self.peek() == Some(b'}') {
+            self.position += 1;
+            return Ok(Json::Object(entries));
+        }
+        loop {
+            self.skip_whitespace();
+            if self.peek() != Some(b'"') {
+                return Err(self.error("se esperaba una clave entre comillas"));
+            }
+            let key = self.string()?;
+            self.expect(b':')?;
+            entries.push((key, self.value(depth + 1)?));
+            self.skip_whitespace();
+            match self.peek() {
+                Some(b',') => self.position += 1,
+                Some(b'}') => {
+                    self.position += 1;
+                    return Ok(Json::Object(entries));
+                }
+                _ => return Err(self.error("se esperaba ',' o '}'")),
+            }
+        }
+    }
+
+    fn array(&mut self, depth: usize) -> Result<Json, JsonError> {
+        self.position += 1;
+        let mut items = Vec::new();
+        self.skip_whitespace();
+        if self.peek() == Some(b']') {
+            self.position += 1;
+            return Ok(Json::Array(items));
+        }
+        loop {
+            items.push(self.value(depth + 1)?);
+            self.skip_whitespace();
+            match self.peek() {
+                Some(b',') => self.position += 1,
+                Some(b']') => {
+                    self.position += 1;
+                    return Ok(Json::Array(items));
+                }
+                _ => return Err(self.error("se esperaba ',' o ']'")),
+            }
+        }
+    }
+
+    fn string(&mut self) -> Result<String, JsonError> {
+        self.position += 1;
+        let mut result = String::new();
+        loop {
+            let rest = &self.text[self.position..];
+            let Some(c) = rest.chars().next() else {
+                return Err(self.error("texto sin cerrar"));
+            };
+            match c {
+                '"' => {
+                    self.position += 1;
+                    return Ok(result);
+                }
+                '\\' => {
+                    self.position += 1;
+                    let escape = self.peek().ok_or_else(|| self.error("texto sin cerrar"))?;
+                    self.position += 1;
+                    match escape {
+                        b'"' => result.push('"'),
+                        b'\\' => result.push('\\'),
+                        b'/' => result.push('/'),
+                        b'b' => result.push('\u{8}'),
+                        b'f' => result.push('\u{c}'),
+                        b'n' => result.push('\n'),
+                        b'r' => result.push('\r'),
+                        b't' => result.push('\t'),
+                        b'u' => {
+                            let mut code = self.hex_escape()?;
+                            // Pares sustitutos para lo que no cabe en 16 bits
+                            if (0xD800..0xDC00).contains(&code) && self.text[self.position..].starts_with("\\u") {
+                                self.position += 2;
+                                let low = self.hex_escape()?;
+                                code = 0x10000 + ((code - 0xD800) << 10) + (low.wrapping_sub(0xDC00) & 0x3FF);
+                            }
+                            result.push(char::from_u32(code).unwrap_or('\u{FFFD}'));
+                        }
+                        _ => return Err(self.error("secuencia de escape no válida")),
+                    }
+                }
+                c if (c as u32) < 0x20 => return Err(self.error("carácter de control dentro de un texto")),
+                c => {
+                    result.push(c);
+                    self.position += c.len_utf8();
+                }
+            }
+        }
+    }
+
+    fn hex_escape(&mut self) -> Result<u32, JsonError> {
+        let digits = self.text.get(self.position..self.position + 4).ok_or_else(|| self.error("\\u incompleto"))?;
+        let code = u32::from_str_radix(digits, 16).map_err(|_| self.error("\\u no válido"))?;
+        self.position += 4;
+        Ok(code)
+    }
+
+    fn number(&mut self) -> Result<Json, JsonError> {
+        let start = self.position;
+        while matches!(self.peek(), Some(b'-' | b'+' | b'.' | b'e' | b'E' | b'0'..=b'9')) {
+            self.position += 1;
+        }
+        self.text[start..self.position]
+            .parse::<f64>()
+            .map(Json::Number)
+            .map_err(|_| {
+                self.position = start;
+                self.error("número no válido")
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn error(text: &str) -> (usize, usize) {
+        let error = Json::parse(text).unwrap_err();
+        (error.line, error.column)
+    }
+
+    #[test]
+    fn parses_a_small_document() {
+        let json = Json::parse(
+            r#"{
+                "asset": {"version": "2.0"},
+                "count": 3, "scale": [1, -2.5, 3e2],
+                "flags": [true, false, null], "empty": {}, "none": []
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(json.get("asset").and_then(|a| a.get("version")).and_then(Json::as_str), Some("2.0"));
+        assert_eq!(json.get("count").and_then(Json::as_usize), Some(3));
+        assert_eq!(json.get("scale").and_then(Json::as_floats), Some([1.0, -2.5, 300.0]));
+        assert_eq!(json.get("scale").and_then(Json::as_floats::<2>), None);
+        assert_eq!(
+            json.get("flags").and_then(Json::as_array),
+            Some(&[Json::Bool(true), Json::Bool(false), Json::Null][..])
+        );
+        assert_eq!(json.get("empty"), Some(&Json::Object(Vec::new())));
+        assert_eq!(json.get("none").and_then(Json::as_array).map(<[Json]>::len), Some(0));
+        assert_eq!(json.get("missing"), None);
+    }
+
+    #[test]
+    fn only_whole_non_negative_numbers_are_indices() {
+        assert_eq!(Json::Number(2.0).as_usize(), Some(2));
+        assert_eq!(Json::Number(2.5).as_usize(), None);
+        assert_eq!(Json::Number(-1.0).as_usize(), None);
+    }
+
+    #[test]
+    fn string_escapes() {
+        let json = Json::parse(r#""a\"b\\c\/d\n\té🚀""#).unwrap();
+        assert_eq!(json.as_str(), Some("a\"b\\c/d\n\té🚀"));
+        // Pares sustitutos de UTF-16
+        assert_eq!(Json::parse(r#""\ud83d\ude80 \u00e9""#).unwrap().as_str(), Some("🚀 é"));
+        // Un sustituto alto sin pareja no es un carácter
+        assert_eq!(Json::parse(r#""\ud83d""#).unwrap().as_str(), Some("\u{FFFD}"));
+        assert_eq!(error(r#""\x""#), (1, 4));
+        assert_eq!(error(r#""\u12""#), (1, 4));
+    }
+
+    #[test]
+    fn malformed_documents_report_line_and_column() {
+        assert_eq!(error("{\n  \"a\": 1,\n  \"b\" 2\n}"), (3, 7));
+        assert_eq!(error("[1, 2"), (1, 6));
+        assert_eq!(error("{\"a\": tru}"), (1, 7));
+        assert_eq!(error("{a: 1}"), (1, 2));
+        assert_eq!(error("[1] 2"), (1, 5));
+        assert_eq!(error("\"sin cerrar"), (1, 12));
+        assert_eq!(error("[1.2.3]"), (1, 2));
+        assert_eq!(error("\"a\nb\""), (1, 3));
+        assert_eq!(error(""), (1, 1));
+    }
+
+    #[test]
+    fn deep_nesting_is_rejected() {
+        let deep = "[".repeat(MAX_DEPTH + 2) + &"]".repeat(MAX_DEPTH + 2);
+        assert_eq!(Json::parse(&deep).unwrap_err().reason, "demasiado anidamiento");
+        let fine = "[".repeat(MAX_DEPTH) + &"]".repeat(MAX_DEPTH);
+        assert!(Json::parse(&fine).is_ok());
+    }
+}
@@ -0,0 +1,146 @@
+use nalgebra::{Matrix4, Vector3 as Vec3};
+use crate::framebuffer::{Framebuffer, rgb_to_u32};
+use crate::planet::Planet;
+use crate::picking::Ray;
+use crate::render::{draw_line_2d, project_vertex, WIDTH, HEIGHT};
+use crate::text::{self, TextStyle};
+
+// Distancias a la cámara entre las que la etiqueta pasa de opaca a invisible
+const FADE_NEAR: f32 = 25.0;
+const FADE_FAR: f32 = 90.0;
+const MIN_ALPHA: f32 = 0.05;
+// Largo de la línea guía en píxeles y hueco entre el texto y el rectángulo
+const LEADER_LENGTH: f32 = 16.0;
+const LABEL_GAP: i32 = 2;
+
+/// Rectángulo de pantalla ocupado por una etiqueta ya colocada
+#[derive(Debug, Clone, Copy)]
+struct LabelRect {
+    left: i32,
+    top: i32,
+    right: i32,
+    bottom: i32,
+}
+
+impl LabelRect {
+    fn overlaps(&self, other: &LabelRect) -> bool {
+        self.left < other.right && other.left < self.right && self.top < other.bottom && other.top < self.bottom
+    }
+
+    fn is_on_screen(&self) -> bool {
+        self.left >= 0 && self.top >= 0 && self.right <= WIDTH as i32 && self.bottom <= HEIGHT as i32
+    }
+}
+
+/// Etiqueta candidata: cuerpo visible con su proyección y opacidad
+struct Candidate<'a> {
+    body: &'a Planet,
+    center: (f32, f32),
+    screen_radius: f32,
+    distance: f32,
+    alpha: f32,
+}
+
+fn fade_alpha(distance: f32) -> f32 {
+    (1.0 - (distance - FADE_NEAR) / (FADE_FAR - FADE_NEAR)).clamp(0.0, 1.0)
+}
+
+/// Otro cuerpo tapa el centro de `body` visto desde la cámara
+fn is_occluded(body: &Planet, bodies: &[Planet], mesh_radius: f32, camera_position: Vec3<f32>) -> bool {
+    let to_body = body.position - camera_position;
+    let distance = to_body.norm();
+    if distance < f32::EPSILON {
+        return true;
+    }
+    let ray = Ray {
+        origin: camera_position,
+        direction: to_body / distance,
+    };
+    let own_surface = distance - body.bounding_radius(mesh_radius);
+
+    bodies
+        .iter()
+        .filter(|other| !std::ptr::eq(*other, body))
+        .filter_map(|other| ray.intersect_sphere(&other.bounding_sphere(mesh_radius)))
+        .any(|t| t < own_surface)
+}
+
+/// Nombre de cada cuerpo junto a su centro proyectado, unido con una línea
+/// guía. Las etiquetas se colocan de la más cercana a la más lejana probando
+/// varias posiciones alrededor del cuerpo; si ninguna queda libre se omite.
+pub fn render_labels(
+    framebuffer: &mut Framebuffer,
+    bodies: &[Planet],
+    mesh_radius: f32,
+    camera_position: Vec3<f32>,
+    camera_right: Vec3<f32>,
+    view_proj: &Matrix4<f32>,
+) {
+    let mut candidates: Vec<Candidate> = bodies
+        .iter()
+        .filter_map(|body| {
+            // project_vertex devuelve None si el cuerpo está detrás de la cámara
+            let (sx, sy, _) = project_vertex(&body.position, view_proj)?;
+            let distance = (body.position - camera_position).norm();
+            let alpha = fade_alpha(distance);
+            if alpha < MIN_ALPHA || is_occluded(body, bodies, mesh_radius, camera_position) {
+                return None;
+            }
+
+            let rim = body.position + camera_right * body.bounding_radius(mesh_radius);
+            let screen_radius = project_vertex(&rim, view_proj)
+                .map_or(0.0, |(rx, ry, _)| ((rx - sx) as f32).hypot((ry - sy) as f32));
+            Some(Candidate {
+                body,
+                center: (sx as f32, sy as f32),
+                screen_radius,
+                distance,
+                alpha,
+            })
+        })
+        .collect();
+    candidates.sort_by(|a, b| a.distance.total_cmp(&b.distance));
+
+    let mut placed: Vec<LabelRect> = Vec::new();
+    for candidate in candidates {
+        let style = TextStyle {
+            color: rgb_to_u32(200, 220, 255),
+            alpha: candidate.alpha,
+            ..TextStyle::default()
+        };
+        let (width, height) = text::measure(&candidate.body.name, &style);
+        let (width, height) = (width as i32, height as i32);
+
+        // Direcciones de la línea guía, por orden de preferencia
+        let directions = [(1.0, -1.0), (-1.0, -1.0), (1.0, 1.0), (-1.0, 1.0)];
+        let slot = [1.0, 2.5].iter().flat_map(|&reach| {
+            directions.iter().map(move |&(dx, dy)| (dx, dy, reach))
+        }).find_map(|(dx, dy, reach)| {
+            let start_offset = candidate.screen_radius * std::f32::consts::FRAC_1_SQRT_2;
+            let start = (candidate.center.0 + dx * start_offset, candidate.center.1 + dy * start_offset);
+            let length = LEADER_LENGTH * reach;
+            let elbow = (start.0 + dx * length, start.1 + dy * length);
+
+            let left = if dx > 0.0 { elbow.0 as i32 + LABEL_GAP } else { elbow.0 as i32 - LABEL_GAP - width };
+            let top = elbow.1 as i32 - height / 2;
+            let rect = LabelRect { left, top, right: left + width, bottom: top + height };
+
+            let free = rect.is_on_screen() && !placed.iter().any(|other| rect.overlaps(other));
+            free.then_some((start, elbow, rect))
+        });
+
+        if let Some((start, elbow, rect)) = slot {
+            // La línea no admite transparencia: se atenúa el color en su lugar,
+            // lo que sobre el fondo oscuro equivale a mezclarla
+            let shade = (200.0 * candidate.alpha) as u8;
+            draw_line_2d(framebuffer, start, elbow, rgb_to_u32(shade, shade, shade));
+            text::draw_text(framebuffer, &candidate.body.name, rect.left, rect.top, &style);
+            placed.push(LabelRect {
+                left: rect.left - LABEL_GAP,
+                top: rect.top - LABEL_GAP,
+                right: rect.right + LABEL_GAP,
+                bottom: rect.bottom + LABEL_GAP,
+            });
+        }
+    }
+}
@@ -0,0 +1,105 @@
+use crate::obj_loader::ObjModel;
+use crate::sphere::{self, SphereKind};
+
+// Radio en pantalla, en píxeles, a partir del cual se pasa del nivel 0 al 1.
+// Cada nivel duplica la resolución, así que el umbral se duplica también y
+// las aristas miden en pantalla más o menos lo mismo en todos los niveles.
+const FIRST_LEVEL_PIXELS: f32 = 12.0;
+// Margen entre el umbral de subida y el de bajada, para que un cuerpo que
+// está justo en el límite no salte de malla en cada cuadro
+const HYSTERESIS: f32 = 0.25;
+
+/// Mallas de la misma esfera de menor a mayor detalle. Cada cuerpo guarda el
+/// nivel que usó en el cuadro anterior y `select` lo cambia según su tamaño
+/// en pantalla.
+pub struct LodChain {
+    levels: Vec<ObjModel>,
+}
+
+impl LodChain {
+    /// Esferas de `kind` con los niveles de detalle de 0 a `max_detail`
+    pub fn generate(kind: SphereKind, max_detail: usize) -> Self {
+        LodChain {
+            levels: (0..=max_detail).map(|detail| sphere::generate(kind, detail)).collect(),
+        }
+    }
+
+    /// Cadena de una sola malla, para los modelos cargados de un archivo
+    pub fn single(mesh: ObjModel) -> Self {
+        LodChain { levels: vec![mesh] }
+    }
+
+    pub fn mesh(&self, level: usize) -> &ObjModel {
+        &self.levels[level.min(self.levels.len() - 1)]
+    }
+
+    /// La malla más detallada, que da el radio de colisión y de gravedad
+    pub fn finest(&self) -> &ObjModel {
+        &self.levels[self.levels.len() - 1]
+    }
+
+    /// Nivel para un cuerpo de `screen_radius` píxeles que usaba `current`.
+    /// Sube al pasar el umbral del nivel y solo baja cuando el radio cae un
+    /// `HYSTERESIS` por debajo del umbral con que subió.
+    pub fn select(&self, current: usize, screen_radius: f32) -> usize {
+        let threshold = |level: usize| FIRST_LEVEL_PIXELS * (1u32 << level.min(31)) as f32;
+        let mut level = current.min(self.levels.len() - 1);
+        while level + 1 < self.levels.len() && screen_radius > threshold(level) {
+            level += 1;
+        }
+        while level > 0 && screen_radius < threshold(level - 1) * (1.0 - HYSTERESIS) {
+            level -= 1;
+        }
+        level
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Niveles 0 a 3, con umbrales de subida de 12, 24 y 48 píxeles
+    fn chain() -> LodChain {
+        LodChain::generate(SphereKind::Ico, 3)
+    }
+
+    #[test]
+    fn first_level_threshold() {
+        let chain = chain();
+        assert_eq!(chain.select(0, FIRST_LEVEL_PIXELS), 0);
+        assert_eq!(chain.select(0, FIRST_LEVEL_PIXELS + 0.1), 1);
+        assert_eq!(chain.select(0, 0.0), 0);
+    }
+
+    #[test]
+    fn radius_inside_the_band_keeps_the_level() {
+        let chain = chain();
+        // Entre 9 y 12 píxeles tanto el nivel 0 como el 1 se mantienen
+        let low = FIRST_LEVEL_PIXELS * (1.0 - HYSTERESIS) + 0.1;
+        for radius in [low, 10.5, FIRST_LEVEL_PIXELS, low, 11.0] {
+            assert_eq!(chain.select(0, radius), 0, "radio {}", radius);
+            assert_eq!(chain.select(1, radius), 1, "radio {}", radius);
+        }
+    }
+
+    #[test]
+    fn crossing_the_band_changes_level() {
+        let chain = chain();
+        let mut level = 0;
+        let mut levels = Vec::new();
+        for radius in [10.0, 12.5, 10.0, 8.9, 10.0, 30.0, 20.0, 17.0, 100.0, 1.0] {
+            level = chain.select(level, radius);
+            levels.push(level);
+        }
+        assert_eq!(levels, vec![0, 1, 1, 0, 0, 2, 2, 1, 3, 0]);
+    }
+
+    #[test]
+    fn single_level_chain_always_uses_it() {
+        let chain = LodChain::single(sphere::generate(SphereKind::Uv, 1));
+        for (current, radius) in [(0, 0.0), (0, 1000.0), (5, 1000.0), (5, 1.0)] {
+            assert_eq!(chain.select(current, radius), 0);
+        }
+        assert_eq!(chain.mesh(4).indices.len(), chain.finest().indices.len());
+    }
+}
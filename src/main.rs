@@ -5,28 +5,313 @@ mod obj_loader;
 mod framebuffer;
 mod skybox;
 mod camera;
+mod camera_transition;
+mod camera_path;
 mod matrix;
 mod planet;
 mod render;
+mod picking;
+mod text;
+mod overlay;
+mod labels;
+mod scene;
+mod export;
+mod cli;
+mod input;
+mod clock;
+mod hud;
+mod minimap;
+mod ship;
+mod nbody;
+mod instancing;
+mod asteroids;
+mod orbit;
+mod comets;
+mod particles;
+mod material;
+mod json;
+mod png;
+mod gltf;
+mod sphere;
+mod lod;
+mod time_prompt;
 
-use minifb::{Key, Window, WindowOptions};
+use minifb::{KeyRepeat, MouseButton, MouseMode, Window, WindowOptions};
 use std::f32::consts::PI;
-use vector::Vector3;
-use shaders::ShaderUniforms;
-use planets::*;
-use obj_loader::ObjModel;
+use nalgebra::{Matrix4, Vector3 as Vec3};
+use obj_loader::{ObjModel, ObjMode};
 use framebuffer::Framebuffer;
-use skybox::render_skybox;
-use camera::Camera;
+use camera::{Camera, CameraMode};
+use camera_path::{CameraPath, Keyframe};
 use matrix::create_projection_matrix;
-use planet::Planet;
-use render::{WIDTH, HEIGHT, render_planet, render_ecliptic_plane, project_vertex};
+use render::{WIDTH, HEIGHT};
+use overlay::OverlayLayer;
+use scene::Scene;
+use cli::CliOptions;
+use input::{Action, InputMap};
+use clock::SimClock;
+use hud::Hud;
+use minimap::Minimap;
+use ship::ShipControls;
+use nbody::Scenario;
+use lod::LodChain;
+use time_prompt::{PromptEvent, TimePrompt};
 
 const FOV: f32 = PI / 3.0;
 const NEAR: f32 = 0.1;
 const FAR: f32 = 100.0;
 
+// Paso fijo de la simulación; cada cuadro exportado avanza exactamente uno
+const SIM_DT: f32 = 1.0 / 60.0;
+// Tiempo real máximo que se simula por cuadro, para no encadenar pasos
+// atrasados después de un bloqueo largo
+const MAX_FRAME_TIME: f32 = 0.25;
+
+// Velocidades angulares del vuelo libre (radianes por segundo)
+const FLIGHT_TURN_RATE: f32 = 1.2;
+const FLIGHT_ROLL_RATE: f32 = 1.6;
+const MOUSE_LOOK_SENSITIVITY: f32 = 0.004;
+
+// Controles de ratón en los modos órbita y seguimiento
+const MOUSE_ORBIT_SENSITIVITY: f32 = 0.008;
+const MOUSE_HEIGHT_SENSITIVITY: f32 = 0.05;
+const SCROLL_ZOOM_SPEED: f32 = 0.8;
+// Movimiento máximo (en píxeles) para considerar una pulsación como clic
+const CLICK_TOLERANCE: f32 = 4.0;
+
+/// Mitad del ángulo de visión horizontal, para dibujar el frustum en el minimapa
+fn horizontal_half_fov() -> f32 {
+    ((FOV / 2.0).tan() * WIDTH as f32 / HEIGHT as f32).atan()
+}
+
+// Acción que alterna cada capa de ayuda visual
+const OVERLAY_ACTIONS: [(Action, OverlayLayer); 5] = [
+    (Action::ToggleGrid, OverlayLayer::Grid),
+    (Action::ToggleAxes, OverlayLayer::Axes),
+    (Action::ToggleOrbits, OverlayLayer::Orbits),
+    (Action::ToggleLabels, OverlayLayer::Labels),
+    (Action::ToggleBounds, OverlayLayer::Bounds),
+];
+
+// Modelo de la nave que se carga si existe y no se indicó otro
+const DEFAULT_SHIP_MODEL: &str = "ship.obj";
+
+// Archivo de teclas que se carga si existe y no se indicó otro
+const DEFAULT_KEYS_FILE: &str = "keybindings.cfg";
+
+/// Carga un modelo OBJ o glTF según la extensión, o termina el programa
+/// explicando qué falló
+fn load_model(filename: &str, mode: ObjMode) -> ObjModel {
+    let extension = std::path::Path::new(filename).extension().and_then(|e| e.to_str()).map(str::to_lowercase);
+    let loaded = match extension.as_deref() {
+        Some("gltf" | "glb") => gltf::load(filename)
+            .map(|(model, warnings)| (model, warnings.iter().map(ToString::to_string).collect::<Vec<_>>()))
+            .map_err(|e| e.to_string()),
+        _ => ObjModel::load(filename, mode)
+            .map(|(model, warnings)| (model, warnings.iter().map(ToString::to_string).collect()))
+            .map_err(|e| e.to_string()),
+    };
+    match loaded {
+        Ok((model, warnings)) => {
+            for warning in warnings {
+                eprintln!("Aviso: {}", warning);
+            }
+            model
+        }
+        Err(e) => {
+            eprintln!("{}", e);
+            std::process::exit(1);
+        }
+    }
+}
+
 fn main() {
+    let options = CliOptions::parse(std::env::args().skip(1)).unwrap_or_else(|e| {
+        eprintln!("{}\n\n{}", e, cli::USAGE);
+        std::process::exit(2);
+    });
+    if options.show_help {
+        println!("{}", cli::USAGE);
+        return;
+    }
+
+    let spheres = match &options.sphere_obj {
+        Some(filename) => {
+            let mut model = load_model(filename, options.obj_mode);
+            // Las UV de sphere.obj cubren la textura entera en cada cara; los
+            // shaders de los planetas esperan latitud y longitud
+            model.apply_spherical_uvs();
+            LodChain::single(model)
+        }
+        None if options.sphere_lod => LodChain::generate(options.sphere_kind, options.sphere_detail),
+        None => LodChain::single(sphere::generate(options.sphere_kind, options.sphere_detail)),
+    };
+    println!(
+        "Modelo cargado: {} vértices, {} triángulos",
+        spheres.finest().vertices.len(),
+        spheres.finest().indices.len()
+    );
+    let ship_model = match &options.ship_model {
+        Some(filename) => load_model(filename, options.obj_mode),
+        None if std::path::Path::new(DEFAULT_SHIP_MODEL).exists() => load_model(DEFAULT_SHIP_MODEL, options.obj_mode),
+        None => ship::builtin_model(),
+    };
+    let mut scene = Scene::solar_system(spheres, ship_model, options.asteroid_count);
+    scene.ship.gravity = options.ship_gravity;
+    match options.follow_body {
+        Some(index) if index >= scene.bodies.len() => {
+            eprintln!("--follow: no existe el cuerpo {} (hay {})", index, scene.bodies.len());
+            std::process::exit(2);
+        }
+        index => scene.selected = index,
+    }
+    for &(body, altitude) in &options.min_altitudes {
+        match body {
+            Some(index) if index < scene.bodies.len() => scene.bodies[index].min_altitude = altitude,
+            Some(index) => {
+                eprintln!("--min-altitude: no existe el cuerpo {}", index);
+                std::process::exit(2);
+            }
+            None => scene.bodies.iter_mut().for_each(|b| b.min_altitude = altitude),
+        }
+    }
+
+    let mut camera = Camera::new(40.0);
+    if let Some((distance, height)) = options.follow_offset {
+        camera.set_follow_offset(distance, height);
+    }
+    if let Some(seconds) = options.transition_duration {
+        camera.set_transition_duration(seconds);
+    }
+    camera.set_chase_goal(scene.ship.position, scene.ship.orientation);
+    camera.start_in_mode(options.camera_mode);
+
+    let camera_path = match &options.camera_path {
+        Some(filename) if std::path::Path::new(filename).exists() => {
+            CameraPath::load(filename).unwrap_or_else(|e| {
+                eprintln!("{}", e);
+                std::process::exit(1);
+            })
+        }
+        _ => CameraPath::new(options.path_interpolation),
+    };
+
+    let mut clock = SimClock::new(options.start_time, 1.0);
+    clock.set_rate(options.time_scale);
+    if let Some(scenario) = options.nbody {
+        scene.set_time(clock.time());
+        scene.enable_nbody(scenario, options.integrator, clock.time());
+    }
+
+    // Usar la función manual de creación de matriz de proyección
+    let projection = create_projection_matrix(
+        FOV,
+        WIDTH as f32 / HEIGHT as f32,
+        NEAR,
+        FAR,
+    );
+
+    if let Some(directory) = &options.export_dir {
+        if let Err(e) = run_headless(&options, directory, scene, camera, clock, &camera_path, &projection) {
+            eprintln!("{}", e);
+            std::process::exit(1);
+        }
+    } else {
+        run_window(&options, scene, camera, clock, camera_path, &projection);
+    }
+}
+
+/// Renderiza cuadros sin ventana y los guarda como PPM. Usa el mismo paso fijo
+/// de simulación y el mismo recorrido de cámara que la ejecución en vivo.
+fn run_headless(
+    options: &CliOptions,
+    directory: &str,
+    mut scene: Scene,
+    mut camera: Camera,
+    mut clock: SimClock,
+    camera_path: &CameraPath,
+    projection: &Matrix4<f32>,
+) -> Result<(), String> {
+    std::fs::create_dir_all(directory)
+        .map_err(|e| format!("No se pudo crear el directorio {}: {}", directory, e))?;
+
+    let mut framebuffer = Framebuffer::new(WIDTH, HEIGHT);
+    let mut hud = Hud::new(options.hud_corner, options.hud_visible.unwrap_or(false));
+    let minimap = Minimap::new(options.minimap_corner, options.minimap_visible.unwrap_or(false));
+    let mut frame_start = std::time::Instant::now();
+
+    for frame in 0..options.export_frames {
+        let time = clock.time();
+        scene.set_time(time);
+        scene.ship.interpolate(1.0);
+
+        match camera_path.sample(time) {
+            Some((position, target)) => camera.set_pose(position, target),
+            None => {
+                camera.set_follow_goal(scene.focus_body().position);
+                camera.set_chase_goal(scene.ship.render_position, scene.ship.render_orientation);
+                camera.update(SIM_DT, &scene.obstacles());
+            }
+        }
+
+        scene.update_lod(&camera, projection);
+        scene.render(&mut framebuffer, &camera, projection, &options.overlays, time);
+        hud.render(&mut framebuffer, &scene, &camera, &clock);
+        minimap.render(&mut framebuffer, &scene, &camera, horizontal_half_fov());
+
+        let filename = format!("{}/frame_{:05}.ppm", directory, frame);
+        export::write_ppm(&filename, &framebuffer, WIDTH, HEIGHT)?;
+        // La nave no tiene mandos al exportar: sigue su inercia
+        let sim_dt = clock.advance(SIM_DT);
+        scene.set_time(clock.time());
+        scene.step_ship(sim_dt, &ShipControls::default());
+        scene.step_particles(sim_dt);
+
+        let now = std::time::Instant::now();
+        hud.record_frame((now - frame_start).as_secs_f32());
+        frame_start = now;
+    }
+
+    println!("{} cuadros exportados en {}", options.export_frames, directory);
+    if let Some(system) = &scene.nbody {
+        println!(
+            "Integrador {}: deriva de energía {:+.3e}, de momento {:.3e}",
+            system.integrator().name(),
+            system.energy_drift(),
+            system.momentum_drift()
+        );
+    }
+    Ok(())
+}
+
+fn run_window(
+    options: &CliOptions,
+    mut scene: Scene,
+    mut camera: Camera,
+    mut clock: SimClock,
+    mut camera_path: CameraPath,
+    projection: &Matrix4<f32>,
+) {
+    let mut overlays = options.overlays;
+
+    let mut input = InputMap::default();
+    let keys_file = match &options.key_bindings {
+        Some(filename) => Some(filename.as_str()),
+        None if std::path::Path::new(DEFAULT_KEYS_FILE).exists() => Some(DEFAULT_KEYS_FILE),
+        None => None,
+    };
+    if let Some(filename) = keys_file {
+        if let Err(e) = input.load_overrides(filename) {
+            eprintln!("{}", e);
+            std::process::exit(1);
+        }
+    }
+    let help_lines = input.help_lines();
+    let mut show_help = false;
+    let mut time_prompt = TimePrompt::default();
+    let mut hud = Hud::new(options.hud_corner, options.hud_visible.unwrap_or(true));
+    let mut minimap = Minimap::new(options.minimap_corner, options.minimap_visible.unwrap_or(true));
+
     let mut window = Window::new(
         "Sistema Solar - Space Travel",
         WIDTH,
@@ -41,159 +326,315 @@ fn main() {
 
     let mut framebuffer = Framebuffer::new(WIDTH, HEIGHT);
 
-    let mut camera = Camera::new(40.0);
-    // Usar la función manual de creación de matriz de proyección
-    let projection = create_projection_matrix(
-        FOV,                     
-        WIDTH as f32 / HEIGHT as f32,  
-        NEAR,                     
-        FAR,                     
-    );
+    let mut last_frame = std::time::Instant::now();
+    // Tiempo real pendiente de simular, siempre menor que un paso
+    let mut accumulator = 0.0;
+    let mut last_mouse_pos: Option<(f32, f32)> = None;
+    // Posición donde se pulsó el botón izquierdo y si ya se arrastró desde ahí
+    let mut left_press: Option<(f32, f32)> = None;
+    let mut dragging = false;
+    let mut right_was_down = false;
+    let half_fov = horizontal_half_fov();
+    let path_filename = options
+        .camera_path
+        .clone()
+        .unwrap_or_else(|| "camera_path.txt".to_string());
+    // Durante la reproducción el recorrido se muestrea con el reloj de la simulación
+    let mut playing = options.play_path && !camera_path.is_empty();
+    if playing {
+        clock.jump_to(camera_path.start_time());
+    }
 
-    // Crear el sol
-    let sun = Planet::new(
-        Box::new(SunShader),
-        0.0,   
-        2.3,   
-        0.1,
-        0.0,
-        0.0,  
-    );
+    // Escape cierra el campo de salto en vez de la ventana, así que salir
+    // solo cuenta en el cuadro en que se pulsa
+    while window.is_open() && !input.is_pressed(&window, Action::Quit) {
+        let now = std::time::Instant::now();
+        let dt = (now - last_frame).as_secs_f32().min(MAX_FRAME_TIME);
+        last_frame = now;
+        hud.record_frame(dt);
 
-    //Crear planetas
-    let mut planets = vec![
-        Planet::new(
-            Box::new(RockyPlanetShader),
-            2.7,   // Radio orbital
-            1.2,   // Tamaño del planeta
-            0.2,   
-            0.3,   // Velocidad orbital
-            0.0,   // Ángulo inicial
-        ),
-        Planet::new(
-            Box::new(GasPlanetShader),
-            5.3,   
-            0.7,   
-            0.15,
-            0.2,   
-            std::f32::consts::PI / 2.5,  
-        ),
-        Planet::new(
-            Box::new(CrystalPlanetShader),
-            6.5,  
-            0.85,  
-            0.2,
-            0.15,  
-            std::f32::consts::PI,  
-        ),
-        Planet::new(
-            Box::new(NebulaPlanetShader),
-            9.4,  
-            0.6,  
-            0.25,
-            0.12,  
-            4.0 * std::f32::consts::PI / 3.0,  
-        ),
-        Planet::new(
-            Box::new(MetallicPlanetShader),
-            8.6,  
-            1.1,   
-            0.12,
-            0.1,   
-            3.0 * std::f32::consts::PI / 2.0, 
-        ),
-    ];
-
-    let sphere_model = ObjModel::load("sphere.obj")
-        .expect("No se pudo cargar el archivo sphere.obj");
-    
-    let vertices = sphere_model.vertices;
-    let normals = sphere_model.normals;
-    let uvs = sphere_model.uvs;
-    let indices = sphere_model.indices;
-
-    println!("Modelo cargado: {} vértices, {} triángulos", vertices.len(), indices.len());
-
-    let start_time = std::time::Instant::now();
-    let mut frame_count = 0;
-    let mut fps_timer = std::time::Instant::now();
-
-    while window.is_open() && !window.is_key_down(Key::Escape) {
-        let dt = 0.016; 
-        let time = start_time.elapsed().as_secs_f32();
-
-        if window.is_key_down(Key::Left) {
-            camera.rotate(-0.05);
-        }
-        if window.is_key_down(Key::Right) {
-            camera.rotate(0.05);
-        }
-        if window.is_key_down(Key::Up) {
-            camera.zoom(-0.5);
-        }
-        if window.is_key_down(Key::Down) {
-            camera.zoom(0.5);
-        }
-        if window.is_key_down(Key::W) {
-            camera.change_height(0.3);
-        }
-        if window.is_key_down(Key::S) {
-            camera.change_height(-0.3);
-        }
-
-        for planet in &mut planets {
-            planet.update(dt);
-        }
-
-        framebuffer.clear();
-
-        let view = camera.get_view_matrix();
-        let view_proj = projection * view;
-
-        let uniforms = ShaderUniforms {
-            time,
-            light_direction: Vector3::new(0.0, 0.0, 1.0).normalize(),
-            camera_position: Vector3::new(camera.position.x, camera.position.y, camera.position.z),
-        };
+        // Mientras se escribe un instante las teclas no disparan acciones,
+        // tampoco en el cuadro en que se confirma o cancela
+        let typing = time_prompt.is_active();
+        if typing {
+            if let PromptEvent::Jump(time) = time_prompt.handle_keys(&window.get_keys_pressed(KeyRepeat::Yes)) {
+                clock.jump_to(time);
+            }
+        }
+        input.set_suspended(typing);
+
+        if input.is_pressed(&window, Action::TogglePause) {
+            clock.toggle_pause();
+        }
+        if input.is_pressed(&window, Action::StepFrame) {
+            clock.step();
+        }
+        if input.is_pressed(&window, Action::SpeedUp) {
+            clock.speed_up();
+        }
+        if input.is_pressed(&window, Action::SlowDown) {
+            clock.slow_down();
+        }
+        if input.is_pressed(&window, Action::ReverseTime) {
+            clock.reverse();
+        }
+        if input.is_pressed(&window, Action::ResetTime) {
+            clock.jump_to(0.0);
+        }
+        if input.is_pressed(&window, Action::JumpToTime) {
+            time_prompt.open();
+        }
+
+        if input.is_pressed(&window, Action::RecordKeyframe) {
+            let time = clock.time();
+            camera_path.add_keyframe(Keyframe {
+                time,
+                position: camera.position,
+                target: camera.target,
+            });
+            match camera_path.save(&path_filename) {
+                Ok(()) => println!(
+                    "Fotograma clave {} en t={:.2}s guardado en {}",
+                    camera_path.keyframes.len(),
+                    time,
+                    path_filename
+                ),
+                Err(e) => eprintln!("{}", e),
+            }
+        }
+        if input.is_pressed(&window, Action::TogglePlayback) {
+            if playing {
+                camera.release_pose();
+                playing = false;
+            } else if !camera_path.is_empty() {
+                clock.jump_to(camera_path.start_time());
+                playing = true;
+            }
+        }
 
-        render_skybox(&mut framebuffer, &view_proj, time, project_vertex);
-        render_ecliptic_plane(&mut framebuffer, &view_proj);
-        render_planet(
-            &mut framebuffer,
-            &sun,
-            &vertices,
-            &normals,
-            &uvs,
-            &indices,
-            &view_proj,
-            &uniforms,
+        if input.is_pressed(&window, Action::CycleCameraMode) {
+            camera.toggle_mode();
+        }
+        if input.is_pressed(&window, Action::ToggleHelp) {
+            show_help = !show_help;
+        }
+        if input.is_pressed(&window, Action::ToggleHud) {
+            hud.toggle();
+        }
+        if input.is_pressed(&window, Action::ToggleMinimap) {
+            minimap.toggle();
+        }
+        if input.is_pressed(&window, Action::ToggleShipGravity) {
+            scene.ship.gravity = !scene.ship.gravity;
+        }
+        if input.is_pressed(&window, Action::ToggleStabilityAssist) {
+            scene.ship.stability_assist = !scene.ship.stability_assist;
+        }
+        if input.is_pressed(&window, Action::ToggleNBody) {
+            if scene.nbody.is_some() {
+                scene.disable_nbody();
+            } else {
+                // Arrancar desde donde están los cuerpos en el paso actual
+                scene.set_time(clock.time());
+                let scenario = options.nbody.unwrap_or(Scenario::Circular);
+                scene.enable_nbody(scenario, options.integrator, clock.time());
+            }
+        }
+        if input.is_pressed(&window, Action::CycleIntegrator) {
+            if let Some(system) = scene.nbody.as_mut() {
+                system.set_integrator(system.integrator().next());
+            }
+        }
+
+        let mouse_pos = window.get_mouse_pos(MouseMode::Pass);
+        for (action, backwards) in [(Action::NextBody, false), (Action::PreviousBody, true)] {
+            if input.is_pressed(&window, action) {
+                scene.cycle_selection(backwards);
+                camera.retarget();
+            }
+        }
+
+        // Arrastrar con el botón izquierdo gira la cámara; un clic sin
+        // arrastre selecciona el cuerpo bajo el cursor, en la escena o en el
+        // minimapa
+        let left_down = window.get_mouse_down(MouseButton::Left);
+        match (left_down, left_press, mouse_pos) {
+            (true, None, Some(pos)) => {
+                left_press = Some(pos);
+                dragging = false;
+            }
+            (true, Some(start), Some(pos)) if !minimap.contains(start.0, start.1) => {
+                if (pos.0 - start.0).abs() > CLICK_TOLERANCE || (pos.1 - start.1).abs() > CLICK_TOLERANCE {
+                    dragging = true;
+                }
+                if let (true, Some(previous)) = (dragging, last_mouse_pos) {
+                    if matches!(camera.mode, CameraMode::Orbit | CameraMode::Follow) {
+                        camera.rotate((pos.0 - previous.0) * MOUSE_ORBIT_SENSITIVITY);
+                        camera.change_height((pos.1 - previous.1) * MOUSE_HEIGHT_SENSITIVITY);
+                    }
+                }
+            }
+            (false, Some(start), _) => {
+                if minimap.contains(start.0, start.1) {
+                    if let Some(index) = minimap.body_at(start.0, start.1, &scene.bodies) {
+                        if scene.selected != Some(index) {
+                            scene.selected = Some(index);
+                            camera.retarget();
+                        }
+                    }
+                } else if !dragging {
+                    let view_proj = projection * camera.get_view_matrix();
+                    if let Some(ray) = picking::screen_ray(start.0, start.1, &view_proj) {
+                        let previous = scene.selected;
+                        if scene.pick(&ray).is_some() && scene.selected != previous {
+                            camera.retarget();
+                        }
+                    }
+                }
+                left_press = None;
+                dragging = false;
+            }
+            _ => {}
+        }
+
+        // Clic derecho sobre un cuerpo del minimapa: seleccionarlo y volar hasta él
+        let right_down = window.get_mouse_down(MouseButton::Right);
+        if right_down && !right_was_down {
+            if let Some(index) = mouse_pos.and_then(|(x, y)| minimap.body_at(x, y, &scene.bodies)) {
+                scene.selected = Some(index);
+                if camera.mode == CameraMode::Follow {
+                    camera.retarget();
+                } else {
+                    camera.set_mode(CameraMode::Follow);
+                }
+            }
+        }
+        right_was_down = right_down;
+
+        if let Some((_, scroll)) = window.get_scroll_wheel() {
+            if matches!(camera.mode, CameraMode::Orbit | CameraMode::Follow) {
+                camera.zoom(-scroll * SCROLL_ZOOM_SPEED);
+            }
+        }
+
+        let axis = |positive: Action, negative: Action| -> f32 {
+            let mut value = 0.0;
+            if input.is_down(&window, positive) {
+                value += 1.0;
+            }
+            if input.is_down(&window, negative) {
+                value -= 1.0;
+            }
+            value
+        };
+        let thrust = Vec3::new(
+            axis(Action::StrafeRight, Action::StrafeLeft),
+            axis(Action::ThrustUp, Action::ThrustDown),
+            axis(Action::ThrustForward, Action::ThrustBackward),
         );
-        for planet in &planets {
-            render_planet(
-                &mut framebuffer,
-                planet,
-                &vertices,
-                &normals,
-                &uvs,
-                &indices,
-                &view_proj,
-                &uniforms,
-            );
+        let rotation = Vec3::new(
+            axis(Action::PitchUp, Action::PitchDown),
+            axis(Action::YawLeft, Action::YawRight),
+            axis(Action::RollLeft, Action::RollRight),
+        );
+        // Los mandos de vuelo mueven la nave solo en el modo persecución
+        let mut ship_controls = ShipControls::default();
+
+        match camera.mode {
+            CameraMode::Orbit | CameraMode::Follow => {
+                if input.is_down(&window, Action::RotateLeft) {
+                    camera.rotate(-0.05);
+                }
+                if input.is_down(&window, Action::RotateRight) {
+                    camera.rotate(0.05);
+                }
+                if input.is_down(&window, Action::ZoomIn) {
+                    camera.zoom(-0.5);
+                }
+                if input.is_down(&window, Action::ZoomOut) {
+                    camera.zoom(0.5);
+                }
+                if input.is_down(&window, Action::RaiseCamera) {
+                    camera.change_height(0.3);
+                }
+                if input.is_down(&window, Action::LowerCamera) {
+                    camera.change_height(-0.3);
+                }
+            }
+            CameraMode::FreeFlight => {
+                camera.thrust(thrust, dt);
+                camera.pitch(rotation.x * FLIGHT_TURN_RATE * dt);
+                camera.yaw(rotation.y * FLIGHT_TURN_RATE * dt);
+                camera.roll(rotation.z * FLIGHT_ROLL_RATE * dt);
+
+                // Mirar con el ratón mientras se mantiene el botón derecho
+                if let (Some((mx, my)), Some((px, py))) = (mouse_pos, last_mouse_pos) {
+                    if window.get_mouse_down(MouseButton::Right) {
+                        camera.yaw(-(mx - px) * MOUSE_LOOK_SENSITIVITY);
+                        camera.pitch(-(my - py) * MOUSE_LOOK_SENSITIVITY);
+                    }
+                }
+            }
+            CameraMode::Chase => {
+                ship_controls = ShipControls { thrust, rotation };
+            }
+        }
+        last_mouse_pos = mouse_pos;
+
+        for (action, layer) in OVERLAY_ACTIONS {
+            if input.is_pressed(&window, action) {
+                overlays.toggle(layer);
+            }
+        }
+
+        accumulator += dt;
+        while accumulator >= SIM_DT {
+            let sim_dt = clock.advance(SIM_DT);
+            scene.set_time(clock.time());
+            scene.step_ship(sim_dt, &ship_controls);
+            scene.step_particles(sim_dt);
+            accumulator -= SIM_DT;
+        }
+        // Dibujar entre los dos últimos pasos para que el movimiento no
+        // dependa de cuántos pasos cupieron en este cuadro
+        let alpha = accumulator / SIM_DT;
+        let time = clock.interpolated_time(alpha);
+        scene.set_time(time);
+        scene.ship.interpolate(alpha);
+
+        // Al salir del recorrido por cualquiera de sus extremos (el tiempo
+        // puede correr hacia atrás) la cámara vuelve a los controles
+        if playing && (time > camera_path.end_time() || time < camera_path.start_time()) {
+            camera.release_pose();
+            playing = false;
+        }
+        // Durante la reproducción el recorrido manda sobre los controles
+        match camera_path.sample(time).filter(|_| playing) {
+            Some((position, target)) => camera.set_pose(position, target),
+            None => {
+                camera.set_follow_goal(scene.focus_body().position);
+                camera.set_chase_goal(scene.ship.render_position, scene.ship.render_orientation);
+                camera.update(dt, &scene.obstacles());
+            }
+        }
+
+        scene.update_lod(&camera, projection);
+        scene.render(&mut framebuffer, &camera, projection, &overlays, time);
+        if hud.visible {
+            hud.render(&mut framebuffer, &scene, &camera, &clock);
+        } else {
+            overlay::render_status(&mut framebuffer, &clock.status());
+        }
+        minimap.render(&mut framebuffer, &scene, &camera, half_fov);
+        if show_help {
+            overlay::render_help(&mut framebuffer, &help_lines);
+        }
+        if let Some(label) = time_prompt.label() {
+            overlay::render_prompt(&mut framebuffer, &label);
         }
 
         window
             .update_with_buffer(framebuffer.get_buffer(), WIDTH, HEIGHT)
             .unwrap();
-
-        // Calcular FPS
-        frame_count += 1;
-        let elapsed = fps_timer.elapsed().as_secs_f32();
-        if elapsed >= 1.0 {
-            let current_fps = frame_count as f32 / elapsed;
-            frame_count = 0;
-            fps_timer = std::time::Instant::now();
-            
-            window.set_title(&format!("Space Travel | FPS: {:.1}", current_fps));
-        }
     }
 }
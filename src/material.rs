@@ -0,0 +1,234 @@
+use nalgebra::{UnitQuaternion, Vector3 as Vec3};
+use std::fs;
+use crate::vector::Vector3;
+use crate::shaders::{ShaderColor, ShaderUniforms, PlanetShader};
+use crate::png;
+
+// Luz ambiente mínima para que las caras en sombra no queden negras
+const AMBIENT: f32 = 0.4;
+// Paso en UV para estimar la pendiente del mapa de relieve
+const BUMP_STEP: f32 = 1.0 / 256.0;
+// Altura del relieve con brillo 1, en unidades de UV
+const BUMP_DEPTH: f32 = 1.0 / 64.0;
+
+/// Imagen RGB para los mapas de un material, con valores de 0 a 1
+pub struct Texture {
+    width: usize,
+    height: usize,
+    pixels: Vec<[f32; 3]>,
+}
+
+impl Texture {
+    pub fn load(path: &str) -> Result<Self, String> {
+        let data = fs::read(path).map_err(|error| error.to_string())?;
+        Texture::decode(&data)
+    }
+
+    /// Lee una imagen PNG o PPM (P3 o P6, el formato que escribe `--export`).
+    /// No hay decodificador de JPEG.
+    pub fn decode(data: &[u8]) -> Result<Self, String> {
+        if data.starts_with(b"\x89PNG") {
+            let (width, height, pixels) = png::decode(data)?;
+            let pixels = pixels
+                .iter()
+                .map(|p| [p[0] as f32 / 255.0, p[1] as f32 / 255.0, p[2] as f32 / 255.0])
+                .collect();
+            return Ok(Texture { width, height, pixels });
+        }
+        if data.len() < 2 || data[0] != b'P' || (data[1] != b'3' && data[1] != b'6') {
+            return Err("solo se admiten imágenes PNG y PPM".to_string());
+        }
+        let binary = data[1] == b'6';
+
+        // Cabecera: formato, ancho, alto y valor máximo, con comentarios '#'
+        let mut position = 2;
+        let mut next_token = |data: &[u8]| -> Option<String> {
+            loop {
+                while position < data.len() && data[position].is_ascii_whitespace() {
+                    position += 1;
+                }
+                if position < data.len() && data[position] == b'#' {
+                    while position < data.len() && data[position] != b'\n' {
+                        position += 1;
+                    }
+                    continue;
+                }
+                break;
+            }
+            let start = position;
+            while position < data.len() && !data[position].is_ascii_whitespace() {
+                position += 1;
+            }
+            (start < position).then(|| String::from_utf8_lossy(&data[start..position]).into_owned())
+        };
+        let mut header = [0usize; 3];
+        for value in &mut header {
+            *value = next_token(data)
+                .and_then(|token| token.parse().ok())
+                .ok_or("cabecera PPM no válida")?;
+        }
+        let [width, height, max_value] = header;
+        if width == 0 || height == 0 || max_value == 0 || max_value > 255 {
+            return Err(format!("imagen {}x{} con máximo {} no admitida", width, height, max_value));
+        }
+
+        let count = width * height * 3;
+        let samples: Vec<u8> = if binary {
+            // Un solo espacio separa la cabecera de los datos
+            let start = position + 1;
+            data.get(start..start + count).ok_or("faltan píxeles en la imagen")?.to_vec()
+        } else {
+            let mut samples = Vec::with_capacity(count);
+            while samples.len() < count {
+                let value: u8 = next_token(data)
+                    .and_then(|token| token.parse().ok())
+                    .ok_or("faltan píxeles en la imagen")?;
+                samples.push(value);
+            }
+            samples
+        };
+        let scale = 1.0 / max_value as f32;
+        let pixels = samples
+            .chunks_exact(3)
+            .map(|p| [p[0] as f32 * scale, p[1] as f32 * scale, p[2] as f32 * scale])
+            .collect();
+        Ok(Texture { width, height, pixels })
+    }
+
+    /// Color en `uv` con filtrado bilineal; la imagen se repite fuera de 0..1
+    /// y v = 0 es la fila de abajo, como en OBJ
+    pub fn sample(&self, (u, v): (f32, f32)) -> [f32; 3] {
+        let x = u.rem_euclid(1.0) * self.width as f32 - 0.5;
+        let y = (1.0 - v.rem_euclid(1.0)) * self.height as f32 - 0.5;
+        let (x0, y0) = (x.floor(), y.floor());
+        let (fx, fy) = (x - x0, y - y0);
+        let texel = |x: f32, y: f32| {
+            let x = (x as i64).rem_euclid(self.width as i64) as usize;
+            let y = (y as i64).rem_euclid(self.height as i64) as usize;
+            self.pixels[y * self.width + x]
+        };
+        let (a, b) = (texel(x0, y0), texel(x0 + 1.0, y0));
+        let (c, d) = (texel(x0, y0 + 1.0), texel(x0 + 1.0, y0 + 1.0));
+        let mut result = [0.0; 3];
+        for i in 0..3 {
+            let top = a[i] + (b[i] - a[i]) * fx;
+            let bottom = c[i] + (d[i] - c[i]) * fx;
+            result[i] = top + (bottom - top) * fy;
+        }
+        result
+    }
+
+    /// Brillo en `uv`, para usar la imagen como mapa de alturas
+    fn height(&self, uv: (f32, f32)) -> f32 {
+        let [r, g, b] = self.sample(uv);
+        (r + g + b) / 3.0
+    }
+}
+
+/// Material de un archivo MTL. Los campos que falten toman los valores por
+/// defecto de la especificación.
+pub struct Material {
+    pub name: String,
+    /// Kd
+    pub diffuse: ShaderColor,
+    /// Ks
+    pub specular: ShaderColor,
+    /// Ns, exponente del brillo especular
+    pub shininess: f32,
+    /// Ke, luz propia
+    pub emission: ShaderColor,
+    /// d, 1 es opaco
+    pub opacity: f32,
+    /// map_Kd, multiplica a `diffuse`
+    pub diffuse_map: Option<Texture>,
+    /// map_Bump, mapa de alturas en escala de grises
+    pub bump_map: Option<Texture>,
+    /// Intensidad del relieve (opción `-bm` de map_Bump)
+    pub bump_strength: f32,
+}
+
+impl Material {
+    pub fn new(name: &str) -> Self {
+        Material {
+            name: name.to_string(),
+            diffuse: ShaderColor::new(0.8, 0.8, 0.8, 1.0),
+            specular: ShaderColor::new(0.0, 0.0, 0.0, 1.0),
+            shininess: 0.0,
+            emission: ShaderColor::new(0.0, 0.0, 0.0, 1.0),
+            opacity: 1.0,
+            diffuse_map: None,
+            bump_map: None,
+            bump_strength: 1.0,
+        }
+    }
+}
+
+/// Blinn-Phong con los valores de un material. Trabaja en el espacio del
+/// modelo como el resto de shaders, así que lleva la orientación del objeto
+/// para girar la luz y la cámara a ese espacio.
+pub struct MaterialShader<'a> {
+    pub material: &'a Material,
+    pub orientation: UnitQuaternion<f32>,
+    /// Escala de `emission`; la nave la sube con el acelerador
+    pub emission_scale: f32,
+}
+
+impl MaterialShader<'_> {
+    /// Normal inclinada por la pendiente del mapa de relieve. Sin tangentes en
+    /// el archivo se usa una base cualquiera perpendicular a la normal.
+    fn bumped_normal(&self, normal: Vec3<f32>, uv: (f32, f32)) -> Vec3<f32> {
+        let Some(bump) = &self.material.bump_map else { return normal };
+        let height = bump.height(uv);
+        let du = bump.height((uv.0 + BUMP_STEP, uv.1)) - height;
+        let dv = bump.height((uv.0, uv.1 + BUMP_STEP)) - height;
+        let helper = if normal.y.abs() < 0.9 { Vec3::y() } else { Vec3::x() };
+        let tangent = helper.cross(&normal).normalize();
+        let bitangent = normal.cross(&tangent);
+        let slope = self.material.bump_strength * BUMP_DEPTH / BUMP_STEP;
+        (normal - (tangent * du + bitangent * dv) * slope).normalize()
+    }
+}
+
+impl PlanetShader for MaterialShader<'_> {
+    fn texture(&self) -> Option<&Texture> {
+        self.material.diffuse_map.as_ref()
+    }
+
+    fn vertex_shader(&self, position: Vector3, normal: Vector3, _uv: (f32, f32), _uniforms: &ShaderUniforms) -> (Vector3, Vector3) {
+        (position, normal)
+    }
+
+    fn fragment_shader(&self, position: Vector3, normal: Vector3, uv: (f32, f32), uniforms: &ShaderUniforms) -> ShaderColor {
+        // El mapa difuso lo aplica el rasterizador en cada píxel (`texture`)
+        let material = self.material;
+        let diffuse_color = [material.diffuse.r, material.diffuse.g, material.diffuse.b];
+
+        let inverse = self.orientation.inverse();
+        // Luz desde el sol, llevada a la orientación del modelo
+        let light = (uniforms.sun_position - uniforms.model_position).normalize();
+        let light = inverse * Vec3::new(light.x, light.y, light.z);
+        let normal = self.bumped_normal(Vec3::new(normal.x, normal.y, normal.z).normalize(), uv);
+        let diffuse = normal.dot(&light).max(0.0);
+
+        // La cámara solo se lleva a la orientación del modelo: la escala y la
+        // traslación no cambian la dirección hacia ella lo suficiente a esta distancia
+        let camera = uniforms.camera_position - uniforms.model_position;
+        let to_camera = inverse * Vec3::new(camera.x, camera.y, camera.z);
+        let to_camera = to_camera - Vec3::new(position.x, position.y, position.z);
+        let half = (light + to_camera.normalize()).normalize();
+        let specular = if diffuse > 0.0 && material.shininess > 0.0 {
+            normal.dot(&half).max(0.0).powf(material.shininess)
+        } else {
+            0.0
+        };
+
+        let intensity = AMBIENT + (1.0 - AMBIENT) * diffuse;
+        let emission = self.emission_scale;
+        ShaderColor::new(
+            diffuse_color[0] * intensity + material.specular.r * specular + material.emission.r * emission,
+            diffuse_color[1] * intensity + material.specular.g * specular + material.emission.g * emission,
+            diffuse_color[2] * intensity + material.specular.b * specular + material.emission.b * emission,
+            material.opacity,
+        )
+    }
+}
@@ -0,0 +1,240 @@
+use nalgebra::Vector3 as Vec3;
+use std::f32::consts::PI;
+use crate::framebuffer::{Framebuffer, rgb_to_u32};
+use crate::camera::Camera;
+use crate::hud::ScreenCorner;
+use crate::planet::Planet;
+use crate::scene::Scene;
+use crate::shaders::PlanetShader;
+use crate::render::{draw_line_2d, draw_line_2d_within, WIDTH, HEIGHT};
+use crate::text;
+
+// Lado del recuadro en píxeles y separación del borde de la ventana
+const MINIMAP_SIZE: usize = 180;
+const MINIMAP_MARGIN: i32 = 10;
+// Espacio extra alrededor de la órbita más externa
+const EXTENT_PADDING: f32 = 1.1;
+// Distancia máxima en píxeles entre un clic y un cuerpo para elegirlo
+const CLICK_RADIUS: f32 = 8.0;
+// Largo del frustum dibujado, en píxeles
+const FRUSTUM_LENGTH: f32 = 45.0;
+// Largo de la marca que indica el rumbo de la nave, en píxeles
+const SHIP_HEADING_LENGTH: f32 = 8.0;
+const ORBIT_SEGMENTS: usize = 48;
+
+/// Vista cenital ortográfica del plano eclíptico en una esquina de la ventana
+pub struct Minimap {
+    pub visible: bool,
+    pub corner: ScreenCorner,
+}
+
+impl Minimap {
+    pub fn new(corner: ScreenCorner, visible: bool) -> Self {
+        Minimap { visible, corner }
+    }
+
+    pub fn toggle(&mut self) {
+        self.visible = !self.visible;
+    }
+
+    /// Esquina superior izquierda del recuadro en pantalla
+    fn origin(&self) -> (f32, f32) {
+        let size = MINIMAP_SIZE as i32;
+        let x = if self.corner.is_right() { WIDTH as i32 - MINIMAP_MARGIN - size } else { MINIMAP_MARGIN };
+        let y = if self.corner.is_bottom() { HEIGHT as i32 - MINIMAP_MARGIN - size } else { MINIMAP_MARGIN };
+        (x as f32, y as f32)
+    }
+
+    /// El punto de pantalla `(x, y)` cae dentro del minimapa visible
+    pub fn contains(&self, x: f32, y: f32) -> bool {
+        let (left, top) = self.origin();
+        let size = MINIMAP_SIZE as f32;
+        self.visible && x >= left && x < left + size && y >= top && y < top + size
+    }
+
+    /// Pasa un punto del mundo (x, z) a píxeles de pantalla, con +z hacia abajo
+    fn to_screen(&self, point: Vec3<f32>, extent: f32) -> (f32, f32) {
+        let (left, top) = self.origin();
+        let half = MINIMAP_SIZE as f32 / 2.0;
+        (
+            left + half + point.x / extent * half,
+            top + half + point.z / extent * half,
+        )
+    }
+
+    /// Índice del cuerpo cuyo punto está más cerca de `(x, y)`, si hay alguno
+    /// dentro del radio de clic
+    pub fn body_at(&self, x: f32, y: f32, bodies: &[Planet]) -> Option<usize> {
+        if !self.contains(x, y) {
+            return None;
+        }
+        let extent = world_extent(bodies);
+        bodies
+            .iter()
+            .enumerate()
+            .map(|(i, body)| {
+                let (bx, by) = self.to_screen(body.position, extent);
+                (i, (bx - x).hypot(by - y))
+            })
+            .filter(|&(_, distance)| distance <= CLICK_RADIUS)
+            .min_by(|a, b| a.1.total_cmp(&b.1))
+            .map(|(i, _)| i)
+    }
+
+    /// Dibuja fondo, órbitas, cuerpos, la nave y la cámara con su campo de
+    /// visión horizontal (`half_fov` es la mitad del ángulo, en radianes)
+    pub fn render(&self, framebuffer: &mut Framebuffer, scene: &Scene, camera: &Camera, half_fov: f32) {
+        if !self.visible {
+            return;
+        }
+        let (bodies, selected, ship) = (&scene.bodies, scene.selected, &scene.ship);
+
+        let (left, top) = self.origin();
+        let size = MINIMAP_SIZE as f32;
+        let extent = world_extent(bodies);
+        let (right, bottom) = (left + size - 1.0, top + size - 1.0);
+        // Todo lo que va dentro del mapa se recorta por dentro del borde
+        let bounds = (left + 1.0, top + 1.0, right - 1.0, bottom - 1.0);
+
+        text::fill_rect(framebuffer, left as i32, top as i32, MINIMAP_SIZE, MINIMAP_SIZE, rgb_to_u32(0, 0, 0), 0.7);
+        let border_color = rgb_to_u32(90, 110, 150);
+        let corners = [(left, top), (right, top), (right, bottom), (left, bottom)];
+        for i in 0..4 {
+            draw_line_2d(framebuffer, corners[i], corners[(i + 1) % 4], border_color);
+        }
+
+        let orbit_color = rgb_to_u32(60, 85, 130);
+        // Con gravedad mutua los cuerpos ya no siguen las órbitas fijas
+        if scene.nbody.is_none() {
+            for orbit in bodies.iter().filter_map(|b| b.kepler.as_ref()) {
+                for i in 0..ORBIT_SEGMENTS {
+                    let a1 = i as f32 / ORBIT_SEGMENTS as f32 * 2.0 * PI;
+                    let a2 = (i + 1) as f32 / ORBIT_SEGMENTS as f32 * 2.0 * PI;
+                    let p1 = self.to_screen(orbit.point_at_anomaly(a1), extent);
+                    let p2 = self.to_screen(orbit.point_at_anomaly(a2), extent);
+                    draw_line_2d_within(framebuffer, p1, p2, bounds, orbit_color);
+                }
+            }
+            for body in bodies.iter().filter(|b| b.has_circular_orbit()) {
+                for i in 0..ORBIT_SEGMENTS {
+                    let a1 = i as f32 / ORBIT_SEGMENTS as f32 * 2.0 * PI;
+                    let a2 = (i + 1) as f32 / ORBIT_SEGMENTS as f32 * 2.0 * PI;
+                    let p1 = Vec3::new(a1.cos(), 0.0, a1.sin()) * body.orbit_radius;
+                    let p2 = Vec3::new(a2.cos(), 0.0, a2.sin()) * body.orbit_radius;
+                    draw_line_2d_within(framebuffer, self.to_screen(p1, extent), self.to_screen(p2, extent), bounds, orbit_color);
+                }
+            }
+        }
+
+        for (i, body) in bodies.iter().enumerate() {
+            let center = self.to_screen(body.position, extent);
+            // Los cometas pasan buena parte de la órbita fuera del mapa
+            if center.0 < bounds.0 || center.0 > bounds.2 || center.1 < bounds.1 || center.1 > bounds.3 {
+                continue;
+            }
+            let radius = if body.kepler.is_some() { 2.0 } else if body.orbit_radius > 0.0 { 3.0 } else { 5.0 };
+            fill_circle(framebuffer, center, radius, bounds, body.shader.map_color().to_u32());
+            if selected == Some(i) {
+                draw_ring(framebuffer, center, radius + 3.0, bounds, rgb_to_u32(120, 255, 160));
+            }
+        }
+
+        // La nave, como la cámara, se queda en el borde si sale del mapa
+        let ship_color = ship.shader().map_color().to_u32();
+        let (sx, sy) = self.to_screen(ship.render_position, extent);
+        let ship_point = (sx.clamp(bounds.0, bounds.2), sy.clamp(bounds.1, bounds.3));
+        let nose = ship.render_orientation * Vec3::new(0.0, 0.0, -1.0);
+        let heading = Vec3::new(nose.x, 0.0, nose.z);
+        if heading.norm() > 1e-3 {
+            let heading = heading.normalize() * SHIP_HEADING_LENGTH;
+            let tip = (ship_point.0 + heading.x, ship_point.1 + heading.z);
+            draw_line_2d_within(framebuffer, ship_point, tip, bounds, ship_color);
+        }
+        fill_circle(framebuffer, ship_point, 2.5, bounds, ship_color);
+
+        // La cámara se queda en el borde si está fuera del área del mapa
+        let camera_color = rgb_to_u32(255, 255, 255);
+        let (cx, cy) = self.to_screen(camera.position, extent);
+        let eye = (cx.clamp(bounds.0, bounds.2), cy.clamp(bounds.1, bounds.3));
+        let forward = camera.target - camera.position;
+        let heading = Vec3::new(forward.x, 0.0, forward.z);
+        if heading.norm() > 1e-3 * forward.norm() {
+            let yaw = heading.z.atan2(heading.x);
+            let edge = |angle: f32| (eye.0 + angle.cos() * FRUSTUM_LENGTH, eye.1 + angle.sin() * FRUSTUM_LENGTH);
+            let far_left = edge(yaw - half_fov);
+            let far_right = edge(yaw + half_fov);
+            draw_line_2d_within(framebuffer, eye, far_left, bounds, camera_color);
+            draw_line_2d_within(framebuffer, eye, far_right, bounds, camera_color);
+            draw_line_2d_within(framebuffer, far_left, far_right, bounds, camera_color);
+        }
+        fill_circle(framebuffer, eye, 2.0, bounds, camera_color);
+    }
+}
+
+/// Radio del mundo que cubre el minimapa: la órbita circular más externa con
+/// margen. Los cometas no cuentan; si no, los planetas quedarían diminutos.
+fn world_extent(bodies: &[Planet]) -> f32 {
+    bodies
+        .iter()
+        .filter(|b| b.has_circular_orbit())
+        .map(|b| b.orbit_radius)
+        .fold(1.0, f32::max)
+        * EXTENT_PADDING
+}
+
+/// Círculo relleno, recortado al rectángulo `(x0, y0, x1, y1)`
+fn fill_circle(framebuffer: &mut Framebuffer, center: (f32, f32), radius: f32, bounds: (f32, f32, f32, f32), color: u32) {
+    let r = radius.ceil() as i32;
+    for dy in -r..=r {
+        let y = center.1.round() as i32 + dy;
+        if (y as f32) < bounds.1 || (y as f32) > bounds.3 {
+            continue;
+        }
+        let half = (radius * radius - (dy * dy) as f32).max(0.0).sqrt().round() as i32;
+        let left = (center.0.round() as i32 - half).max(bounds.0.ceil() as i32);
+        let right = (center.0.round() as i32 + half).min(bounds.2.floor() as i32);
+        if left <= right {
+            text::fill_rect(framebuffer, left, y, (right - left + 1) as usize, 1, color, 1.0);
+        }
+    }
+}
+
+fn draw_ring(framebuffer: &mut Framebuffer, center: (f32, f32), radius: f32, bounds: (f32, f32, f32, f32), color: u32) {
+    let segments = 16;
+    for i in 0..segments {
+        let a1 = i as f32 / segments as f32 * 2.0 * PI;
+        let a2 = (i + 1) as f32 / segments as f32 * 2.0 * PI;
+        draw_line_2d_within(
+            framebuffer,
+            (center.0 + a1.cos() * radius, center.1 + a1.sin() * radius),
+            (center.0 + a2.cos() * radius, center.1 + a2.sin() * radius),
+            bounds,
+            color,
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn circles_stay_inside_the_bounds() {
+        let background = Framebuffer::new(WIDTH, HEIGHT).buffer[0];
+        let bounds = (100.0, 100.0, 200.0, 200.0);
+        let color = rgb_to_u32(255, 0, 0);
+        for center in [(100.0, 100.0), (200.0, 150.0), (150.0, 200.0), (99.0, 201.0)] {
+            let mut framebuffer = Framebuffer::new(WIDTH, HEIGHT);
+            fill_circle(&mut framebuffer, center, 3.0, bounds, color);
+            let mut painted = 0;
+            for (index, &pixel) in framebuffer.buffer.iter().enumerate() {
+                if pixel != background {
+                    let (x, y) = ((index % WIDTH) as f32, (index / WIDTH) as f32);
+                    assert!(x >= bounds.0 && x <= bounds.2 && y >= bounds.1 && y <= bounds.3, "({}, {})", x, y);
+                    painted += 1;
+                }
+            }
+            assert!(painted > 0, "{:?}", center);
+        }
+    }
+}
@@ -0,0 +1,525 @@
+use nalgebra::Vector3 as Vec3;
+use std::collections::BTreeMap;
+use crate::planet::Planet;
+
+// Paso fijo de integración en segundos de simulación. Se integra siempre con
+// este paso (hacia adelante o hacia atrás) para que los integradores
+// simplécticos conserven la energía y el resultado no dependa de los cuadros.
+const STEPS_PER_SECOND: f64 = 120.0;
+const STEP: f32 = (1.0 / STEPS_PER_SECOND) as f32;
+// Cada cuántos pasos se guarda una copia del estado. Para volver atrás se
+// parte de la copia anterior y se integra hacia adelante: integrar al revés
+// no deshace exactamente lo integrado (RK4 no es reversible) y un salto largo
+// costaría tantos pasos como se avanzó.
+const SNAPSHOT_INTERVAL: i64 = 600;
+// Pasos máximos por llamada, para que un salto largo no congele la ventana;
+// lo que falte se integra en los cuadros siguientes
+const MAX_STEPS_PER_CALL: i64 = 4 * SNAPSHOT_INTERVAL;
+// Suavizado de la gravedad a distancias cortas, para que los encuentros
+// cercanos no disparen la aceleración
+const SOFTENING: f32 = 0.05;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Integrator {
+    /// Verlet de velocidades: simpléctico y reversible, dos evaluaciones por paso
+    VelocityVerlet,
+    /// Salto de rana deriva-impulso-deriva: simpléctico, una evaluación por paso
+    Leapfrog,
+    /// Runge-Kutta de cuarto orden: más preciso a corto plazo pero la energía
+    /// deriva sin límite
+    Rk4,
+}
+
+impl Integrator {
+    pub const ALL: [Integrator; 3] = [Integrator::VelocityVerlet, Integrator::Leapfrog, Integrator::Rk4];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Integrator::VelocityVerlet => "verlet",
+            Integrator::Leapfrog => "leapfrog",
+            Integrator::Rk4 => "rk4",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Integrator::ALL.iter().copied().find(|i| i.name() == name)
+    }
+
+    pub fn next(&self) -> Self {
+        let index = Integrator::ALL.iter().position(|i| i == self).unwrap_or(0);
+        Integrator::ALL[(index + 1) % Integrator::ALL.len()]
+    }
+}
+
+/// Condiciones iniciales del modo de N cuerpos
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Scenario {
+    /// Cada planeta en órbita circular alrededor del sol: estable
+    Circular,
+    /// Planetas muy pesados y con poca velocidad: órbitas excéntricas que se
+    /// perturban entre sí hasta desordenarse
+    Unstable,
+    /// Un cuerpo errante atraviesa el sistema en trayectoria hiperbólica
+    Flyby,
+}
+
+impl Scenario {
+    pub const ALL: [Scenario; 3] = [Scenario::Circular, Scenario::Unstable, Scenario::Flyby];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Scenario::Circular => "circular",
+            Scenario::Unstable => "unstable",
+            Scenario::Flyby => "flyby",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Scenario::ALL.iter().copied().find(|s| s.name() == name)
+    }
+}
+
+// Escenario inestable: factor de masa de los planetas y fracción de la
+// velocidad circular con la que arrancan
+const UNSTABLE_MASS_FACTOR: f32 = 40.0;
+const UNSTABLE_SPEED_FACTOR: f32 = 0.8;
+// Escenario de sobrevuelo: posición y velocidad iniciales del cuerpo errante
+// (que la escena añade al final de la lista)
+const FLYBY_POSITION: Vec3<f32> = Vec3::new(-45.0, 1.0, 14.0);
+const FLYBY_VELOCITY: Vec3<f32> = Vec3::new(3.0, 0.0, 0.0);
+
+#[derive(Debug, Clone, Copy)]
+struct BodyState {
+    position: Vec3<f32>,
+    velocity: Vec3<f32>,
+}
+
+/// Integración de la gravedad mutua entre todos los cuerpos. Guarda el estado
+/// en dos instantes consecutivos de la rejilla de pasos y, para cualquier
+/// tiempo intermedio, interpola las posiciones.
+pub struct NBodySystem {
+    /// Integrador elegido en cada paso donde se cambió; vale desde ese paso
+    /// alejándose del arranque, hasta el siguiente cambio
+    integrators: BTreeMap<i64, Integrator>,
+    /// Parámetro gravitatorio (G por la masa) de cada cuerpo
+    mu: Vec<f32>,
+    current: Vec<BodyState>,
+    previous: Vec<BodyState>,
+    // Instante de `current` como pasos desde el arranque, para no acumular
+    // error sumando el paso; `previous` está un paso antes
+    start_time: f32,
+    steps: i64,
+    /// Copias de `(previous, current)` cada `SNAPSHOT_INTERVAL` pasos
+    snapshots: BTreeMap<i64, (Vec<BodyState>, Vec<BodyState>)>,
+    initial_energy: f32,
+    initial_momentum: Vec3<f32>,
+    // Escala con la que se expresa la deriva del momento: suma de |m v| inicial
+    momentum_scale: f32,
+}
+
+impl NBodySystem {
+    /// Arranca desde las posiciones actuales de `bodies` en el instante `time`.
+    /// El sol es el cuerpo 0; el resto recibe la velocidad que pide el escenario.
+    pub fn new(bodies: &[Planet], mesh_radius: f32, scenario: Scenario, integrator: Integrator, time: f32) -> Self {
+        let mut mu: Vec<f32> = bodies.iter().map(|b| b.gravitational_parameter(mesh_radius)).collect();
+        if scenario == Scenario::Unstable {
+            mu.iter_mut().skip(1).for_each(|m| *m *= UNSTABLE_MASS_FACTOR);
+        }
+
+        let sun = bodies[0].position;
+        let speed_factor = if scenario == Scenario::Unstable { UNSTABLE_SPEED_FACTOR } else { 1.0 };
+        let mut current: Vec<BodyState> = bodies
+            .iter()
+            .enumerate()
+            .map(|(i, body)| {
+                let offset = body.position - sun;
+                let radius = offset.norm();
+                let velocity = if i == 0 || radius < 1e-4 {
+                    Vec3::zeros()
+                } else if body.kepler.is_some() {
+                    // Las órbitas elípticas ya usan la masa del sol: su
+                    // velocidad cinemática es la de la gravedad real
+                    body.velocity * speed_factor
+                } else {
+                    // Perpendicular al radio en el plano de la órbita, en el
+                    // mismo sentido de giro que el movimiento cinemático
+                    let tangent = Vec3::y().cross(&offset).normalize() * -body.orbit_speed.signum();
+                    tangent * (mu[0] / radius).sqrt() * speed_factor
+                };
+                BodyState { position: body.position, velocity }
+            })
+            .collect();
+
+        // Quitar el movimiento del centro de masa para que el sistema no se
+        // desplace por la pantalla. El cuerpo errante llega después: es el
+        // sistema solar el que debe quedar quieto mientras no se acerque.
+        let total_mu: f32 = mu.iter().sum();
+        let drift = current.iter().zip(&mu).fold(Vec3::zeros(), |sum, (s, m)| sum + s.velocity * *m) / total_mu;
+        current.iter_mut().for_each(|s| s.velocity -= drift);
+        if scenario == Scenario::Flyby {
+            if let Some(intruder) = current.last_mut() {
+                intruder.position = FLYBY_POSITION;
+                intruder.velocity = FLYBY_VELOCITY;
+            }
+        }
+
+        let mut system = NBodySystem {
+            integrators: BTreeMap::from([(0, integrator)]),
+            mu,
+            previous: current.clone(),
+            current,
+            start_time: time,
+            steps: 0,
+            snapshots: BTreeMap::new(),
+            initial_energy: 0.0,
+            initial_momentum: Vec3::zeros(),
+            momentum_scale: 0.0,
+        };
+        system.reset_drift();
+        system.previous = system.integrate(&system.current, -STEP, integrator);
+        system.snapshots.insert(0, (system.previous.clone(), system.current.clone()));
+        system
+    }
+
+    /// Integrador con el que se calcula el estado actual
+    pub fn integrator(&self) -> Integrator {
+        if self.steps >= 0 {
+            self.integrator_at(self.steps, 1)
+        } else {
+            self.integrator_at(self.steps - 1, -1)
+        }
+    }
+
+    /// Integrador del paso que sale de `from` en `direction`, que siempre se
+    /// aleja del arranque
+    fn integrator_at(&self, from: i64, direction: i64) -> Integrator {
+        let change = if direction > 0 {
+            self.integrators.range(0..=from).next_back()
+        } else {
+            self.integrators.range(from + 1..=0).next()
+        };
+        *change.expect("el arranque tiene integrador").1
+    }
+
+    /// Cambia de integrador desde el paso actual. Las copias y los cambios
+    /// más alejados del arranque se calcularon con el anterior y se
+    /// descartan, y la deriva pasa a medirse desde aquí.
+    pub fn set_integrator(&mut self, integrator: Integrator) {
+        let steps = self.steps;
+        let beyond = |s: i64| match steps.signum() {
+            1 => s > steps,
+            -1 => s < steps,
+            _ => s != 0,
+        };
+        self.snapshots.retain(|&s, _| !beyond(s));
+        self.integrators.retain(|&s, _| !beyond(s));
+        self.snapshots.insert(steps, (self.previous.clone(), self.current.clone()));
+        self.integrators.insert(steps, integrator);
+        self.reset_drift();
+    }
+
+    /// Toma el estado actual como referencia de la deriva de energía y momento
+    fn reset_drift(&mut self) {
+        self.initial_energy = self.energy();
+        self.initial_momentum = self.momentum();
+        self.momentum_scale = self
+            .current
+            .iter()
+            .zip(&self.mu)
+            .map(|(s, m)| (s.velocity * *m).norm())
+            .sum::<f32>()
+            .max(f32::EPSILON);
+    }
+
+    fn accelerations(&self, state: &[BodyState]) -> Vec<Vec3<f32>> {
+        let mut accelerations = vec![Vec3::zeros(); state.len()];
+        for i in 0..state.len() {
+            for j in i + 1..state.len() {
+                let offset = state[j].position - state[i].position;
+                let distance_sq = offset.norm_squared() + SOFTENING * SOFTENING;
+                let direction = offset / (distance_sq * distance_sq.sqrt());
+                accelerations[i] += direction * self.mu[j];
+                accelerations[j] -= direction * self.mu[i];
+            }
+        }
+        accelerations
+    }
+
+    /// Un paso de `h` segundos (negativo para retroceder) desde `state`
+    fn integrate(&self, state: &[BodyState], h: f32, integrator: Integrator) -> Vec<BodyState> {
+        match integrator {
+            Integrator::VelocityVerlet => {
+                let a0 = self.accelerations(state);
+                let mut next: Vec<BodyState> = state
+                    .iter()
+                    .zip(&a0)
+                    .map(|(s, a)| BodyState {
+                        position: s.position + s.velocity * h + a * (0.5 * h * h),
+                        velocity: s.velocity,
+                    })
+                    .collect();
+                let a1 = self.accelerations(&next);
+                for (s, (a0, a1)) in next.iter_mut().zip(a0.iter().zip(&a1)) {
+                    s.velocity += (a0 + a1) * (0.5 * h);
+                }
+                next
+            }
+            Integrator::Leapfrog => {
+                let mut next: Vec<BodyState> = state
+                    .iter()
+                    .map(|s| BodyState {
+                        position: s.position + s.velocity * (0.5 * h),
+                        velocity: s.velocity,
+                    })
+                    .collect();
+                let a = self.accelerations(&next);
+                for (s, a) in next.iter_mut().zip(&a) {
+                    s.velocity += a * h;
+                    s.position += s.velocity * (0.5 * h);
+                }
+                next
+            }
+            Integrator::Rk4 => {
+                // Derivada del estado: (velocidad, aceleración)
+                let derive = |s: &[BodyState]| -> Vec<BodyState> {
+                    self.accelerations(s)
+                        .into_iter()
+                        .zip(s)
+                        .map(|(a, s)| BodyState { position: s.velocity, velocity: a })
+                        .collect()
+                };
+                let offset = |k: &[BodyState], scale: f32| -> Vec<BodyState> {
+                    state
+                        .iter()
+                        .zip(k)
+                        .map(|(s, d)| BodyState {
+                            position: s.position + d.position * scale,
+                            velocity: s.velocity + d.velocity * scale,
+                        })
+                        .collect()
+                };
+                let k1 = derive(state);
+                let k2 = derive(&offset(&k1, 0.5 * h));
+                let k3 = derive(&offset(&k2, 0.5 * h));
+                let k4 = derive(&offset(&k3, h));
+                state
+                    .iter()
+                    .enumerate()
+                    .map(|(i, s)| {
+                        let dx = k1[i].position + (k2[i].position + k3[i].position) * 2.0 + k4[i].position;
+                        let dv = k1[i].velocity + (k2[i].velocity + k3[i].velocity) * 2.0 + k4[i].velocity;
+                        BodyState {
+                            position: s.position + dx * (h / 6.0),
+                            velocity: s.velocity + dv * (h / 6.0),
+                        }
+                    })
+                    .collect()
+            }
+        }
+    }
+
+    /// Instante del paso `steps`, en f64 para no perder precisión con
+    /// millones de pasos
+    fn time_at(&self, steps: i64) -> f64 {
+        self.start_time as f64 + steps as f64 / STEPS_PER_SECOND
+    }
+
+    /// Integra paso a paso hasta que `time` quede entre `previous` y `current`.
+    /// Siempre se integra alejándose del arranque (hacia adelante después de
+    /// él y hacia atrás antes), así que cada instante se calcula siempre igual.
+    fn advance_to(&mut self, time: f32) {
+        let target = ((time as f64 - self.start_time as f64) * STEPS_PER_SECOND).ceil() as i64;
+        let toward_start = target.signum() != self.steps.signum() || target.abs() < self.steps.abs();
+        if target != self.steps && toward_start {
+            // Copia más cercana a `target` entre el arranque y él
+            let snapshot = if target >= 0 {
+                self.snapshots.range(..=target).next_back()
+            } else {
+                self.snapshots.range(target..).next()
+            };
+            if let Some((&steps, (previous, current))) = snapshot {
+                self.previous = previous.clone();
+                self.current = current.clone();
+                self.steps = steps;
+            }
+        }
+
+        let direction = (target - self.steps).signum();
+        for _ in 0..(target - self.steps).abs().min(MAX_STEPS_PER_CALL) {
+            if direction > 0 {
+                let next = self.integrate(&self.current, STEP, self.integrator_at(self.steps, 1));
+                self.previous = std::mem::replace(&mut self.current, next);
+            } else {
+                let earlier = self.integrate(&self.previous, -STEP, self.integrator_at(self.steps - 1, -1));
+                self.current = std::mem::replace(&mut self.previous, earlier);
+            }
+            self.steps += direction;
+            if self.steps % SNAPSHOT_INTERVAL == 0 {
+                self.snapshots
+                    .entry(self.steps)
+                    .or_insert_with(|| (self.previous.clone(), self.current.clone()));
+            }
+        }
+    }
+
+    /// Lleva el sistema al instante `time` y coloca allí los cuerpos
+    pub fn apply(&mut self, time: f32, bodies: &mut [Planet]) {
+        self.advance_to(time);
+        let t = ((time as f64 - self.time_at(self.steps - 1)) * STEPS_PER_SECOND).clamp(0.0, 1.0) as f32;
+        for (body, (previous, current)) in bodies.iter_mut().zip(self.previous.iter().zip(&self.current)) {
+            body.position = previous.position.lerp(&current.position, t);
+            body.velocity = previous.velocity.lerp(&current.velocity, t);
+            let offset = body.position - self.current[0].position;
+            body.orbit_angle = offset.z.atan2(offset.x);
+        }
+    }
+
+    /// Energía cinética más potencial, multiplicada por G
+    fn energy(&self) -> f32 {
+        let state = &self.current;
+        let kinetic: f32 = state.iter().zip(&self.mu).map(|(s, m)| 0.5 * m * s.velocity.norm_squared()).sum();
+        let mut potential = 0.0;
+        for i in 0..state.len() {
+            for j in i + 1..state.len() {
+                let distance_sq = (state[j].position - state[i].position).norm_squared() + SOFTENING * SOFTENING;
+                potential -= self.mu[i] * self.mu[j] / distance_sq.sqrt();
+            }
+        }
+        kinetic + potential
+    }
+
+    fn momentum(&self) -> Vec3<f32> {
+        self.current.iter().zip(&self.mu).fold(Vec3::zeros(), |sum, (s, m)| sum + s.velocity * *m)
+    }
+
+    /// Variación relativa de la energía desde el arranque o el último cambio
+    /// de integrador
+    pub fn energy_drift(&self) -> f32 {
+        (self.energy() - self.initial_energy) / self.initial_energy.abs().max(f32::EPSILON)
+    }
+
+    /// Variación del momento lineal desde el arranque o el último cambio de
+    /// integrador, relativa a la suma de los momentos de cada cuerpo entonces
+    pub fn momentum_drift(&self) -> f32 {
+        (self.momentum() - self.initial_momentum).norm() / self.momentum_scale
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::planets::GasPlanetShader;
+
+    fn system(integrator: Integrator) -> NBodySystem {
+        let mut bodies = vec![
+            Planet::new("Sol", Box::new(GasPlanetShader), 0.0, 2.0, 0.0, 0.0, 0.0),
+            Planet::new("A", Box::new(GasPlanetShader), 5.0, 0.5, 0.0, 0.3, 0.0),
+            Planet::new("B", Box::new(GasPlanetShader), 9.0, 0.8, 0.0, 0.2, 2.0),
+        ];
+        bodies.iter_mut().for_each(|b| b.set_time(0.0));
+        NBodySystem::new(&bodies, 0.5, Scenario::Unstable, integrator, 0.0)
+    }
+
+    /// Sistema recién llevado a `time` sin pasar por otros instantes
+    fn system_at(integrator: Integrator, time: f32) -> NBodySystem {
+        let mut system = system(integrator);
+        settle(&mut system, time);
+        system
+    }
+
+    /// Cuerpos en los que `apply` escribe las posiciones
+    fn placeholders(count: usize) -> Vec<Planet> {
+        (0..count).map(|_| Planet::new("", Box::new(GasPlanetShader), 0.0, 1.0, 0.0, 0.0, 0.0)).collect()
+    }
+
+    /// Llama a `apply` hasta que el sistema llega a `time`
+    fn settle(system: &mut NBodySystem, time: f32) -> Vec<Vec3<f32>> {
+        let mut bodies = placeholders(system.mu.len());
+        let mut calls = 0;
+        loop {
+            let before = system.steps;
+            system.apply(time, &mut bodies);
+            calls += 1;
+            if system.steps == before || calls > 1000 {
+                break;
+            }
+        }
+        bodies.iter().map(|b| b.position).collect()
+    }
+
+    #[test]
+    fn going_back_repeats_the_forward_result() {
+        for integrator in Integrator::ALL {
+            let mut system = system(integrator);
+            let at_thirty = settle(&mut system, 30.0);
+            settle(&mut system, 100.0);
+            assert_eq!(settle(&mut system, 30.0), at_thirty, "{:?}", integrator);
+        }
+    }
+
+    #[test]
+    fn reset_after_a_long_run_takes_one_call() {
+        let mut system = system(Integrator::Rk4);
+        let start = settle(&mut system, 0.0);
+        settle(&mut system, 500.0);
+        let mut bodies = placeholders(3);
+        system.apply(0.0, &mut bodies);
+        assert_eq!(system.steps, 0);
+        assert_eq!(bodies.iter().map(|b| b.position).collect::<Vec<_>>(), start);
+    }
+
+    #[test]
+    fn long_jumps_are_spread_over_several_calls() {
+        let mut system = system(Integrator::VelocityVerlet);
+        let mut bodies = placeholders(3);
+        system.apply(1000.0, &mut bodies);
+        assert_eq!(system.steps, MAX_STEPS_PER_CALL);
+        system.apply(-1000.0, &mut bodies);
+        assert_eq!(system.steps, -MAX_STEPS_PER_CALL);
+    }
+
+    #[test]
+    fn switching_integrator_keeps_going_back_and_forth_consistent() {
+        let mut system = system(Integrator::VelocityVerlet);
+        let at_ten = settle(&mut system, 10.0);
+        // Copias a 15 y 20 s calculadas con el integrador anterior
+        settle(&mut system, 20.0);
+        settle(&mut system, 12.0);
+        system.set_integrator(Integrator::Rk4);
+        assert_eq!(system.integrator(), Integrator::Rk4);
+        assert_eq!(system.energy_drift(), 0.0);
+        assert_eq!(system.momentum_drift(), 0.0);
+
+        let at_thirty = settle(&mut system, 30.0);
+        // Antes del cambio sigue valiendo lo calculado con Verlet
+        assert_eq!(settle(&mut system, 10.0), at_ten);
+        assert_eq!(system.integrator(), Integrator::VelocityVerlet);
+        assert_eq!(settle(&mut system, 30.0), at_thirty);
+        assert_eq!(system.integrator(), Integrator::Rk4);
+
+        // El mismo recorrido sin los viajes intermedios da lo mismo
+        let mut direct = system_at(Integrator::VelocityVerlet, 12.0);
+        direct.set_integrator(Integrator::Rk4);
+        assert_eq!(settle(&mut direct, 30.0), at_thirty);
+    }
+
+    #[test]
+    fn switching_before_the_start_applies_going_backwards() {
+        let mut system = system(Integrator::Leapfrog);
+        settle(&mut system, -5.0);
+        system.set_integrator(Integrator::Rk4);
+        let at_minus_twenty = settle(&mut system, -20.0);
+        settle(&mut system, 10.0);
+        assert_eq!(system.integrator(), Integrator::Leapfrog);
+        assert_eq!(settle(&mut system, -20.0), at_minus_twenty);
+        assert_eq!(system.integrator(), Integrator::Rk4);
+    }
+
+    #[test]
+    fn step_times_keep_their_precision() {
+        let system = system(Integrator::Leapfrog);
+        let steps = 1i64 << 30;
+        let gap = system.time_at(steps) - system.time_at(steps - 1);
+        assert!((gap - 1.0 / STEPS_PER_SECOND).abs() < 1e-9);
+    }
+}
@@ -1,134 +1,891 @@
 use crate::vector::Vector3;
-use std::fs::File;
-use std::io::{BufRead, BufReader};
+use crate::material::{Material, Texture};
+use crate::shaders::ShaderColor;
+use std::fmt;
+use std::fs;
+use std::collections::HashMap;
+use std::ops::Range;
+use std::path::Path;
+
+// Instrucciones válidas de OBJ que el cargador no usa: grupos, puntos y
+// líneas, y las de curvas y superficies de forma libre y sus atributos
+const IGNORED_STATEMENTS: [&str; 31] = [
+    "o", "g", "s", "l", "p", "vp", "cstype", "deg", "bmat", "step", "curv", "curv2", "surf", "parm",
+    "trim", "hole", "scrv", "sp", "end", "con", "mg", "bevel", "c_interp", "d_interp", "lod", "usemap",
+    "maplib", "shadow_obj", "trace_obj", "ctech", "stech",
+];
+// Instrucciones válidas de MTL que el shader de materiales no usa
+const IGNORED_MATERIAL_STATEMENTS: [&str; 17] = [
+    "Ka", "Ni", "illum", "Tf", "sharpness", "map_Ka", "map_Ks", "map_Ns", "map_d", "map_Ke",
+    "disp", "decal", "refl", "norm", "Pr", "Pm", "aniso",
+];
+
+/// Qué hacer con las líneas mal formadas
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ObjMode {
+    /// El primer problema hace fallar la carga
+    Strict,
+    /// Las líneas con problemas se saltan y se devuelven como avisos
+    Lenient,
+}
+
+/// Posición en un archivo OBJ; línea y columna empiezan en 1
+#[derive(Debug, Clone)]
+pub struct Location {
+    pub file: String,
+    pub line: usize,
+    pub column: usize,
+}
+
+impl fmt::Display for Location {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}:{}:{}", self.file, self.line, self.column)
+    }
+}
+
+/// Lista del archivo a la que apunta un índice de cara
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Element {
+    Position,
+    TexCoord,
+    Normal,
+}
+
+impl Element {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Element::Position => "posición",
+            Element::TexCoord => "coordenada de textura",
+            Element::Normal => "normal",
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum ObjError {
+    Io { file: String, error: std::io::Error },
+    /// Texto que no es un número finito
+    InvalidNumber { at: Location, text: String },
+    /// Instrucción con menos valores de los que necesita
+    MissingValues { at: Location, keyword: String, expected: usize, found: usize },
+    /// Índice de cara que no es un entero
+    InvalidIndex { at: Location, text: String },
+    /// Índice 0 o que apunta fuera de lo leído hasta esa línea
+    IndexOutOfRange { at: Location, element: Element, index: i64, count: usize },
+    TooFewCorners { at: Location, found: usize },
+    UnknownStatement { at: Location, keyword: String },
+    /// Propiedad de MTL antes del primer `newmtl`
+    NoMaterial { at: Location, keyword: String },
+    /// `usemtl` con un nombre que no está en ninguna biblioteca
+    UnknownMaterial { at: Location, name: String },
+    /// Biblioteca de materiales o mapa que no se pudo cargar
+    Resource { at: Location, path: String, reason: String },
+    /// La malla resultante no se puede dibujar
+    InvalidMesh { file: String, reason: String },
+}
+
+impl fmt::Display for ObjError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ObjError::Io { file, error } => write!(f, "{}: no se pudo leer: {}", file, error),
+            ObjError::InvalidNumber { at, text } => write!(f, "{}: número no válido '{}'", at, text),
+            ObjError::MissingValues { at, keyword, expected, found } => {
+                write!(f, "{}: '{}' necesita {} valores y tiene {}", at, keyword, expected, found)
+            }
+            ObjError::InvalidIndex { at, text } => write!(f, "{}: índice no válido '{}'", at, text),
+            ObjError::IndexOutOfRange { at, element, index, count } => write!(
+                f,
+                "{}: el índice {} no existe: hay {} de tipo {} hasta esta línea",
+                at,
+                index,
+                count,
+                element.name()
+            ),
+            ObjError::TooFewCorners { at, found } => {
+                write!(f, "{}: una cara necesita al menos 3 esquinas y tiene {}", at, found)
+            }
+            ObjError::UnknownStatement { at, keyword } => write!(f, "{}: instrucción desconocida '{}'", at, keyword),
+            ObjError::NoMaterial { at, keyword } => write!(f, "{}: '{}' antes de cualquier 'newmtl'", at, keyword),
+            ObjError::UnknownMaterial { at, name } => write!(f, "{}: material desconocido '{}'", at, name),
+            ObjError::Resource { at, path, reason } => write!(f, "{}: no se pudo cargar '{}': {}", at, path, reason),
+            ObjError::InvalidMesh { file, reason } => write!(f, "{}: malla no válida: {}", file, reason),
+        }
+    }
+}
+
+impl std::error::Error for ObjError {}
+
+/// Esquina de una cara: índices base 0 en las listas de posiciones, UV y normales
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+struct Corner {
+    position: usize,
+    uv: Option<usize>,
+    normal: Option<usize>,
+}
+
+/// Datos leídos del archivo antes de unificar los vértices
+#[derive(Default)]
+struct RawObj {
+    positions: Vec<Vector3>,
+    normals: Vec<Vector3>,
+    uvs: Vec<(f32, f32)>,
+    // Caras ya trianguladas, con los índices de cada esquina resueltos
+    triangles: Vec<[Corner; 3]>,
+    // Archivos de `mtllib` y cada `usemtl` con el primer triángulo al que se aplica
+    libraries: Vec<(Location, String)>,
+    material_uses: Vec<(Location, String, usize)>,
+}
+
+/// Triángulos seguidos de `indices` que comparten material
+#[derive(Debug, Clone)]
+pub struct MaterialGroup {
+    /// Índice en `materials`, o `None` si el archivo no asigna ninguno
+    pub material: Option<usize>,
+    pub triangles: Range<usize>,
+}
 
 pub struct ObjModel {
     pub vertices: Vec<Vector3>,
     pub normals: Vec<Vector3>,
     pub uvs: Vec<(f32, f32)>,
     pub indices: Vec<(usize, usize, usize)>,
+    pub materials: Vec<Material>,
+    /// Cubren todos los triángulos en orden
+    pub groups: Vec<MaterialGroup>,
 }
 
 impl ObjModel {
-    pub fn load(filename: &str) -> Result<Self, String> {
-        let file = File::open(filename)
-            .map_err(|e| format!("No se pudo abrir el archivo {}: {}", filename, e))?;
-        
-        let reader = BufReader::new(file);
+    /// Carga un archivo OBJ con sus bibliotecas de materiales. En modo
+    /// permisivo devuelve también los problemas de las líneas que se saltaron.
+    /// Las bibliotecas, mapas y materiales que faltan son siempre avisos: la
+    /// malla se puede dibujar igual con el material por defecto.
+    pub fn load(filename: &str, mode: ObjMode) -> Result<(Self, Vec<ObjError>), ObjError> {
+        ObjModel::load_with(filename, mode, &read_file)
+    }
+
+    /// Como `load`, pero el OBJ y sus bibliotecas se leen con `read` en lugar
+    /// de del disco; sirve para los modelos incluidos en el programa
+    pub fn load_with(
+        filename: &str,
+        mode: ObjMode,
+        read: &dyn Fn(&str) -> Result<String, ObjError>,
+    ) -> Result<(Self, Vec<ObjError>), ObjError> {
+        let mut raw = RawObj::default();
+        let mut warnings = Vec::new();
+        let text = read(filename)?;
+        for_each_line(&text, mode, &mut warnings, |line, line_number| raw.parse_line(line, filename, line_number))?;
         
-        let mut indices: Vec<(usize, usize, usize)> = Vec::new();
+        // Las bibliotecas se buscan junto al OBJ
+        let directory = Path::new(filename).parent().unwrap_or(Path::new(""));
+        let mut materials = Vec::new();
+        for (at, library) in &raw.libraries {
+            let path = directory.join(library).to_string_lossy().into_owned();
+            match load_materials(&path, mode, &mut warnings, read) {
+                Ok(loaded) => materials.extend(loaded),
+                Err(ObjError::Io { error, .. }) => {
+                    warnings.push(ObjError::Resource { at: at.clone(), path, reason: error.to_string() });
+                }
+                Err(error) => return Err(error),
+            }
+        }
+        let groups = raw.material_groups(&materials, &mut warnings);
         
-        let mut temp_vertices: Vec<Vector3> = Vec::new();
-        let mut temp_normals: Vec<Vector3> = Vec::new();
-        let mut temp_uvs: Vec<(f32, f32)> = Vec::new();
+        let mut model = raw.build();
+        model.materials = materials;
+        model.groups = groups;
+        model.validate().map_err(|reason| ObjError::InvalidMesh { file: filename.to_string(), reason })?;
+        Ok((model, warnings))
+    }
+
+    /// Comprueba que la malla se puede dibujar sin salirse de sus listas
+    pub fn validate(&self) -> Result<(), String> {
+        if self.indices.is_empty() {
+            return Err("no tiene caras".to_string());
+        }
+        if self.normals.len() != self.vertices.len() || self.uvs.len() != self.vertices.len() {
+            return Err(format!(
+                "{} vértices con {} normales y {} UV",
+                self.vertices.len(),
+                self.normals.len(),
+                self.uvs.len()
+            ));
+        }
+        let groups_end = self.groups.iter().try_fold(0, |start, group| {
+            (group.triangles.start == start && group.triangles.end >= start).then_some(group.triangles.end)
+        });
+        if groups_end != Some(self.indices.len()) {
+            return Err("los grupos de materiales no cubren los triángulos en orden".to_string());
+        }
+        if self.groups.iter().any(|group| group.material.is_some_and(|m| m >= self.materials.len())) {
+            return Err(format!("un grupo apunta fuera de los {} materiales", self.materials.len()));
+        }
+        let count = self.vertices.len();
+        if let Some(triangle) = self.indices.iter().find(|&&(a, b, c)| a >= count || b >= count || c >= count) {
+            return Err(format!("el triángulo {:?} apunta fuera de los {} vértices", triangle, count));
+        }
+        let finite = |v: &Vector3| v.x.is_finite() && v.y.is_finite() && v.z.is_finite();
+        if !self.vertices.iter().chain(&self.normals).all(finite)
+            || !self.uvs.iter().all(|(u, v)| u.is_finite() && v.is_finite())
+        {
+            return Err("tiene valores no finitos".to_string());
+        }
+        Ok(())
+    }
+
+    /// Sustituye las UV del archivo por una proyección esférica desde el origen
+    pub fn apply_spherical_uvs(&mut self) {
+        self.uvs = self.vertices.iter().map(|&v| spherical_uv(v)).collect();
+    }
+
+    /// Distancia máxima de un vértice al origen del modelo
+    pub fn bounding_radius(&self) -> f32 {
+        self.vertices
+            .iter()
+            .map(|v| v.length())
+            .fold(0.0, f32::max)
+    }
+}
+
+impl RawObj {
+    fn parse_line(&mut self, line: &str, file: &str, line_number: usize) -> Result<(), ObjError> {
+        let content = line.split('#').next().unwrap_or("");
+        let tokens = tokenize(content);
+        let Some(&(_, keyword)) = tokens.first() else { return Ok(()) };
+        let at = |column: usize| Location { file: file.to_string(), line: line_number, column };
+        let values = &tokens[1..];
         
-        for line in reader.lines() {
-            let line = line.map_err(|e| format!("Error leyendo línea: {}", e))?;
-            let parts: Vec<&str> = line.split_whitespace().collect();
-            
-            if parts.is_empty() {
-                continue;
-            }
-            
-            match parts[0] {
-                "v" if parts.len() >= 4 => {
-                    // Vértice
-                    let x: f32 = parts[1].parse().unwrap_or(0.0);
-                    let y: f32 = parts[2].parse().unwrap_or(0.0);
-                    let z: f32 = parts[3].parse().unwrap_or(0.0);
-                    temp_vertices.push(Vector3::new(x, y, z));
-                }
-                "vn" if parts.len() >= 4 => {
-                    // Normal
-                    let x: f32 = parts[1].parse().unwrap_or(0.0);
-                    let y: f32 = parts[2].parse().unwrap_or(0.0);
-                    let z: f32 = parts[3].parse().unwrap_or(0.0);
-                    temp_normals.push(Vector3::new(x, y, z));
+        match keyword {
+            "v" => {
+                // Vértice; una cuarta coordenada o un color se ignoran
+                let [x, y, z] = parse_floats(values, keyword, &at)?;
+                self.positions.push(Vector3::new(x, y, z));
+            }
+            "vn" => {
+                // Normal
+                let [x, y, z] = parse_floats(values, keyword, &at)?;
+                self.normals.push(Vector3::new(x, y, z));
+            }
+            "vt" => {
+                // Coordenada de textura; v vale 0 si falta
+                let [u] = parse_floats(values, keyword, &at)?;
+                let v = match values.get(1) {
+                    Some(&value) => parse_floats::<1>(&[value], keyword, &at)?[0],
+                    None => 0.0,
+                };
+                self.uvs.push((u, v));
+            }
+            "f" => {
+                // Cara: cada esquina es v, v/vt, v//vn o v/vt/vn
+                if values.len() < 3 {
+                    return Err(ObjError::TooFewCorners { at: at(tokens[0].0), found: values.len() });
                 }
-                "vt" if parts.len() >= 3 => {
-                    // Coordenada de textura
-                    let u: f32 = parts[1].parse().unwrap_or(0.0);
-                    let v: f32 = parts[2].parse().unwrap_or(0.0);
-                    temp_uvs.push((u, v));
+                let corners = values
+                    .iter()
+                    .map(|&(column, text)| self.parse_corner(text, column, &at))
+                    .collect::<Result<Vec<Corner>, ObjError>>()?;
+                
+                // Triangular caras con más de 3 vértices
+                for i in 1..corners.len() - 1 {
+                    self.triangles.push([corners[0], corners[i], corners[i + 1]]);
                 }
-                "f" if parts.len() >= 4 => {
-                    // Cara (triángulo)
-                    let mut face_vertices = Vec::new();
-                    
-                    for i in 1..parts.len() {
-                        let face_data: Vec<&str> = parts[i].split('/').collect();
-                        
-                        if !face_data.is_empty() {
-                            if let Ok(v_idx) = face_data[0].parse::<usize>() {
-                                face_vertices.push(v_idx - 1); // OBJ usa índices base 1
-                            }
-                        }
-                    }
-                    
-                    // Triangular caras con más de 3 vértices
-                    if face_vertices.len() >= 3 {
-                        for i in 1..face_vertices.len() - 1 {
-                            indices.push((
-                                face_vertices[0],
-                                face_vertices[i],
-                                face_vertices[i + 1]
-                            ));
-                        }
-                    }
+            }
+            "mtllib" => {
+                for &(column, library) in values {
+                    self.libraries.push((at(column), library.to_string()));
                 }
-                _ => {}
+            }
+            "usemtl" => {
+                let name = material_name(content, values).ok_or_else(|| {
+                    let column = tokens[0].0 + keyword.len();
+                    ObjError::MissingValues { at: at(column), keyword: keyword.to_string(), expected: 1, found: 0 }
+                })?;
+                self.material_uses.push((at(values[0].0), name, self.triangles.len()));
+            }
+            _ if IGNORED_STATEMENTS.contains(&keyword) => {}
+            _ => {
+                return Err(ObjError::UnknownStatement { at: at(tokens[0].0), keyword: keyword.to_string() });
             }
         }
+        Ok(())
+    }
+
+    /// Lee una esquina `v`, `v/vt`, `v//vn` o `v/vt/vn` que empieza en `column`
+    fn parse_corner(&self, text: &str, column: usize, at: &impl Fn(usize) -> Location) -> Result<Corner, ObjError> {
+        let mut offset = 0;
+        let mut fields = Vec::with_capacity(3);
+        for field in text.split('/') {
+            fields.push((column + offset, field));
+            offset += field.chars().count() + 1;
+        }
+        if fields.len() > 3 {
+            return Err(ObjError::InvalidIndex { at: at(column), text: text.to_string() });
+        }
         
-        if temp_normals.is_empty() {
-            temp_normals = vec![Vector3::new(0.0, 0.0, 0.0); temp_vertices.len()];
-            
-            for &(i0, i1, i2) in &indices {
-                if i0 < temp_vertices.len() && i1 < temp_vertices.len() && i2 < temp_vertices.len() {
-                    let v0 = temp_vertices[i0];
-                    let v1 = temp_vertices[i1];
-                    let v2 = temp_vertices[i2];
-                    
-                    let edge1 = Vector3::new(v1.x - v0.x, v1.y - v0.y, v1.z - v0.z);
-                    let edge2 = Vector3::new(v2.x - v0.x, v2.y - v0.y, v2.z - v0.z);
-                    let normal = edge1.cross(&edge2).normalize();
-                    
-                    temp_normals[i0] = Vector3::new(
-                        temp_normals[i0].x + normal.x,
-                        temp_normals[i0].y + normal.y,
-                        temp_normals[i0].z + normal.z,
-                    );
-                    temp_normals[i1] = Vector3::new(
-                        temp_normals[i1].x + normal.x,
-                        temp_normals[i1].y + normal.y,
-                        temp_normals[i1].z + normal.z,
-                    );
-                    temp_normals[i2] = Vector3::new(
-                        temp_normals[i2].x + normal.x,
-                        temp_normals[i2].y + normal.y,
-                        temp_normals[i2].z + normal.z,
-                    );
-                }
+        let resolve = |(column, field): (usize, &str), element: Element, count: usize| -> Result<usize, ObjError> {
+            let index: i64 = field
+                .parse()
+                .map_err(|_| ObjError::InvalidIndex { at: at(column), text: field.to_string() })?;
+            // Base 1, o negativo contando desde el último elemento leído
+            let resolved = if index > 0 { index - 1 } else { count as i64 + index };
+            if index == 0 || resolved < 0 || resolved >= count as i64 {
+                return Err(ObjError::IndexOutOfRange { at: at(column), element, index, count });
             }
-            
-            for normal in &mut temp_normals {
-                *normal = normal.normalize();
+            Ok(resolved as usize)
+        };
+        let optional = |field: Option<&(usize, &str)>, element: Element, count: usize| match field {
+            None => Ok(None),
+            Some((_, "")) => Ok(None),
+            Some(&field) => resolve(field, element, count).map(Some),
+        };
+        
+        Ok(Corner {
+            position: resolve(fields[0], Element::Position, self.positions.len())?,
+            uv: optional(fields.get(1), Element::TexCoord, self.uvs.len())?,
+            normal: optional(fields.get(2), Element::Normal, self.normals.len())?,
+        })
+    }
+
+    /// Reparte los triángulos en grupos según los `usemtl` leídos
+    fn material_groups(&self, materials: &[Material], warnings: &mut Vec<ObjError>) -> Vec<MaterialGroup> {
+        let mut starts = vec![(0, None)];
+        for (at, name, first) in &self.material_uses {
+            // Si hay dos con el mismo nombre gana el último, como en los exportadores
+            let material = materials.iter().rposition(|material| &material.name == name);
+            if material.is_none() {
+                warnings.push(ObjError::UnknownMaterial { at: at.clone(), name: name.clone() });
+            }
+            starts.push((*first, material));
+        }
+        
+        let mut groups = Vec::new();
+        for (i, &(start, material)) in starts.iter().enumerate() {
+            let end = starts.get(i + 1).map_or(self.triangles.len(), |&(next, _)| next);
+            if end > start {
+                groups.push(MaterialGroup { material, triangles: start..end });
             }
         }
+        groups
+    }
+
+    /// Une posiciones, UV y normales en una lista de vértices
+    fn build(self) -> ObjModel {
+        let RawObj { positions, normals: file_normals, uvs: file_uvs, triangles, .. } = self;
         
-        if temp_uvs.is_empty() {
-            for vertex in &temp_vertices {
-                let u = 0.5 + vertex.x.atan2(vertex.z) / (2.0 * std::f32::consts::PI);
-                let v = 0.5 - vertex.y.asin() / std::f32::consts::PI;
-                temp_uvs.push((u, v));
+        // Normales suaves por posición, para las esquinas que no traen la suya
+        let mut smooth_normals = vec![Vector3::new(0.0, 0.0, 0.0); positions.len()];
+        for triangle in &triangles {
+            let v0 = positions[triangle[0].position];
+            let v1 = positions[triangle[1].position];
+            let v2 = positions[triangle[2].position];
+            let normal = (v1 - v0).cross(&(v2 - v0)).normalize();
+            for corner in triangle {
+                smooth_normals[corner.position] = smooth_normals[corner.position] + normal;
             }
         }
         
-        Ok(ObjModel {
-            vertices: temp_vertices,
-            normals: temp_normals,
-            uvs: temp_uvs,
+        // Cada combinación distinta de posición, UV y normal es un vértice
+        let mut vertices: Vec<Vector3> = Vec::new();
+        let mut normals: Vec<Vector3> = Vec::new();
+        let mut uvs: Vec<(f32, f32)> = Vec::new();
+        let mut unified: HashMap<Corner, usize> = HashMap::new();
+        let mut indices: Vec<(usize, usize, usize)> = Vec::with_capacity(triangles.len());
+        for triangle in &triangles {
+            let mut index = |corner: Corner| {
+                *unified.entry(corner).or_insert_with(|| {
+                    let position = positions[corner.position];
+                    vertices.push(position);
+                    normals.push(match corner.normal {
+                        Some(n) => file_normals[n],
+                        None => smooth_normals[corner.position].normalize(),
+                    });
+                    uvs.push(match corner.uv {
+                        Some(t) => file_uvs[t],
+                        None => spherical_uv(position),
+                    });
+                    vertices.len() - 1
+                })
+            };
+            indices.push((index(triangle[0]), index(triangle[1]), index(triangle[2])));
+        }
+        
+        ObjModel {
+            vertices,
+            normals,
+            uvs,
             indices,
-        })
+            materials: Vec::new(),
+            groups: Vec::new(),
+        }
+    }
+}
+
+/// Lee un OBJ o MTL del disco
+fn read_file(filename: &str) -> Result<String, ObjError> {
+    fs::read_to_string(filename).map_err(|error| ObjError::Io { file: filename.to_string(), error })
+}
+
+/// Pasa cada línea de `text` a `parse`; en modo estricto el primer error
+/// para la carga y en modo permisivo se guarda como aviso
+fn for_each_line(
+    text: &str,
+    mode: ObjMode,
+    warnings: &mut Vec<ObjError>,
+    mut parse: impl FnMut(&str, usize) -> Result<(), ObjError>,
+) -> Result<(), ObjError> {
+    for (line_index, line) in text.lines().enumerate() {
+        // Una línea se aplica entera o no se aplica
+        if let Err(error) = parse(line, line_index + 1) {
+            match mode {
+                ObjMode::Strict => return Err(error),
+                ObjMode::Lenient => warnings.push(error),
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Carga los materiales de un archivo MTL
+fn load_materials(
+    filename: &str,
+    mode: ObjMode,
+    warnings: &mut Vec<ObjError>,
+    read: &dyn Fn(&str) -> Result<String, ObjError>,
+) -> Result<Vec<Material>, ObjError> {
+    let directory = Path::new(filename).parent().unwrap_or(Path::new("")).to_path_buf();
+    let mut materials: Vec<Material> = Vec::new();
+    // Los mapas que fallan se guardan aparte: son avisos también en modo estricto
+    let mut texture_warnings = Vec::new();
+    let text = read(filename)?;
+    for_each_line(&text, mode, warnings, |line, line_number| {
+        let content = line.split('#').next().unwrap_or("");
+        let tokens = tokenize(content);
+        let Some(&(keyword_column, keyword)) = tokens.first() else { return Ok(()) };
+        let at = |column: usize| Location { file: filename.to_string(), line: line_number, column };
+        let values = &tokens[1..];
+        
+        if keyword == "newmtl" {
+            let name = material_name(content, values).ok_or_else(|| {
+                ObjError::MissingValues { at: at(keyword_column + keyword.len()), keyword: keyword.to_string(), expected: 1, found: 0 }
+            })?;
+            materials.push(Material::new(&name));
+            return Ok(());
+        }
+        if IGNORED_MATERIAL_STATEMENTS.contains(&keyword) {
+            return Ok(());
+        }
+        let known = ["Kd", "Ks", "Ke", "Ns", "d", "Tr", "map_Kd", "map_Bump", "bump"];
+        if !known.contains(&keyword) {
+            return Err(ObjError::UnknownStatement { at: at(keyword_column), keyword: keyword.to_string() });
+        }
+        let Some(material) = materials.last_mut() else {
+            return Err(ObjError::NoMaterial { at: at(keyword_column), keyword: keyword.to_string() });
+        };
+        
+        match keyword {
+            "Kd" => material.diffuse = parse_color(values, keyword, &at)?,
+            "Ks" => material.specular = parse_color(values, keyword, &at)?,
+            "Ke" => material.emission = parse_color(values, keyword, &at)?,
+            "Ns" => material.shininess = parse_floats::<1>(values, keyword, &at)?[0].max(0.0),
+            "d" => material.opacity = parse_floats::<1>(values, keyword, &at)?[0].clamp(0.0, 1.0),
+            // Tr es la transparencia, lo contrario de d
+            "Tr" => material.opacity = 1.0 - parse_floats::<1>(values, keyword, &at)?[0].clamp(0.0, 1.0),
+            _ => {
+                // Mapas: opciones `-nombre valores...` y el archivo al final
+                let Some(&(path_column, path)) = values.last() else {
+                    let column = keyword_column + keyword.len();
+                    return Err(ObjError::MissingValues { at: at(column), keyword: keyword.to_string(), expected: 1, found: 0 });
+                };
+                let mut strength = 1.0;
+                if let Some(option) = values.iter().position(|&(_, text)| text == "-bm") {
+                    strength = parse_floats::<1>(&values[option + 1..], "-bm", &at)?[0];
+                }
+                let full_path = directory.join(path).to_string_lossy().into_owned();
+                match Texture::load(&full_path) {
+                    Ok(texture) if keyword == "map_Kd" => material.diffuse_map = Some(texture),
+                    Ok(texture) => {
+                        material.bump_map = Some(texture);
+                        material.bump_strength = strength;
+                    }
+                    Err(reason) => texture_warnings.push(ObjError::Resource { at: at(path_column), path: full_path, reason }),
+                }
+            }
+        }
+        Ok(())
+    })?;
+    warnings.extend(texture_warnings);
+    Ok(materials)
+}
+
+/// Nombre de material de `usemtl` o `newmtl`: el resto de la línea, que puede
+/// tener espacios
+fn material_name(content: &str, values: &[(usize, &str)]) -> Option<String> {
+    let &(column, _) = values.first()?;
+    Some(content.chars().skip(column - 1).collect::<String>().trim_end().to_string())
+}
+
+/// Color RGB de MTL; con un solo valor es un gris
+fn parse_color(values: &[(usize, &str)], keyword: &str, at: &impl Fn(usize) -> Location) -> Result<ShaderColor, ObjError> {
+    let [r, g, b] = if values.len() == 1 {
+        let [gray] = parse_floats(values, keyword, at)?;
+        [gray; 3]
+    } else {
+        parse_floats(values, keyword, at)?
+    };
+    Ok(ShaderColor::new(r, g, b, 1.0))
+}
+
+/// Palabras de la línea con la columna (en caracteres, desde 1) donde empiezan
+fn tokenize(line: &str) -> Vec<(usize, &str)> {
+    let mut tokens = Vec::new();
+    let mut start: Option<(usize, usize)> = None;
+    for (column, (offset, c)) in line.char_indices().enumerate() {
+        match (c.is_whitespace(), start) {
+            (true, Some((begin, begin_column))) => {
+                tokens.push((begin_column + 1, &line[begin..offset]));
+                start = None;
+            }
+            (false, None) => start = Some((offset, column)),
+            _ => {}
+        }
+    }
+    if let Some((begin, begin_column)) = start {
+        tokens.push((begin_column + 1, &line[begin..]));
+    }
+    tokens
+}
+
+/// Lee los primeros `N` valores como números finitos
+fn parse_floats<const N: usize>(
+    values: &[(usize, &str)],
+    keyword: &str,
+    at: &impl Fn(usize) -> Location,
+) -> Result<[f32; N], ObjError> {
+    if values.len() < N {
+        let column = values.last().map_or(1, |&(column, text)| column + text.chars().count());
+        return Err(ObjError::MissingValues { at: at(column), keyword: keyword.to_string(), expected: N, found: values.len() });
+    }
+    let mut result = [0.0; N];
+    for (slot, &(column, text)) in result.iter_mut().zip(values) {
+        *slot = text
+            .parse::<f32>()
+            .ok()
+            .filter(|value| value.is_finite())
+            .ok_or_else(|| ObjError::InvalidNumber { at: at(column), text: text.to_string() })?;
+    }
+    Ok(result)
+}
+
+/// UV de una esfera centrada en el origen, para modelos sin coordenadas de textura
+pub fn spherical_uv(position: Vector3) -> (f32, f32) {
+    let direction = position.normalize();
+    let u = 0.5 + direction.x.atan2(direction.z) / (2.0 * std::f32::consts::PI);
+    let v = 0.5 - direction.y.clamp(-1.0, 1.0).asin() / std::f32::consts::PI;
+    (u, v)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Carga `files[0]` leyendo todos los archivos de la lista en memoria
+    fn load(files: &[(&str, &str)], mode: ObjMode) -> Result<(ObjModel, Vec<ObjError>), ObjError> {
+        let read = |filename: &str| {
+            files
+                .iter()
+                .find(|(name, _)| *name == filename)
+                .map(|(_, text)| text.to_string())
+                .ok_or_else(|| ObjError::Io { file: filename.to_string(), error: std::io::ErrorKind::NotFound.into() })
+        };
+        ObjModel::load_with(files[0].0, mode, &read)
+    }
+
+    fn load_obj(text: &str, mode: ObjMode) -> Result<(ObjModel, Vec<ObjError>), ObjError> {
+        load(&[("test.obj", text)], mode)
+    }
+
+    /// Archivo, línea y columna de un error con posición
+    fn location(error: &ObjError) -> (String, usize, usize) {
+        let at = match error {
+            ObjError::InvalidNumber { at, .. }
+            | ObjError::MissingValues { at, .. }
+            | ObjError::InvalidIndex { at, .. }
+            | ObjError::IndexOutOfRange { at, .. }
+            | ObjError::TooFewCorners { at, .. }
+            | ObjError::UnknownStatement { at, .. }
+            | ObjError::NoMaterial { at, .. }
+            | ObjError::UnknownMaterial { at, .. }
+            | ObjError::Resource { at, .. } => at,
+            other => panic!("error sin posición: {}", other),
+        };
+        (at.file.clone(), at.line, at.column)
+    }
+
+    fn strict_error(text: &str) -> ObjError {
+        match load_obj(text, ObjMode::Strict) {
+            Ok(_) => panic!("se esperaba un error en:\n{}", text),
+            Err(error) => error,
+        }
+    }
+
+    const TRIANGLE: &str = "v 0 0 0\nv 1 0 0\nv 0 1 0\n";
+
+    #[test]
+    fn invalid_number_points_at_the_value() {
+        let error = strict_error("v 0 0 0\nv 1 x 3");
+        assert!(matches!(&error, ObjError::InvalidNumber { text, .. } if text == "x"));
+        assert_eq!(location(&error), ("test.obj".to_string(), 2, 5));
+        assert_eq!(error.to_string(), "test.obj:2:5: número no válido 'x'");
+    }
+
+    #[test]
+    fn non_finite_numbers_are_rejected() {
+        assert!(matches!(strict_error("v 0 inf 0"), ObjError::InvalidNumber { .. }));
+        assert!(matches!(strict_error("vn NaN 0 1"), ObjError::InvalidNumber { .. }));
+    }
+
+    #[test]
+    fn missing_values_point_after_the_last_value() {
+        let error = strict_error("v 1 2");
+        assert!(matches!(error, ObjError::MissingValues { expected: 3, found: 2, .. }));
+        assert_eq!(location(&error), ("test.obj".to_string(), 1, 6));
+    }
+
+    #[test]
+    fn invalid_index_points_at_the_corner() {
+        let error = strict_error(&format!("{}f 1 2 a", TRIANGLE));
+        assert!(matches!(&error, ObjError::InvalidIndex { text, .. } if text == "a"));
+        assert_eq!(location(&error), ("test.obj".to_string(), 4, 7));
+    }
+
+    #[test]
+    fn index_zero_is_out_of_range() {
+        let error = strict_error(&format!("{}f 0 1 2", TRIANGLE));
+        assert!(matches!(error, ObjError::IndexOutOfRange { element: Element::Position, index: 0, count: 3, .. }));
+        assert_eq!(location(&error), ("test.obj".to_string(), 4, 3));
+    }
+
+    #[test]
+    fn indices_past_the_end_are_out_of_range() {
+        let error = strict_error(&format!("{}f 1 2 4", TRIANGLE));
+        assert!(matches!(error, ObjError::IndexOutOfRange { element: Element::Position, index: 4, count: 3, .. }));
+        assert_eq!(location(&error), ("test.obj".to_string(), 4, 7));
+
+        // Solo cuenta lo leído hasta la línea de la cara
+        let error = strict_error(&format!("{}f 1 2 3 4\nv 1 1 0", TRIANGLE));
+        assert!(matches!(error, ObjError::IndexOutOfRange { index: 4, count: 3, .. }));
+
+        let error = strict_error(&format!("{}f -4 1 2", TRIANGLE));
+        assert!(matches!(error, ObjError::IndexOutOfRange { index: -4, count: 3, .. }));
+
+        let error = strict_error(&format!("{}vt 0 0\nf 1/2 2/1 3/1", TRIANGLE));
+        assert!(matches!(error, ObjError::IndexOutOfRange { element: Element::TexCoord, index: 2, count: 1, .. }));
+        assert_eq!(location(&error), ("test.obj".to_string(), 5, 5));
+
+        let error = strict_error(&format!("{}f 1//1 2//1 3//1", TRIANGLE));
+        assert!(matches!(error, ObjError::IndexOutOfRange { element: Element::Normal, count: 0, .. }));
+        assert_eq!(location(&error), ("test.obj".to_string(), 4, 6));
+    }
+
+    #[test]
+    fn faces_need_three_corners() {
+        let error = strict_error(&format!("{}  f 1 2", TRIANGLE));
+        assert!(matches!(error, ObjError::TooFewCorners { found: 2, .. }));
+        assert_eq!(location(&error), ("test.obj".to_string(), 4, 3));
+    }
+
+    #[test]
+    fn unknown_statements_point_at_the_keyword() {
+        let error = strict_error(&format!("{}f 1 2 3\n\tfoo 1 2", TRIANGLE));
+        assert!(matches!(&error, ObjError::UnknownStatement { keyword, .. } if keyword == "foo"));
+        assert_eq!(location(&error), ("test.obj".to_string(), 5, 2));
+    }
+
+    #[test]
+    fn free_form_statements_are_ignored() {
+        let text = format!(
+            "{}f 1 2 3\ncstype bspline\ndeg 3\ncurv 0 1 1 2 3\nparm u 0 0 1 1\nend\nsurf 0 1 0 1 1 2 3\n\
+             trim 0 1 1\nhole 0 1 1\nmg 1 0.5\nbevel off\nc_interp on\nd_interp off\nlod 1\n\
+             shadow_obj a.obj\ntrace_obj a.obj\nctech cparm 1\nstech cparma 1 1\ncurv2 1 2\n",
+            TRIANGLE
+        );
+        let (model, warnings) = load_obj(&text, ObjMode::Strict).unwrap();
+        assert!(warnings.is_empty());
+        assert_eq!(model.indices.len(), 1);
+    }
+
+    #[test]
+    fn material_errors_point_into_the_library() {
+        let obj = format!("mtllib test.mtl\n{}f 1 2 3", TRIANGLE);
+        let error = load(&[("test.obj", &obj), ("test.mtl", "# sin material\n Kd 1 1 1")], ObjMode::Strict)
+            .err()
+            .unwrap();
+        assert!(matches!(&error, ObjError::NoMaterial { keyword, .. } if keyword == "Kd"));
+        assert_eq!(location(&error), ("test.mtl".to_string(), 2, 2));
+
+        let error = load(&[("test.obj", &obj), ("test.mtl", "newmtl a\nKd 1 z 1")], ObjMode::Strict)
+            .err()
+            .unwrap();
+        assert!(matches!(error, ObjError::InvalidNumber { .. }));
+        assert_eq!(location(&error), ("test.mtl".to_string(), 2, 6));
+    }
+
+    #[test]
+    fn missing_resources_are_warnings_in_strict_mode() {
+        let obj = format!("mtllib nada.mtl\n{}usemtl metal\nf 1 2 3", TRIANGLE);
+        let (model, warnings) = load_obj(&obj, ObjMode::Strict).unwrap();
+        assert_eq!(warnings.len(), 2);
+        assert!(matches!(&warnings[0], ObjError::Resource { path, .. } if path == "nada.mtl"));
+        assert_eq!(location(&warnings[0]), ("test.obj".to_string(), 1, 8));
+        assert!(matches!(&warnings[1], ObjError::UnknownMaterial { name, .. } if name == "metal"));
+        assert_eq!(location(&warnings[1]), ("test.obj".to_string(), 5, 8));
+        assert!(model.materials.is_empty());
+        assert_eq!(model.groups.len(), 1);
+        assert_eq!(model.groups[0].material, None);
+    }
+
+    #[test]
+    fn missing_file_is_an_io_error() {
+        let error = ObjModel::load("no/existe.obj", ObjMode::Lenient).map(|_| ()).unwrap_err();
+        assert!(matches!(&error, ObjError::Io { file, .. } if file == "no/existe.obj"));
+    }
+
+    #[test]
+    fn strict_and_lenient_on_the_same_input() {
+        let text = format!("{}v 1 1\nf 1 2 3\nf 1 2 9\nbar\nf 3 2 1", TRIANGLE);
+
+        let error = strict_error(&text);
+        assert!(matches!(error, ObjError::MissingValues { .. }));
+        assert_eq!(location(&error).1, 4);
+
+        let (model, warnings) = load_obj(&text, ObjMode::Lenient).unwrap();
+        let lines: Vec<usize> = warnings.iter().map(|w| location(w).1).collect();
+        assert_eq!(lines, vec![4, 6, 7]);
+        assert!(matches!(warnings[1], ObjError::IndexOutOfRange { .. }));
+        assert!(matches!(warnings[2], ObjError::UnknownStatement { .. }));
+        // Las líneas saltadas no dejan nada a medias
+        assert_eq!(model.indices.len(), 2);
+        assert_eq!(model.vertices.len(), 3);
+    }
+
+    #[test]
+    fn a_model_without_faces_is_invalid() {
+        let error = load_obj(TRIANGLE, ObjMode::Lenient).map(|_| ()).unwrap_err();
+        assert!(matches!(&error, ObjError::InvalidMesh { file, .. } if file == "test.obj"));
+        assert_eq!(error.to_string(), "test.obj: malla no válida: no tiene caras");
+    }
+
+    fn triangle_model() -> ObjModel {
+        let (model, _) = load_obj(&format!("{}f 1 2 3", TRIANGLE), ObjMode::Strict).unwrap();
+        model
+    }
+
+    #[test]
+    fn validate_accepts_a_loaded_model() {
+        assert_eq!(triangle_model().validate(), Ok(()));
+    }
+
+    #[test]
+    fn validate_rejects_broken_meshes() {
+        let mut model = triangle_model();
+        model.indices.push((0, 1, 3));
+        model.groups[0].triangles = 0..2;
+        assert!(model.validate().unwrap_err().contains("fuera de los 3 vértices"));
+
+        let mut model = triangle_model();
+        model.normals.pop();
+        assert!(model.validate().is_err());
+
+        let mut model = triangle_model();
+        model.uvs[1].0 = f32::NAN;
+        assert_eq!(model.validate(), Err("tiene valores no finitos".to_string()));
+
+        let mut model = triangle_model();
+        model.groups.clear();
+        assert!(model.validate().unwrap_err().contains("grupos"));
+
+        let mut model = triangle_model();
+        model.groups[0].material = Some(0);
+        assert!(model.validate().unwrap_err().contains("materiales"));
+
+        let mut model = triangle_model();
+        model.indices.clear();
+        model.groups[0].triangles = 0..0;
+        assert_eq!(model.validate(), Err("no tiene caras".to_string()));
+    }
+
+    fn face_model(text: &str) -> ObjModel {
+        let (model, warnings) = load_obj(text, ObjMode::Strict).unwrap();
+        assert!(warnings.is_empty());
+        model
+    }
+
+    const QUAD: &str = "v 0 0 0\nv 1 0 0\nv 1 1 0\nv 0 1 0\n";
+
+    #[test]
+    fn polygons_are_fanned_into_triangles() {
+        let model = face_model(&format!("{}f 1 2 3 4", QUAD));
+        assert_eq!(model.indices, vec![(0, 1, 2), (0, 2, 3)]);
+        assert_eq!(model.vertices.len(), 4);
+    }
+
+    #[test]
+    fn all_corner_formats_are_read() {
+        let model = face_model(&format!(
+            "{}vt 0.25 0.5\nvt 0.75\nvn 0 0 -1\nf 1 2 3\nf 1/1 2/2 3/1\nf 1//1 2//1 3//1\nf 1/2/1 2/1/1 3/2/1",
+            QUAD
+        ));
+        assert_eq!(model.indices.len(), 4);
+        assert_eq!(model.vertices.len(), 12);
+        // v/vt: UV del archivo; vt sin v vale 0
+        assert_eq!(model.uvs[model.indices[1].0], (0.25, 0.5));
+        assert_eq!(model.uvs[model.indices[1].1], (0.75, 0.0));
+        // v//vn: normal del archivo; sin vn, la de la cara
+        let normal = model.normals[model.indices[2].0];
+        assert_eq!((normal.x, normal.y, normal.z), (0.0, 0.0, -1.0));
+        let smooth = model.normals[model.indices[0].0];
+        assert_eq!((smooth.x, smooth.y, smooth.z), (0.0, 0.0, 1.0));
+        // v/vt/vn
+        let last = model.indices[3];
+        assert_eq!(model.uvs[last.0], (0.75, 0.0));
+        assert_eq!(model.normals[last.2].z, -1.0);
+    }
+
+    #[test]
+    fn negative_indices_count_back_from_the_last_element() {
+        let model = face_model(&format!("{}vt 0 0\nvt 1 1\nf -3/-1 -2/-2 -1/-1\nv 5 5 5\nf -1 -2 -5", QUAD));
+        let [a, b, c] = [model.indices[0].0, model.indices[0].1, model.indices[0].2].map(|i| model.vertices[i]);
+        assert_eq!([a.x, a.y, b.x, b.y, c.x, c.y], [1.0, 0.0, 1.0, 1.0, 0.0, 1.0]);
+        assert_eq!(model.uvs[model.indices[0].0], (1.0, 1.0));
+        assert_eq!(model.uvs[model.indices[0].1], (0.0, 0.0));
+        // Después de otro `v` los mismos índices relativos apuntan más adelante
+        let (d, e, f) = model.indices[1];
+        assert_eq!(model.vertices[d].x, 5.0);
+        assert_eq!(model.vertices[e].y, 1.0);
+        assert_eq!((model.vertices[f].x, model.vertices[f].y), (0.0, 0.0));
+    }
+
+    #[test]
+    fn identical_corners_share_a_vertex() {
+        let model = face_model(&format!("{}vn 0 0 1\nf 1//1 2//1 3//1\nf 1//1 3//1 4//1", QUAD));
+        assert_eq!(model.vertices.len(), 4);
+        assert_eq!(model.indices, vec![(0, 1, 2), (0, 2, 3)]);
+    }
+
+    #[test]
+    fn corners_with_different_normals_are_not_merged() {
+        // La arista 1-3 es dura: cada lado trae su propia normal
+        let model = face_model(&format!("{}vn 0 0 1\nvn 0 1 0\nf 1//1 2//1 3//1\nf 1//2 3//2 4//2", QUAD));
+        assert_eq!(model.vertices.len(), 6);
+        let (first, second) = (model.indices[0], model.indices[1]);
+        assert_ne!(first.0, second.0);
+        assert_ne!(first.2, second.1);
+        assert_eq!(model.vertices[first.0].x, model.vertices[second.0].x);
+        assert_eq!(model.normals[second.0].y, 1.0);
+    }
+
+    #[test]
+    fn corners_with_different_uvs_are_not_merged() {
+        let model = face_model(&format!("{}vt 0 0\nvt 1 0\nf 1/1 2/1 3/1\nf 1/2 3/1 4/1", QUAD));
+        assert_eq!(model.vertices.len(), 5);
     }
 }
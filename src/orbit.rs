@@ -0,0 +1,92 @@
+use nalgebra::Vector3 as Vec3;
+use std::f32::consts::PI;
+
+// Iteraciones de Newton para la ecuación de Kepler; con el arranque en π
+// bastan incluso para excentricidades cercanas a 1
+const KEPLER_ITERATIONS: usize = 8;
+
+/// Órbita elíptica alrededor del origen, con el foco en el sol. La posición
+/// se calcula en cualquier instante resolviendo la ecuación de Kepler, así que
+/// se puede retroceder o saltar en el tiempo como con las órbitas circulares.
+#[derive(Debug, Clone, Copy)]
+pub struct KeplerOrbit {
+    pub semi_major_axis: f32,
+    pub eccentricity: f32,
+    /// Ángulo del perihelio en el plano de la órbita, medido como `orbit_angle`
+    periapsis_angle: f32,
+    /// Giro del plano de la órbita alrededor del eje X
+    inclination: f32,
+    mean_anomaly_at_epoch: f32,
+    /// Velocidad angular media (2π / periodo)
+    pub mean_motion: f32,
+}
+
+impl KeplerOrbit {
+    /// `central_mu` es G por la masa del sol; el periodo sale de la tercera ley
+    /// de Kepler para que la órbita coincida con la gravedad de N cuerpos
+    pub fn new(
+        perihelion: f32,
+        eccentricity: f32,
+        periapsis_angle: f32,
+        inclination: f32,
+        mean_anomaly_at_epoch: f32,
+        central_mu: f32,
+    ) -> Self {
+        let semi_major_axis = perihelion / (1.0 - eccentricity);
+        KeplerOrbit {
+            semi_major_axis,
+            eccentricity,
+            periapsis_angle,
+            inclination,
+            mean_anomaly_at_epoch,
+            mean_motion: (central_mu / semi_major_axis.powi(3)).sqrt(),
+        }
+    }
+
+    pub fn perihelion(&self) -> f32 {
+        self.semi_major_axis * (1.0 - self.eccentricity)
+    }
+
+    pub fn aphelion(&self) -> f32 {
+        self.semi_major_axis * (1.0 + self.eccentricity)
+    }
+
+    /// Anomalía excéntrica en el instante `time`
+    fn eccentric_anomaly(&self, time: f32) -> f32 {
+        let mean = (self.mean_anomaly_at_epoch + self.mean_motion * time + PI).rem_euclid(2.0 * PI) - PI;
+        let e = self.eccentricity;
+        let mut anomaly = if e > 0.8 { PI.copysign(mean) } else { mean };
+        for _ in 0..KEPLER_ITERATIONS {
+            anomaly -= (anomaly - e * anomaly.sin() - mean) / (1.0 - e * anomaly.cos());
+        }
+        anomaly
+    }
+
+    /// Lleva un vector del plano de la órbita (perihelio en +X) al mundo
+    fn plane_to_world(&self, x: f32, z: f32) -> Vec3<f32> {
+        let (sin_w, cos_w) = self.periapsis_angle.sin_cos();
+        let (sin_i, cos_i) = self.inclination.sin_cos();
+        let rotated_x = x * cos_w - z * sin_w;
+        let rotated_z = x * sin_w + z * cos_w;
+        Vec3::new(rotated_x, rotated_z * sin_i, rotated_z * cos_i)
+    }
+
+    /// Punto de la elipse para una anomalía excéntrica dada, para dibujarla
+    pub fn point_at_anomaly(&self, anomaly: f32) -> Vec3<f32> {
+        let a = self.semi_major_axis;
+        let b = a * (1.0 - self.eccentricity * self.eccentricity).sqrt();
+        self.plane_to_world(a * (anomaly.cos() - self.eccentricity), b * anomaly.sin())
+    }
+
+    pub fn position_at(&self, time: f32) -> Vec3<f32> {
+        self.point_at_anomaly(self.eccentric_anomaly(time))
+    }
+
+    pub fn velocity_at(&self, time: f32) -> Vec3<f32> {
+        let anomaly = self.eccentric_anomaly(time);
+        let a = self.semi_major_axis;
+        let b = a * (1.0 - self.eccentricity * self.eccentricity).sqrt();
+        let rate = self.mean_motion / (1.0 - self.eccentricity * anomaly.cos());
+        self.plane_to_world(-a * anomaly.sin() * rate, b * anomaly.cos() * rate)
+    }
+}
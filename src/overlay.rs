@@ -0,0 +1,332 @@
+use nalgebra::{Matrix4, Vector3 as Vec3};
+use std::f32::consts::PI;
+use crate::framebuffer::{Framebuffer, rgb_to_u32};
+use crate::planet::Planet;
+use crate::render::{draw_line_3d, WIDTH, HEIGHT};
+use crate::text::{self, Align, TextStyle};
+
+/// Capas de ayuda visual que se dibujan encima de la escena
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OverlayLayer {
+    Grid,
+    Axes,
+    Orbits,
+    Labels,
+    Bounds,
+}
+
+impl OverlayLayer {
+    pub const ALL: [OverlayLayer; 5] = [
+        OverlayLayer::Grid,
+        OverlayLayer::Axes,
+        OverlayLayer::Orbits,
+        OverlayLayer::Labels,
+        OverlayLayer::Bounds,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            OverlayLayer::Grid => "grid",
+            OverlayLayer::Axes => "axes",
+            OverlayLayer::Orbits => "orbits",
+            OverlayLayer::Labels => "labels",
+            OverlayLayer::Bounds => "bounds",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        OverlayLayer::ALL
+            .iter()
+            .copied()
+            .find(|layer| layer.name() == name)
+    }
+}
+
+/// Estado de visibilidad de cada capa
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OverlayLayers {
+    pub grid: bool,
+    pub axes: bool,
+    pub orbits: bool,
+    pub labels: bool,
+    pub bounds: bool,
+}
+
+impl Default for OverlayLayers {
+    fn default() -> Self {
+        OverlayLayers {
+            grid: true,
+            axes: true,
+            orbits: true,
+            labels: false,
+            bounds: false,
+        }
+    }
+}
+
+impl OverlayLayers {
+    pub fn none() -> Self {
+        OverlayLayers {
+            grid: false,
+            axes: false,
+            orbits: false,
+            labels: false,
+            bounds: false,
+        }
+    }
+
+    pub fn all() -> Self {
+        OverlayLayers {
+            grid: true,
+            axes: true,
+            orbits: true,
+            labels: true,
+            bounds: true,
+        }
+    }
+
+    pub fn is_enabled(&self, layer: OverlayLayer) -> bool {
+        match layer {
+            OverlayLayer::Grid => self.grid,
+            OverlayLayer::Axes => self.axes,
+            OverlayLayer::Orbits => self.orbits,
+            OverlayLayer::Labels => self.labels,
+            OverlayLayer::Bounds => self.bounds,
+        }
+    }
+
+    pub fn set(&mut self, layer: OverlayLayer, enabled: bool) {
+        match layer {
+            OverlayLayer::Grid => self.grid = enabled,
+            OverlayLayer::Axes => self.axes = enabled,
+            OverlayLayer::Orbits => self.orbits = enabled,
+            OverlayLayer::Labels => self.labels = enabled,
+            OverlayLayer::Bounds => self.bounds = enabled,
+        }
+    }
+
+    pub fn toggle(&mut self, layer: OverlayLayer) {
+        let enabled = self.is_enabled(layer);
+        self.set(layer, !enabled);
+    }
+}
+
+const GRID_EXTENT: f32 = 20.0;
+const GRID_SPACING: f32 = 2.0;
+const AXIS_LENGTH: f32 = 12.0;
+const CIRCLE_SEGMENTS: usize = 64;
+// Las elipses de los cometas son largas: más tramos para que no se vean quebradas
+const ELLIPSE_SEGMENTS: usize = 128;
+
+fn draw_circle_3d(
+    framebuffer: &mut Framebuffer,
+    center: Vec3<f32>,
+    radius: f32,
+    axis_u: Vec3<f32>,
+    axis_v: Vec3<f32>,
+    view_proj: &Matrix4<f32>,
+    color: u32,
+) {
+    for i in 0..CIRCLE_SEGMENTS {
+        let angle1 = (i as f32 / CIRCLE_SEGMENTS as f32) * 2.0 * PI;
+        let angle2 = ((i + 1) as f32 / CIRCLE_SEGMENTS as f32) * 2.0 * PI;
+
+        let p1 = center + (axis_u * angle1.cos() + axis_v * angle1.sin()) * radius;
+        let p2 = center + (axis_u * angle2.cos() + axis_v * angle2.sin()) * radius;
+
+        draw_line_3d(framebuffer, p1, p2, view_proj, color);
+    }
+}
+
+/// Cuadrícula sobre el plano eclíptico (y = 0)
+pub fn render_grid(framebuffer: &mut Framebuffer, view_proj: &Matrix4<f32>) {
+    let grid_color = rgb_to_u32(35, 50, 85);
+    let lines = (GRID_EXTENT / GRID_SPACING) as i32;
+
+    for i in -lines..=lines {
+        let offset = i as f32 * GRID_SPACING;
+        draw_line_3d(
+            framebuffer,
+            Vec3::new(offset, 0.0, -GRID_EXTENT),
+            Vec3::new(offset, 0.0, GRID_EXTENT),
+            view_proj,
+            grid_color,
+        );
+        draw_line_3d(
+            framebuffer,
+            Vec3::new(-GRID_EXTENT, 0.0, offset),
+            Vec3::new(GRID_EXTENT, 0.0, offset),
+            view_proj,
+            grid_color,
+        );
+    }
+}
+
+/// Ejes de coordenadas RGB (X=Rojo, Y=Verde, Z=Azul)
+pub fn render_axes(framebuffer: &mut Framebuffer, view_proj: &Matrix4<f32>) {
+    let origin = Vec3::new(0.0, 0.0, 0.0);
+    let axes = [
+        (Vec3::new(AXIS_LENGTH, 0.0, 0.0), rgb_to_u32(220, 60, 60)),
+        (Vec3::new(0.0, AXIS_LENGTH, 0.0), rgb_to_u32(60, 220, 60)),
+        (Vec3::new(0.0, 0.0, AXIS_LENGTH), rgb_to_u32(60, 100, 240)),
+    ];
+
+    for (end, color) in axes {
+        draw_line_3d(framebuffer, origin, end, view_proj, color);
+    }
+}
+
+/// Trayectoria de cada cuerpo que orbita: círculos y elipses de Kepler
+pub fn render_orbits(framebuffer: &mut Framebuffer, bodies: &[Planet], view_proj: &Matrix4<f32>) {
+    let orbit_color = rgb_to_u32(100, 140, 200);
+    let ellipse_color = rgb_to_u32(120, 190, 210);
+
+    for orbit in bodies.iter().filter_map(|b| b.kepler.as_ref()) {
+        for i in 0..ELLIPSE_SEGMENTS {
+            let a1 = i as f32 / ELLIPSE_SEGMENTS as f32 * 2.0 * PI;
+            let a2 = (i + 1) as f32 / ELLIPSE_SEGMENTS as f32 * 2.0 * PI;
+            draw_line_3d(framebuffer, orbit.point_at_anomaly(a1), orbit.point_at_anomaly(a2), view_proj, ellipse_color);
+        }
+    }
+
+    for body in bodies.iter().filter(|b| b.has_circular_orbit()) {
+        draw_circle_3d(
+            framebuffer,
+            Vec3::new(0.0, 0.0, 0.0),
+            body.orbit_radius,
+            Vec3::new(1.0, 0.0, 0.0),
+            Vec3::new(0.0, 0.0, 1.0),
+            view_proj,
+            orbit_color,
+        );
+    }
+}
+
+/// Esfera envolvente de cada cuerpo, dibujada como tres círculos máximos
+pub fn render_bounds(
+    framebuffer: &mut Framebuffer,
+    bodies: &[Planet],
+    mesh_radius: f32,
+    view_proj: &Matrix4<f32>,
+) {
+    let bounds_color = rgb_to_u32(230, 200, 80);
+    let x = Vec3::new(1.0, 0.0, 0.0);
+    let y = Vec3::new(0.0, 1.0, 0.0);
+    let z = Vec3::new(0.0, 0.0, 1.0);
+
+    for body in bodies {
+        let radius = body.bounding_radius(mesh_radius);
+        draw_circle_3d(framebuffer, body.position, radius, x, z, view_proj, bounds_color);
+        draw_circle_3d(framebuffer, body.position, radius, x, y, view_proj, bounds_color);
+        draw_circle_3d(framebuffer, body.position, radius, y, z, view_proj, bounds_color);
+    }
+}
+
+/// Anillo alrededor del cuerpo seleccionado, siempre de frente a la cámara
+pub fn render_selection(
+    framebuffer: &mut Framebuffer,
+    body: &Planet,
+    mesh_radius: f32,
+    camera_right: Vec3<f32>,
+    camera_up: Vec3<f32>,
+    view_proj: &Matrix4<f32>,
+) {
+    let highlight_color = rgb_to_u32(120, 255, 160);
+    let radius = body.bounding_radius(mesh_radius) * 1.15;
+    draw_circle_3d(framebuffer, body.position, radius, camera_right, camera_up, view_proj, highlight_color);
+    draw_circle_3d(framebuffer, body.position, radius * 1.04, camera_right, camera_up, view_proj, highlight_color);
+}
+
+/// Dibuja las capas de líneas activas. Se llama después de los planetas para
+/// que las líneas respeten el z-buffer de la escena. Las etiquetas van aparte
+/// porque necesitan la posición de la cámara.
+pub fn render_overlays(
+    framebuffer: &mut Framebuffer,
+    layers: &OverlayLayers,
+    bodies: &[Planet],
+    mesh_radius: f32,
+    view_proj: &Matrix4<f32>,
+) {
+    if layers.grid {
+        render_grid(framebuffer, view_proj);
+    }
+    if layers.axes {
+        render_axes(framebuffer, view_proj);
+    }
+    if layers.orbits {
+        render_orbits(framebuffer, bodies, view_proj);
+    }
+    if layers.bounds {
+        render_bounds(framebuffer, bodies, mesh_radius, view_proj);
+    }
+}
+
+/// Línea de estado en la esquina superior derecha
+pub fn render_status(framebuffer: &mut Framebuffer, text: &str) {
+    let style = TextStyle {
+        color: rgb_to_u32(255, 220, 120),
+        scale: 2,
+        align: Align::Right,
+        ..TextStyle::default()
+    };
+    text::draw_text(framebuffer, text, WIDTH as i32 - 10, 10, &style);
+}
+
+/// Campo de texto centrado en la parte inferior de la ventana
+pub fn render_prompt(framebuffer: &mut Framebuffer, text: &str) {
+    let padding = 8;
+    let style = TextStyle {
+        color: rgb_to_u32(255, 220, 120),
+        scale: 2,
+        ..TextStyle::default()
+    };
+    let (text_width, text_height) = text::measure(text, &style);
+    let width = text_width + padding * 2;
+    let height = text_height + padding * 2;
+    let left = (WIDTH.saturating_sub(width) / 2) as i32;
+    let top = HEIGHT as i32 - height as i32 - 40;
+
+    text::fill_rect(framebuffer, left, top, width, height, rgb_to_u32(0, 0, 0), 0.75);
+    text::draw_text(framebuffer, text, left + padding as i32, top + padding as i32, &style);
+}
+
+/// Panel semitransparente con la lista de teclas asignadas
+pub fn render_help(framebuffer: &mut Framebuffer, lines: &[String]) {
+    let padding = 12;
+    let title_style = TextStyle {
+        color: rgb_to_u32(255, 220, 120),
+        scale: 2,
+        align: Align::Center,
+        ..TextStyle::default()
+    };
+    let body_style = TextStyle {
+        color: rgb_to_u32(200, 220, 255),
+        ..TextStyle::default()
+    };
+    let title = "Teclas";
+    let body = lines.join("\n");
+    let footer = "Edita keybindings.cfg o usa --keys ARCHIVO para cambiar las teclas. F1 cierra esta ayuda.";
+
+    let (body_width, body_height) = text::measure(&body, &body_style);
+    let footer_style = TextStyle {
+        color: rgb_to_u32(150, 160, 190),
+        max_width: Some(body_width),
+        ..TextStyle::default()
+    };
+    let (_, title_height) = text::measure(title, &title_style);
+    let (_, footer_height) = text::measure(footer, &footer_style);
+
+    let width = body_width + padding * 2;
+    let height = title_height + body_height + footer_height + padding * 4;
+    let left = (WIDTH.saturating_sub(width) / 2) as i32;
+    let top = (HEIGHT.saturating_sub(height) / 2) as i32;
+
+    text::fill_rect(framebuffer, left, top, width, height, rgb_to_u32(0, 0, 0), 0.75);
+
+    let mut y = top + padding as i32;
+    text::draw_text(framebuffer, title, left + width as i32 / 2, y, &title_style);
+    y += (title_height + padding) as i32;
+    text::draw_text(framebuffer, &body, left + padding as i32, y, &body_style);
+    y += (body_height + padding) as i32;
+    text::draw_text(framebuffer, footer, left + padding as i32, y, &footer_style);
+}
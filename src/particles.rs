@@ -0,0 +1,397 @@
+use nalgebra::{Matrix4, Vector3 as Vec3, Vector4, UnitQuaternion};
+use std::f32::consts::PI;
+use crate::framebuffer::Framebuffer;
+use crate::shaders::{ShaderColor, mix_color};
+use crate::render::{draw_glow, WIDTH, HEIGHT};
+
+// Límite de partículas vivas por emisor, para que una escala de tiempo alta
+// no dispare el costo
+const MAX_PARTICLES_PER_EMITTER: usize = 2000;
+// Paso máximo de integración de las partículas
+const MAX_SUBSTEP: f32 = 1.0 / 30.0;
+const MAX_SPRITE_PIXELS: f32 = 8.0;
+
+/// A qué se sujeta un emisor
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Anchor {
+    /// Índice en `Scene.bodies`
+    Body(usize),
+    Ship,
+}
+
+/// Pose del ancla en un instante, en coordenadas de mundo
+#[derive(Debug, Clone, Copy)]
+pub struct AnchorFrame {
+    pub position: Vec3<f32>,
+    pub orientation: UnitQuaternion<f32>,
+    pub velocity: Vec3<f32>,
+}
+
+/// Espacio en el que viven las partículas
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Space {
+    /// Se mueven con el ancla, como las llamaradas pegadas a un planeta en órbita
+    Local,
+    /// Quedan sueltas en el mundo, como el rastro de un motor
+    World,
+}
+
+/// Lugar y dirección de salida de las partículas, en el espacio del ancla
+#[derive(Debug, Clone, Copy)]
+pub enum EmitterShape {
+    /// Cualquier punto de una esfera, saliendo por la normal
+    Sphere { radius: f32 },
+    /// Un punto fijo con las partículas en un cono de semiángulo `spread`
+    Vent { offset: Vec3<f32>, direction: Vec3<f32>, spread: f32 },
+}
+
+/// Fuerzas que actúan sobre cada partícula
+#[derive(Debug, Clone, Copy)]
+pub enum Force {
+    /// Aceleración constante hacia el centro del ancla, para que lo que sale
+    /// de la superficie vuelva a caer en arco
+    Attraction(f32),
+    /// Frenado proporcional a la velocidad relativa al ancla
+    Drag(f32),
+}
+
+/// Color y tamaño al nacer y al morir; en medio se interpolan
+#[derive(Debug, Clone, Copy)]
+pub struct ParticleStyle {
+    pub start_color: ShaderColor,
+    pub end_color: ShaderColor,
+    /// Radio en unidades de mundo
+    pub start_size: f32,
+    pub end_size: f32,
+    /// Brillo que suma cada partícula sobre la imagen
+    pub intensity: f32,
+}
+
+/// Emisión a ráfagas: emite durante `duty` de cada `period` segundos
+#[derive(Debug, Clone, Copy)]
+pub struct Pulse {
+    pub period: f32,
+    pub duty: f32,
+}
+
+struct Particle {
+    position: Vec3<f32>,
+    velocity: Vec3<f32>,
+    age: f32,
+    lifetime: f32,
+}
+
+pub struct Emitter {
+    pub anchor: Anchor,
+    pub space: Space,
+    pub shape: EmitterShape,
+    /// Partículas por segundo con `strength` = 1
+    pub rate: f32,
+    /// Multiplica la emisión; la nave lo ajusta con la potencia del motor
+    pub strength: f32,
+    pub lifetime: (f32, f32),
+    pub speed: (f32, f32),
+    pub forces: Vec<Force>,
+    pub style: ParticleStyle,
+    pub pulse: Option<Pulse>,
+    particles: Vec<Particle>,
+    // Fracción de partícula pendiente de emitir
+    pending: f32,
+    // Segundos desde que existe el emisor, para las ráfagas
+    clock: f32,
+}
+
+impl Emitter {
+    pub fn new(anchor: Anchor, space: Space, shape: EmitterShape, style: ParticleStyle) -> Self {
+        Emitter {
+            anchor,
+            space,
+            shape,
+            rate: 50.0,
+            strength: 1.0,
+            lifetime: (1.0, 2.0),
+            speed: (1.0, 1.0),
+            forces: Vec::new(),
+            style,
+            pulse: None,
+            particles: Vec::new(),
+            pending: 0.0,
+            clock: 0.0,
+        }
+    }
+
+    fn emitting(&self) -> bool {
+        match self.pulse {
+            Some(pulse) => self.clock.rem_euclid(pulse.period) < pulse.period * pulse.duty,
+            None => true,
+        }
+    }
+
+    /// Crea una partícula en el espacio del ancla
+    fn spawn(&self, rng: &mut Rng) -> Particle {
+        let (position, direction) = match self.shape {
+            EmitterShape::Sphere { radius } => {
+                let normal = rng.unit_vector();
+                (normal * radius, normal)
+            }
+            EmitterShape::Vent { offset, direction, spread } => (offset, rng.cone(direction, spread)),
+        };
+        Particle {
+            position,
+            velocity: direction * rng.range(self.speed),
+            age: 0.0,
+            lifetime: rng.range(self.lifetime),
+        }
+    }
+
+    fn step(&mut self, dt: f32, frame: &AnchorFrame, rng: &mut Rng) {
+        self.clock += dt;
+        if self.emitting() {
+            self.pending += self.rate * self.strength * dt;
+        }
+        while self.pending >= 1.0 {
+            self.pending -= 1.0;
+            if self.particles.len() >= MAX_PARTICLES_PER_EMITTER {
+                continue;
+            }
+            let mut particle = self.spawn(rng);
+            // Las partículas nacen repartidas a lo largo del paso para que no
+            // salgan en grupos con cada cuadro
+            let head_start = rng.next() * dt;
+            particle.position += particle.velocity * head_start;
+            particle.age = head_start;
+            if self.space == Space::World {
+                particle.position = frame.position + frame.orientation * particle.position;
+                particle.velocity = frame.velocity + frame.orientation * particle.velocity;
+            }
+            self.particles.push(particle);
+        }
+
+        // En el espacio del mundo las fuerzas se miden desde el ancla
+        let (center, carrier) = match self.space {
+            Space::Local => (Vec3::zeros(), Vec3::zeros()),
+            Space::World => (frame.position, frame.velocity),
+        };
+        for particle in &mut self.particles {
+            let mut acceleration = Vec3::zeros();
+            for force in &self.forces {
+                acceleration += match *force {
+                    Force::Attraction(strength) => {
+                        let offset = center - particle.position;
+                        let distance = offset.norm();
+                        if distance > 1e-5 { offset / distance * strength } else { Vec3::zeros() }
+                    }
+                    Force::Drag(k) => (carrier - particle.velocity) * k,
+                };
+            }
+            particle.velocity += acceleration * dt;
+            particle.position += particle.velocity * dt;
+            particle.age += dt;
+        }
+        self.particles.retain(|p| p.age < p.lifetime);
+    }
+}
+
+/// Generador pseudoaleatorio xorshift: la misma secuencia en cada ejecución,
+/// para que los cuadros exportados se puedan repetir
+struct Rng(u32);
+
+impl Rng {
+    /// Valor en [0, 1)
+    fn next(&mut self) -> f32 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 17;
+        self.0 ^= self.0 << 5;
+        (self.0 >> 8) as f32 / (1u32 << 24) as f32
+    }
+
+    fn range(&mut self, (min, max): (f32, f32)) -> f32 {
+        min + (max - min) * self.next()
+    }
+
+    fn unit_vector(&mut self) -> Vec3<f32> {
+        let y = self.next() * 2.0 - 1.0;
+        let angle = self.next() * 2.0 * PI;
+        let ring = (1.0 - y * y).sqrt();
+        Vec3::new(ring * angle.cos(), y, ring * angle.sin())
+    }
+
+    /// Dirección dentro del cono de semiángulo `spread` alrededor de `axis`
+    fn cone(&mut self, axis: Vec3<f32>, spread: f32) -> Vec3<f32> {
+        let axis = axis.normalize();
+        let cos_max = spread.cos();
+        let cos_theta = 1.0 - self.next() * (1.0 - cos_max);
+        let sin_theta = (1.0 - cos_theta * cos_theta).sqrt();
+        let angle = self.next() * 2.0 * PI;
+        let helper = if axis.x.abs() < 0.9 { Vec3::x() } else { Vec3::y() };
+        let u = axis.cross(&helper).normalize();
+        let v = axis.cross(&u);
+        axis * cos_theta + (u * angle.cos() + v * angle.sin()) * sin_theta
+    }
+}
+
+/// Conjunto de emisores que se simulan en la CPU y se dibujan como manchas de
+/// luz aditivas
+pub struct ParticleSystem {
+    pub emitters: Vec<Emitter>,
+    rng: Rng,
+}
+
+impl Default for ParticleSystem {
+    fn default() -> Self {
+        ParticleSystem {
+            emitters: Vec::new(),
+            rng: Rng(0x9E37_79B9),
+        }
+    }
+}
+
+impl ParticleSystem {
+    pub fn particle_count(&self) -> usize {
+        self.emitters.iter().map(|e| e.particles.len()).sum()
+    }
+
+    /// Avanza `dt` segundos. Con el tiempo detenido o hacia atrás las
+    /// partículas se quedan quietas: su simulación no es reversible.
+    /// `frame` da la pose de cada ancla; si no existe, el emisor se salta.
+    pub fn step(&mut self, dt: f32, frame: impl Fn(Anchor) -> Option<AnchorFrame>) {
+        if dt <= 0.0 {
+            return;
+        }
+        let substeps = (dt / MAX_SUBSTEP).ceil().max(1.0) as usize;
+        let h = dt / substeps as f32;
+        for emitter in &mut self.emitters {
+            let Some(anchor) = frame(emitter.anchor) else { continue };
+            for _ in 0..substeps {
+                emitter.step(h, &anchor, &mut self.rng);
+            }
+        }
+    }
+
+    /// Dibuja las partículas con prueba de profundidad y sin escribirla, así
+    /// que deben ir después de todo lo opaco
+    pub fn render(
+        &self,
+        framebuffer: &mut Framebuffer,
+        view_proj: &Matrix4<f32>,
+        focal_length: f32,
+        frame: impl Fn(Anchor) -> Option<AnchorFrame>,
+    ) {
+        for emitter in &self.emitters {
+            let Some(anchor) = frame(emitter.anchor) else { continue };
+            let style = &emitter.style;
+            for particle in &emitter.particles {
+                let world = match emitter.space {
+                    Space::Local => anchor.position + anchor.orientation * particle.position,
+                    Space::World => particle.position,
+                };
+                let clip = view_proj * Vector4::new(world.x, world.y, world.z, 1.0);
+                if clip.w <= 0.0 {
+                    continue;
+                }
+                let life = (particle.age / particle.lifetime).clamp(0.0, 1.0);
+                let size = style.start_size + (style.end_size - style.start_size) * life;
+                let radius = (size * focal_length / clip.w).min(MAX_SPRITE_PIXELS);
+                let x = (clip.x / clip.w + 1.0) * 0.5 * WIDTH as f32;
+                let y = (1.0 - clip.y / clip.w) * 0.5 * HEIGHT as f32;
+                if x + radius < 0.0 || x - radius > WIDTH as f32 || y + radius < 0.0 || y - radius > HEIGHT as f32 {
+                    continue;
+                }
+                let color = mix_color(style.start_color, style.end_color, life);
+                draw_glow(framebuffer, (x, y, clip.z / clip.w), radius, color, style.intensity * color.a);
+            }
+        }
+    }
+}
+
+/// Protuberancias solares: chorros de plasma que salen a ráfagas de unas
+/// pocas regiones activas y vuelven a caer en arco, más un chisporroteo tenue
+/// en toda la superficie. `radius` es el radio del cuerpo en unidades de mundo.
+pub fn solar_prominences(body: usize, radius: f32) -> Vec<Emitter> {
+    let style = ParticleStyle {
+        start_color: ShaderColor::new(1.0, 0.85, 0.4, 1.0),
+        end_color: ShaderColor::new(0.9, 0.25, 0.05, 0.0),
+        start_size: 0.16,
+        end_size: 0.06,
+        intensity: 0.35,
+    };
+
+    let mut spicules = Emitter::new(Anchor::Body(body), Space::Local, EmitterShape::Sphere { radius }, style);
+    spicules.rate = 40.0;
+    spicules.lifetime = (0.6, 1.2);
+    spicules.speed = (0.5, 1.0);
+    spicules.forces = vec![Force::Attraction(1.6)];
+
+    // Regiones activas repartidas por la esfera, cada una con su ritmo
+    let regions = [
+        (Vec3::new(0.8, 0.5, 0.3), 3.1),
+        (Vec3::new(-0.6, 0.2, 0.8), 4.3),
+        (Vec3::new(0.1, -0.7, -0.7), 3.7),
+        (Vec3::new(-0.7, -0.3, -0.5), 5.2),
+        (Vec3::new(0.3, 0.9, -0.4), 4.7),
+        (Vec3::new(0.6, -0.2, 0.9), 5.9),
+    ];
+    let mut emitters = vec![spicules];
+    for (direction, period) in regions {
+        let direction = direction.normalize();
+        let mut region = Emitter::new(
+            Anchor::Body(body),
+            Space::Local,
+            EmitterShape::Vent { offset: direction * radius, direction, spread: 0.7 },
+            style,
+        );
+        region.rate = 140.0;
+        region.lifetime = (1.4, 2.4);
+        region.speed = (1.2, 2.0);
+        region.forces = vec![Force::Attraction(1.6)];
+        region.pulse = Some(Pulse { period, duty: 0.45 });
+        emitters.push(region);
+    }
+    emitters
+}
+
+/// Erupciones de lava a ráfagas desde un respiradero del cuerpo
+pub fn lava_eruptions(body: usize, radius: f32) -> Emitter {
+    let direction = Vec3::new(0.3, 1.0, 0.2).normalize();
+    let mut emitter = Emitter::new(
+        Anchor::Body(body),
+        Space::Local,
+        EmitterShape::Vent { offset: direction * radius, direction, spread: 0.35 },
+        ParticleStyle {
+            start_color: ShaderColor::new(1.0, 0.9, 0.45, 1.0),
+            end_color: ShaderColor::new(0.6, 0.08, 0.02, 0.0),
+            start_size: 0.05,
+            end_size: 0.03,
+            intensity: 0.8,
+        },
+    );
+    emitter.rate = 120.0;
+    emitter.lifetime = (0.8, 1.4);
+    emitter.speed = (0.4, 0.8);
+    emitter.forces = vec![Force::Attraction(0.9)];
+    emitter.pulse = Some(Pulse { period: 3.0, duty: 0.3 });
+    emitter
+}
+
+/// Estela del motor principal: sale hacia atrás por la tobera en `offset`
+/// y se queda en el mundo mientras la nave avanza
+pub fn engine_exhaust(offset: Vec3<f32>) -> Emitter {
+    let mut emitter = Emitter::new(
+        Anchor::Ship,
+        Space::World,
+        EmitterShape::Vent { offset, direction: Vec3::z(), spread: 0.12 },
+        ParticleStyle {
+            start_color: ShaderColor::new(0.7, 0.85, 1.0, 1.0),
+            end_color: ShaderColor::new(1.0, 0.45, 0.1, 0.0),
+            start_size: 0.08,
+            end_size: 0.25,
+            intensity: 0.45,
+        },
+    );
+    emitter.rate = 160.0;
+    emitter.strength = 0.0;
+    emitter.lifetime = (0.4, 0.9);
+    emitter.speed = (5.0, 7.0);
+    emitter.forces = vec![Force::Drag(1.5)];
+    emitter
+}
@@ -0,0 +1,129 @@
+use nalgebra::{Matrix4, Vector3 as Vec3, Vector4};
+use crate::matrix::multiply_matrix_vector4;
+use crate::planet::BoundingSphere;
+use crate::render::{WIDTH, HEIGHT};
+
+/// Rayo en coordenadas de mundo con dirección normalizada
+#[derive(Debug, Clone, Copy)]
+pub struct Ray {
+    pub origin: Vec3<f32>,
+    pub direction: Vec3<f32>,
+}
+
+fn unproject(ndc: Vector4<f32>, inverse_view_proj: &Matrix4<f32>) -> Vec3<f32> {
+    let world = multiply_matrix_vector4(inverse_view_proj, &ndc);
+    Vec3::new(world.x, world.y, world.z) / world.w
+}
+
+/// Rayo que sale de la cámara y atraviesa el píxel `(x, y)` de la ventana
+pub fn screen_ray(x: f32, y: f32, view_proj: &Matrix4<f32>) -> Option<Ray> {
+    let inverse = view_proj.try_inverse()?;
+
+    let ndc_x = (x + 0.5) / WIDTH as f32 * 2.0 - 1.0;
+    let ndc_y = 1.0 - (y + 0.5) / HEIGHT as f32 * 2.0;
+
+    let near = unproject(Vector4::new(ndc_x, ndc_y, -1.0, 1.0), &inverse);
+    let far = unproject(Vector4::new(ndc_x, ndc_y, 1.0, 1.0), &inverse);
+
+    let direction = far - near;
+    if direction.norm() < f32::EPSILON {
+        return None;
+    }
+    Some(Ray {
+        origin: near,
+        direction: direction.normalize(),
+    })
+}
+
+impl Ray {
+    /// Distancia a lo largo del rayo hasta la primera intersección con la esfera
+    pub fn intersect_sphere(&self, sphere: &BoundingSphere) -> Option<f32> {
+        let oc = self.origin - sphere.center;
+        let b = oc.dot(&self.direction);
+        let c = oc.dot(&oc) - sphere.radius * sphere.radius;
+        let discriminant = b * b - c;
+        if discriminant < 0.0 {
+            return None;
+        }
+
+        let root = discriminant.sqrt();
+        let near = -b - root;
+        let far = -b + root;
+        if near >= 0.0 {
+            Some(near)
+        } else if far >= 0.0 {
+            // El origen está dentro de la esfera
+            Some(0.0)
+        } else {
+            None
+        }
+    }
+}
+
+/// Índice de la esfera más cercana que atraviesa el rayo
+pub fn pick(ray: &Ray, spheres: &[BoundingSphere]) -> Option<usize> {
+    spheres
+        .iter()
+        .enumerate()
+        .filter_map(|(i, sphere)| ray.intersect_sphere(sphere).map(|t| (i, t)))
+        .min_by(|a, b| a.1.total_cmp(&b.1))
+        .map(|(i, _)| i)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::matrix::{create_projection_matrix, create_view_matrix};
+
+    // Cámara en z = 10 mirando al origen
+    fn view_proj() -> Matrix4<f32> {
+        let projection = create_projection_matrix(
+            std::f32::consts::FRAC_PI_3,
+            WIDTH as f32 / HEIGHT as f32,
+            0.1,
+            1000.0,
+        );
+        projection * create_view_matrix(Vec3::new(0.0, 0.0, 10.0), Vec3::zeros(), Vec3::y())
+    }
+
+    fn center_ray() -> Ray {
+        // El centro del píxel (WIDTH/2 - 0.5, HEIGHT/2 - 0.5) cae en NDC (0, 0)
+        screen_ray(WIDTH as f32 / 2.0 - 0.5, HEIGHT as f32 / 2.0 - 0.5, &view_proj()).unwrap()
+    }
+
+    fn sphere(z: f32, radius: f32) -> BoundingSphere {
+        BoundingSphere { center: Vec3::new(0.0, 0.0, z), radius }
+    }
+
+    #[test]
+    fn center_ray_follows_the_view_axis() {
+        let ray = center_ray();
+        assert!((ray.direction - Vec3::new(0.0, 0.0, -1.0)).norm() < 1e-4, "{:?}", ray.direction);
+        // Sale del plano cercano, a 0.1 de la cámara
+        assert!((ray.origin - Vec3::new(0.0, 0.0, 9.9)).norm() < 1e-3, "{:?}", ray.origin);
+
+        let distance = ray.intersect_sphere(&sphere(0.0, 1.0)).unwrap();
+        assert!((distance - 8.9).abs() < 1e-3, "{}", distance);
+        // Un rayo desde la esquina no toca la esfera del eje
+        let corner = screen_ray(0.0, 0.0, &view_proj()).unwrap();
+        assert_eq!(corner.intersect_sphere(&sphere(0.0, 1.0)), None);
+    }
+
+    #[test]
+    fn nearest_sphere_wins() {
+        let ray = center_ray();
+        // La lejana es más grande y va primero en la lista
+        let spheres = [sphere(-20.0, 5.0), sphere(2.0, 0.5)];
+        assert_eq!(pick(&ray, &spheres), Some(1));
+        assert_eq!(pick(&ray, &spheres[..1]), Some(0));
+    }
+
+    #[test]
+    fn spheres_behind_the_camera_are_ignored() {
+        let ray = center_ray();
+        assert_eq!(pick(&ray, &[sphere(20.0, 2.0)]), None);
+        assert_eq!(pick(&ray, &[sphere(20.0, 2.0), sphere(-5.0, 1.0)]), Some(1));
+        // Con la cámara dentro de una esfera la distancia es 0
+        assert_eq!(ray.intersect_sphere(&sphere(10.0, 3.0)), Some(0.0));
+    }
+}
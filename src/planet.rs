@@ -1,8 +1,24 @@
 use nalgebra::{Matrix4, Vector3 as Vec3};
 use crate::shaders::PlanetShader;
 use crate::matrix::create_model_matrix;
+use crate::orbit::KeplerOrbit;
+use crate::comets::CometTail;
+
+/// Esfera envolvente de un cuerpo en coordenadas de mundo
+#[derive(Debug, Clone, Copy)]
+pub struct BoundingSphere {
+    pub center: Vec3<f32>,
+    pub radius: f32,
+}
+
+// Distancia mínima por defecto entre la cámara y la superficie de un cuerpo
+const DEFAULT_MIN_ALTITUDE: f32 = 0.5;
+// Parámetro gravitatorio por unidad de volumen: G * masa = GRAVITY_DENSITY * r³
+const GRAVITY_DENSITY: f32 = 0.3;
 
 pub struct Planet {
+    /// Nombre que se muestra en las etiquetas y el HUD
+    pub name: String,
     pub shader: Box<dyn PlanetShader>,
     pub position: Vec3<f32>,
     pub scale: f32,
@@ -11,10 +27,23 @@ pub struct Planet {
     pub orbit_radius: f32,
     pub orbit_speed: f32,
     pub orbit_angle: f32,
+    pub initial_angle: f32,
+    /// Velocidad en coordenadas de mundo en el instante actual
+    pub velocity: Vec3<f32>,
+    /// Órbita elíptica que sustituye a la circular; `orbit_radius` guarda
+    /// entonces el semieje mayor y `orbit_speed` la velocidad angular media
+    pub kepler: Option<KeplerOrbit>,
+    /// Cola que se dibuja detrás del cuerpo cuando se acerca al sol
+    pub tail: Option<CometTail>,
+    /// Distancia mínima que la cámara guarda sobre la superficie
+    pub min_altitude: f32,
+    /// Nivel de detalle de la malla con que se dibujó en el último cuadro
+    pub lod_level: usize,
 }
 
 impl Planet {
     pub fn new(
+        name: &str,
         shader: Box<dyn PlanetShader>,
         orbit_radius: f32,
         scale: f32,
@@ -26,6 +55,7 @@ impl Planet {
         let initial_z = initial_angle.sin() * orbit_radius;
         
         Planet {
+            name: name.to_string(),
             shader,
             position: Vec3::new(initial_x, 0.0, initial_z),
             scale,
@@ -34,14 +64,70 @@ impl Planet {
             orbit_radius,
             orbit_speed,
             orbit_angle: initial_angle,
+            initial_angle,
+            velocity: Vec3::new(-initial_angle.sin(), 0.0, initial_angle.cos()) * orbit_radius * orbit_speed,
+            kepler: None,
+            tail: None,
+            min_altitude: DEFAULT_MIN_ALTITUDE,
+            lod_level: 0,
         }
     }
 
-    pub fn update(&mut self, dt: f32) {
-        self.orbit_angle += self.orbit_speed * dt;
-        
+    /// Coloca el cuerpo donde está en el instante `time` de la simulación.
+    /// Se calcula desde el ángulo inicial para poder retroceder o saltar.
+    pub fn set_time(&mut self, time: f32) {
+        if let Some(orbit) = &self.kepler {
+            self.position = orbit.position_at(time);
+            self.velocity = orbit.velocity_at(time);
+            self.orbit_angle = self.position.z.atan2(self.position.x);
+            return;
+        }
+
+        self.orbit_angle = self.initial_angle + self.orbit_speed * time;
+
         self.position.x = self.orbit_angle.cos() * self.orbit_radius;
         self.position.z = self.orbit_angle.sin() * self.orbit_radius;
+        let tangential_speed = self.orbit_radius * self.orbit_speed;
+        self.velocity = Vec3::new(-self.orbit_angle.sin(), 0.0, self.orbit_angle.cos()) * tangential_speed;
+    }
+
+    /// Cuerpo que sigue una órbita elíptica, colocado en el instante 0
+    pub fn on_kepler_orbit(name: &str, shader: Box<dyn PlanetShader>, orbit: KeplerOrbit, scale: f32) -> Self {
+        let mut planet = Planet::new(name, shader, orbit.semi_major_axis, scale, 0.0, orbit.mean_motion, 0.0);
+        planet.kepler = Some(orbit);
+        planet.set_time(0.0);
+        planet
+    }
+
+    /// Con órbita circular fija, no elíptica ni de N cuerpos
+    pub fn has_circular_orbit(&self) -> bool {
+        self.orbit_radius > 0.0 && self.kepler.is_none()
+    }
+
+    /// Radio de la esfera que envuelve al cuerpo en coordenadas de mundo,
+    /// contando lo que el vertex shader puede desplazar la superficie
+    pub fn bounding_radius(&self, mesh_radius: f32) -> f32 {
+        (mesh_radius + self.shader.max_displacement()) * self.scale
+    }
+
+    /// G por la masa del cuerpo, proporcional al volumen de la malla sin relieve
+    pub fn gravitational_parameter(&self, mesh_radius: f32) -> f32 {
+        GRAVITY_DENSITY * (self.scale * mesh_radius).powi(3)
+    }
+
+    pub fn bounding_sphere(&self, mesh_radius: f32) -> BoundingSphere {
+        BoundingSphere {
+            center: self.position,
+            radius: self.bounding_radius(mesh_radius),
+        }
+    }
+
+    /// Esfera en la que la cámara no puede entrar
+    pub fn collision_sphere(&self, mesh_radius: f32) -> BoundingSphere {
+        BoundingSphere {
+            center: self.position,
+            radius: self.bounding_radius(mesh_radius) + self.min_altitude,
+        }
     }
 
     pub fn get_model_matrix(&self) -> Matrix4<f32> {
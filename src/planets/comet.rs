@@ -0,0 +1,45 @@
+use crate::vector::Vector3;
+use crate::shaders::{ShaderColor, ShaderUniforms, PlanetShader, fbm3d, mix_color, smoothstep};
+
+/// Núcleo de cometa: roca oscura e irregular con hielo que brilla en la cara
+/// que mira al sol. Se ilumina desde la posición real del sol, no desde la
+/// luz fija de los planetas.
+pub struct CometShader;
+
+impl PlanetShader for CometShader {
+    fn map_color(&self) -> ShaderColor {
+        ShaderColor::from_rgb(190, 235, 255)
+    }
+
+    fn max_displacement(&self) -> f32 {
+        0.3
+    }
+
+    fn vertex_shader(&self, position: Vector3, normal: Vector3, _uv: (f32, f32), _uniforms: &ShaderUniforms) -> (Vector3, Vector3) {
+        // Forma de patata: ruido de baja frecuencia sobre la esfera
+        let lumps = fbm3d(position.x * 1.3 + 4.0, position.y * 1.3, position.z * 1.3, 3) * 0.3;
+        (position + normal * lumps, normal)
+    }
+
+    fn fragment_shader(&self, position: Vector3, normal: Vector3, _uv: (f32, f32), uniforms: &ShaderUniforms) -> ShaderColor {
+        let rock = ShaderColor::from_rgb(60, 56, 54);
+        let ice = ShaderColor::from_rgb(205, 225, 240);
+        let frost = smoothstep(0.05, 0.35, fbm3d(position.x * 4.0, position.y * 4.0, position.z * 4.0, 3));
+        let base = mix_color(rock, ice, frost * 0.6);
+
+        // El modelo no gira, así que la dirección al sol en el mundo sirve en
+        // el espacio del modelo
+        let to_sun = (uniforms.sun_position - uniforms.model_position).normalize();
+        let diffuse = normal.normalize().dot(&to_sun).max(0.0);
+        let intensity = 0.12 + 0.88 * diffuse;
+
+        // Los chorros de gas salen del lado iluminado
+        let jets = smoothstep(0.6, 1.0, diffuse) * frost * 0.4;
+        ShaderColor::new(
+            (base.r * intensity + jets).min(1.0),
+            (base.g * intensity + jets).min(1.0),
+            (base.b * intensity + jets * 1.1).min(1.0),
+            1.0,
+        )
+    }
+}
@@ -4,6 +4,14 @@ use crate::shaders::{ShaderColor, ShaderUniforms, PlanetShader, fbm, fbm3d, voro
 pub struct CrystalPlanetShader;
 
 impl PlanetShader for CrystalPlanetShader {
+    fn map_color(&self) -> ShaderColor {
+        ShaderColor::from_rgb(120, 170, 255)
+    }
+
+    fn max_displacement(&self) -> f32 {
+        0.01
+    }
+
     fn vertex_shader(&self, position: Vector3, normal: Vector3, _uv: (f32, f32), uniforms: &ShaderUniforms) -> (Vector3, Vector3) {
         // Deformación de pulso de energía
         let pulse = (uniforms.time * 3.0 + position.length() * 5.0).sin() * 0.01;
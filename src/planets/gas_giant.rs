@@ -4,6 +4,10 @@ use crate::shaders::{ShaderColor, ShaderUniforms, PlanetShader, fbm, smoothstep,
 pub struct GasPlanetShader;
 
 impl PlanetShader for GasPlanetShader {
+    fn map_color(&self) -> ShaderColor {
+        ShaderColor::from_rgb(225, 160, 90)
+    }
+
     fn vertex_shader(&self, position: Vector3, normal: Vector3, _uv: (f32, f32), _uniforms: &ShaderUniforms) -> (Vector3, Vector3) {
         (position, normal)
     }
@@ -4,6 +4,15 @@ use crate::shaders::{ShaderColor, ShaderUniforms, PlanetShader, fbm, fbm3d, voro
 pub struct MetallicPlanetShader;
 
 impl PlanetShader for MetallicPlanetShader {
+    fn map_color(&self) -> ShaderColor {
+        ShaderColor::from_rgb(190, 195, 210)
+    }
+
+    fn max_displacement(&self) -> f32 {
+        // Los picos grandes y medianos en su máximo de crecimiento más el resto de capas
+        0.95
+    }
+
     fn vertex_shader(&self, position: Vector3, normal: Vector3, _uv: (f32, f32), uniforms: &ShaderUniforms) -> (Vector3, Vector3) {
         // === GENERAR PICOS/PÚAS PROCEDURALMENTE CON ANIMACIÓN ===
         
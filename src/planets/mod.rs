@@ -4,6 +4,7 @@ pub mod gas_giant;
 pub mod crystal;
 pub mod nebula;
 pub mod metallic;
+pub mod comet;
 
 pub use sun::SunShader;
 pub use rocky::RockyPlanetShader;
@@ -11,3 +12,4 @@ pub use gas_giant::GasPlanetShader;
 pub use crystal::CrystalPlanetShader;
 pub use nebula::NebulaPlanetShader;
 pub use metallic::MetallicPlanetShader;
+pub use comet::CometShader;
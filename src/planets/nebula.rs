@@ -4,6 +4,15 @@ use crate::shaders::{ShaderColor, ShaderUniforms, PlanetShader, fbm, fbm3d, voro
 pub struct NebulaPlanetShader;
 
 impl PlanetShader for NebulaPlanetShader {
+    fn map_color(&self) -> ShaderColor {
+        ShaderColor::from_rgb(240, 95, 45)
+    }
+
+    fn max_displacement(&self) -> f32 {
+        // Suma de las dos ondas
+        0.05
+    }
+
     fn vertex_shader(&self, position: Vector3, normal: Vector3, _uv: (f32, f32), uniforms: &ShaderUniforms) -> (Vector3, Vector3) {
         let wave1 = (uniforms.time * 1.5 + position.x * 3.0 + position.y * 2.0).sin() * 0.03;
         let wave2 = (uniforms.time * 2.0 - position.z * 4.0 + position.y).cos() * 0.02;
@@ -4,6 +4,15 @@ use crate::shaders::{ShaderColor, ShaderUniforms, PlanetShader, fbm3d, voronoi_n
 pub struct RockyPlanetShader;
 
 impl PlanetShader for RockyPlanetShader {
+    fn map_color(&self) -> ShaderColor {
+        ShaderColor::from_rgb(165, 150, 135)
+    }
+
+    fn max_displacement(&self) -> f32 {
+        // Montañas, colinas y detalle; los cráteres hunden
+        0.27
+    }
+
     fn vertex_shader(&self, position: Vector3, normal: Vector3, _uv: (f32, f32), uniforms: &ShaderUniforms) -> (Vector3, Vector3) {
         // === DEFORMACIÓN PROCEDURAL DEL TERRENO ===
         
@@ -4,6 +4,15 @@ use crate::shaders::{ShaderColor, ShaderUniforms, PlanetShader, fbm3d, fbm, mix_
 pub struct SunShader;
 
 impl PlanetShader for SunShader {
+    fn map_color(&self) -> ShaderColor {
+        ShaderColor::from_rgb(255, 210, 90)
+    }
+
+    fn max_displacement(&self) -> f32 {
+        // Plasma, llamaradas, granulación y prominencias
+        0.09
+    }
+
     fn vertex_shader(&self, position: Vector3, normal: Vector3, _uv: (f32, f32), uniforms: &ShaderUniforms) -> (Vector3, Vector3) {
         
         // Capa 1: Ondas de plasma muy sutiles
@@ -0,0 +1,452 @@
+// Decodificador de PNG sin dependencias: lee las imágenes no entrelazadas
+// de 1 a 16 bits por canal que exportan Blender y las herramientas de glTF
+
+const SIGNATURE: [u8; 8] = [0x89, b'P', b'N', b'G', 0x0D, 0x0A, 0x1A, 0x0A];
+
+// Tablas de deflate (RFC 1951): base y bits extra de cada código de
+// longitud y distancia
+const LENGTH_BASE: [u16; 29] = [
+    3, 4, 5, 6, 7, 8, 9, 10, 11, 13, 15, 17, 19, 23, 27, 31, 35, 43, 51, 59, 67, 83, 99, 115, 131, 163, 195, 227, 258,
+];
+const LENGTH_EXTRA: [u8; 29] = [0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 2, 2, 2, 2, 3, 3, 3, 3, 4, 4, 4, 4, 5, 5, 5, 5, 0];
+const DISTANCE_BASE: [u16; 30] = [
+    1, 2, 3, 4, 5, 7, 9, 13, 17, 25, 33, 49, 65, 97, 129, 193, 257, 385, 513, 769, 1025, 1537, 2049, 3073, 4097, 6145,
+    8193, 12289, 16385, 24577,
+];
+const DISTANCE_EXTRA: [u8; 30] = [0, 0, 0, 0, 1, 1, 2, 2, 3, 3, 4, 4, 5, 5, 6, 6, 7, 7, 8, 8, 9, 9, 10, 10, 11, 11, 12, 12, 13, 13];
+// Orden en que llegan las longitudes del código de longitudes
+const CODE_LENGTH_ORDER: [usize; 19] = [16, 17, 18, 0, 8, 7, 9, 6, 10, 5, 11, 4, 12, 3, 13, 2, 14, 1, 15];
+
+/// Imagen decodificada a RGB de 8 bits, fila a fila desde arriba
+pub fn decode(data: &[u8]) -> Result<(usize, usize, Vec<[u8; 3]>), String> {
+    if !data.starts_with(&SIGNATURE) {
+        return Err("no es un PNG".to_string());
+    }
+    let mut position = SIGNATURE.len();
+    let mut header = None;
+    let mut palette: Vec<[u8; 3]> = Vec::new();
+    let mut compressed = Vec::new();
+    while position + 8 <= data.len() {
+        let length = u32::from_be_bytes(data[position..position + 4].try_into().unwrap()) as usize;
+        let kind = &data[position + 4..position + 8];
+        let body = data.get(position + 8..position + 8 + length).ok_or("bloque PNG cortado")?;
+        match kind {
+            b"IHDR" if body.len() >= 13 => {
+                let width = u32::from_be_bytes(body[0..4].try_into().unwrap()) as usize;
+                let height = u32::from_be_bytes(body[4..8].try_into().unwrap()) as usize;
+                header = Some((width, height, body[8], body[9], body[12]));
+            }
+            b"PLTE" => palette = body.chunks_exact(3).map(|c| [c[0], c[1], c[2]]).collect(),
+            b"IDAT" => compressed.extend_from_slice(body),
+            b"IEND" => break,
+            _ => {}
+        }
+        // Longitud, tipo, datos y CRC, que no se comprueba
+        position += 12 + length;
+    }
+
+    let (width, height, depth, color_type, interlace) = header.ok_or("falta la cabecera IHDR")?;
+    if interlace != 0 {
+        return Err("no se admiten PNG entrelazados".to_string());
+    }
+    let channels = match (color_type, depth) {
+        (0, 1 | 2 | 4 | 8 | 16) => 1,
+        (3, 1 | 2 | 4 | 8) => 1,
+        (2, 8 | 16) => 3,
+        (4, 8 | 16) => 2,
+        (6, 8 | 16) => 4,
+        _ => return Err(format!("tipo de color {} con {} bits no válido", color_type, depth)),
+    };
+    if width == 0 || height == 0 || width > 16384 || height > 16384 {
+        return Err(format!("tamaño {}x{} no admitido", width, height));
+    }
+    if color_type == 3 && palette.is_empty() {
+        return Err("imagen con paleta sin bloque PLTE".to_string());
+    }
+
+    let raw = zlib_decompress(&compressed)?;
+    let bits_per_pixel = channels * depth as usize;
+    let stride = (width * bits_per_pixel).div_ceil(8);
+    let rows = unfilter(&raw, stride, bits_per_pixel.div_ceil(8), height)?;
+
+    let sample = |row: &[u8], index: usize| -> usize {
+        match depth {
+            8 => row[index] as usize,
+            // Con 16 bits basta el byte alto
+            16 => row[index * 2] as usize,
+            _ => {
+                let bit = index * depth as usize;
+                let shift = 8 - depth as usize - bit % 8;
+                (row[bit / 8] as usize >> shift) & ((1 << depth) - 1)
+            }
+        }
+    };
+    let to_byte = |value: usize| -> u8 {
+        match depth {
+            8 | 16 => value as u8,
+            _ => (value * 255 / ((1 << depth) - 1)) as u8,
+        }
+    };
+
+    let mut pixels = Vec::with_capacity(width * height);
+    for row in rows.chunks_exact(stride) {
+        for x in 0..width {
+            let first = x * channels;
+            pixels.push(match color_type {
+                0 | 4 => [to_byte(sample(row, first)); 3],
+                3 => *palette.get(sample(row, first)).ok_or("índice de paleta fuera de rango")?,
+                _ => [sample(row, first) as u8, sample(row, first + 1) as u8, sample(row, first + 2) as u8],
+            });
+        }
+    }
+    Ok((width, height, pixels))
+}
+
+/// Deshace los filtros de cada fila; `raw` lleva un byte de filtro por fila
+fn unfilter(raw: &[u8], stride: usize, bytes_per_pixel: usize, height: usize) -> Result<Vec<u8>, String> {
+    if raw.len() < (stride + 1) * height {
+        return Err("faltan datos de imagen".to_string());
+    }
+    let mut rows = vec![0u8; stride * height];
+    for y in 0..height {
+        let filter = raw[y * (stride + 1)];
+        let source = &raw[y * (stride + 1) + 1..(y + 1) * (stride + 1)];
+        let (done, current) = rows.split_at_mut(y * stride);
+        let previous = if y > 0 { &done[(y - 1) * stride..] } else { &[][..] };
+        let current = &mut current[..stride];
+        for x in 0..stride {
+            let left = if x >= bytes_per_pixel { current[x - bytes_per_pixel] } else { 0 };
+            let up = previous.get(x).copied().unwrap_or(0);
+            let up_left = if x >= bytes_per_pixel { previous.get(x - bytes_per_pixel).copied().unwrap_or(0) } else { 0 };
+            let predicted = match filter {
+                0 => 0,
+                1 => left,
+                2 => up,
+                3 => ((left as u16 + up as u16) / 2) as u8,
+                4 => paeth(left, up, up_left),
+                _ => return Err(format!("filtro de fila {} desconocido", filter)),
+            };
+            current[x] = source[x].wrapping_add(predicted);
+        }
+    }
+    Ok(rows)
+}
+
+fn paeth(a: u8, b: u8, c: u8) -> u8 {
+    let p = a as i16 + b as i16 - c as i16;
+    let (pa, pb, pc) = ((p - a as i16).abs(), (p - b as i16).abs(), (p - c as i16).abs());
+    if pa <= pb && pa <= pc {
+        a
+    } else if pb <= pc {
+        b
+    } else {
+        c
+    }
+}
+
+/// Lee los bits de un flujo deflate, del menos significativo al más
+struct BitReader<'a> {
+    data: &'a [u8],
+    position: usize,
+    bit: u32,
+}
+
+impl BitReader<'_> {
+    fn bits(&mut self, count: u32) -> Result<u32, String> {
+        let mut value = 0;
+        for i in 0..count {
+            let byte = *self.data.get(self.position).ok_or("datos comprimidos cortados")?;
+            value |= ((byte >> self.bit) as u32 & 1) << i;
+            self.bit += 1;
+            if self.bit == 8 {
+                self.bit = 0;
+                self.position += 1;
+            }
+        }
+        Ok(value)
+    }
+
+    fn align_to_byte(&mut self) {
+        if self.bit > 0 {
+            self.bit = 0;
+            self.position += 1;
+        }
+    }
+}
+
+/// Código de Huffman canónico: cuántos códigos hay de cada largo y los
+/// símbolos ordenados por código
+struct Huffman {
+    counts: [u16; 16],
+    symbols: Vec<u16>,
+}
+
+impl Huffman {
+    fn new(lengths: &[u8]) -> Self {
+        let mut counts = [0u16; 16];
+        for &length in lengths {
+            counts[length as usize] += 1;
+        }
+        counts[0] = 0;
+        let mut offsets = [0u16; 16];
+        for length in 1..15 {
+            offsets[length + 1] = offsets[length] + counts[length];
+        }
+        let mut symbols = vec![0; lengths.len()];
+        for (symbol, &length) in lengths.iter().enumerate() {
+            if length != 0 {
+                symbols[offsets[length as usize] as usize] = symbol as u16;
+                offsets[length as usize] += 1;
+            }
+        }
+        Huffman { counts, symbols }
+    }
+
+    fn decode(&self, reader: &mut BitReader) -> Result<u16, String> {
+        let (mut code, mut first, mut index) = (0i32, 0i32, 0i32);
+        for length in 1..16 {
+            code |= reader.bits(1)? as i32;
+            let count = self.counts[length] as i32;
+            if code - first < count {
+                return self
+                    .symbols
+                    .get((index + code - first) as usize)
+                    .copied()
+                    .ok_or_else(|| "código de Huffman no válido".to_string());
+            }
+            index += count;
+            first = (first + count) << 1;
+            code <<= 1;
+        }
+        Err("código de Huffman no válido".to_string())
+    }
+}
+
+/// Descomprime un flujo zlib (cabecera de 2 bytes, deflate y suma Adler-32,
+/// que no se comprueba)
+fn zlib_decompress(data: &[u8]) -> Result<Vec<u8>, String> {
+    if data.len() < 2 || data[0] & 0x0F != 8 || !(data[0] as u16 * 256 + data[1] as u16).is_multiple_of(31) {
+        return Err("cabecera zlib no válida".to_string());
+    }
+    if data[1] & 0x20 != 0 {
+        return Err("zlib con diccionario no admitido".to_string());
+    }
+    let mut reader = BitReader { data: &data[2..], position: 0, bit: 0 };
+    let mut output = Vec::new();
+    loop {
+        let last = reader.bits(1)? == 1;
+        match reader.bits(2)? {
+            0 => {
+                reader.align_to_byte();
+                let start = reader.position;
+                let header = reader.data.get(start..start + 4).ok_or("bloque sin comprimir cortado")?;
+                let length = u16::from_le_bytes([header[0], header[1]]) as usize;
+                if length != !u16::from_le_bytes([header[2], header[3]]) as usize {
+                    return Err("longitud de bloque sin comprimir no válida".to_string());
+                }
+                let body = reader.data.get(start + 4..start + 4 + length).ok_or("bloque sin comprimir cortado")?;
+                output.extend_from_slice(body);
+                reader.position = start + 4 + length;
+            }
+            1 => {
+                let mut lengths = [0u8; 288];
+                lengths[..144].fill(8);
+                lengths[144..256].fill(9);
+                lengths[256..280].fill(7);
+                lengths[280..].fill(8);
+                let literals = Huffman::new(&lengths);
+                let distances = Huffman::new(&[5; 30]);
+                inflate_block(&mut reader, &literals, &distances, &mut output)?;
+            }
+            2 => {
+                let (literals, distances) = dynamic_tables(&mut reader)?;
+                inflate_block(&mut reader, &literals, &distances, &mut output)?;
+            }
+            _ => return Err("tipo de bloque deflate no válido".to_string()),
+        }
+        if last {
+            return Ok(output);
+        }
+    }
+}
+
+/// Lee las tablas de Huffman que trae un bloque comprimido dinámico
+fn dynamic_tables(reader: &mut BitReader) -> Result<(Huffman, Huffman), String> {
+    let literal_count = reader.bits(5)? as usize + 257;
+    let distance_count = reader.bits(5)? as usize + 1;
+    let code_length_count = reader.bits(4)? as usize + 4;
+    let mut code_lengths = [0u8; 19];
+    for &symbol in &CODE_LENGTH_ORDER[..code_length_count] {
+        code_lengths[symbol] = reader.bits(3)? as u8;
+    }
+    let code_length_code = Huffman::new(&code_lengths);
+
+    let mut lengths = Vec::with_capacity(literal_count + distance_count);
+    while lengths.len() < literal_count + distance_count {
+        let symbol = code_length_code.decode(reader)?;
+        let (value, repeat) = match symbol {
+            0..=15 => (symbol as u8, 1),
+            16 => (*lengths.last().ok_or("repetición sin longitud previa")?, 3 + reader.bits(2)?),
+            17 => (0, 3 + reader.bits(3)?),
+            _ => (0, 11 + reader.bits(7)?),
+        };
+        lengths.extend(std::iter::repeat_n(value, repeat as usize));
+    }
+    if lengths.len() > literal_count + distance_count {
+        return Err("demasiadas longitudes de código".to_string());
+    }
+    Ok((Huffman::new(&lengths[..literal_count]), Huffman::new(&lengths[literal_count..])))
+}
+
+fn inflate_block(reader: &mut BitReader, literals: &Huffman, distances: &Huffman, output: &mut Vec<u8>) -> Result<(), String> {
+    loop {
+        let symbol = literals.decode(reader)? as usize;
+        match symbol {
+            0..=255 => output.push(symbol as u8),
+            256 => return Ok(()),
+            _ => {
+                let code = symbol - 257;
+                if code >= LENGTH_BASE.len() {
+                    return Err("longitud no válida".to_string());
+                }
+                let length = LENGTH_BASE[code] as usize + reader.bits(LENGTH_EXTRA[code] as u32)? as usize;
+                let code = distances.decode(reader)? as usize;
+                if code >= DISTANCE_BASE.len() {
+                    return Err("distancia no válida".to_string());
+                }
+                let distance = DISTANCE_BASE[code] as usize + reader.bits(DISTANCE_EXTRA[code] as u32)? as usize;
+                if distance > output.len() {
+                    return Err("distancia antes del inicio de los datos".to_string());
+                }
+                // Byte a byte: la copia puede solaparse con lo que escribe
+                let start = output.len() - distance;
+                for i in 0..length {
+                    output.push(output[start + i]);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// PNG con un IHDR, los bloques `extra` y un IDAT con `zlib`. El CRC no
+    /// se comprueba al leer, así que va a cero.
+    fn png(width: u32, height: u32, color_type: u8, depth: u8, extra: &[(&[u8; 4], Vec<u8>)], zlib: &[u8]) -> Vec<u8> {
+        let mut data = SIGNATURE.to_vec();
+        let mut chunk = |kind: &[u8; 4], body: &[u8]| {
+            data.extend_from_slice(&(body.len() as u32).to_be_bytes());
+            data.extend_from_slice(kind);
+            data.extend_from_slice(body);
+            data.extend_from_slice(&[0; 4]);
+        };
+        let mut header = Vec::new();
+        header.extend_from_slice(&width.to_be_bytes());
+        header.extend_from_slice(&height.to_be_bytes());
+        header.extend_from_slice(&[depth, color_type, 0, 0, 0]);
+        chunk(b"IHDR", &header);
+        for (kind, body) in extra {
+            chunk(kind, body);
+        }
+        chunk(b"IDAT", zlib);
+        chunk(b"IEND", &[]);
+        data
+    }
+
+    /// Flujo zlib con los datos en bloques sin comprimir
+    fn stored(raw: &[u8]) -> Vec<u8> {
+        let mut zlib = vec![0x78, 0x01];
+        let blocks: Vec<&[u8]> = raw.chunks(7).collect();
+        for (i, block) in blocks.iter().enumerate() {
+            zlib.push((i + 1 == blocks.len()) as u8);
+            let length = block.len() as u16;
+            zlib.extend_from_slice(&length.to_le_bytes());
+            zlib.extend_from_slice(&(!length).to_le_bytes());
+            zlib.extend_from_slice(block);
+        }
+        zlib.extend_from_slice(&[0; 4]);
+        zlib
+    }
+
+    #[test]
+    fn stored_blocks_in_grayscale() {
+        // 3x2 en gris de 8 bits, sin filtros; varios bloques de 7 bytes
+        let raw = [0, 0, 128, 255, 0, 10, 20, 30];
+        let (width, height, pixels) = decode(&png(3, 2, 0, 8, &[], &stored(&raw))).unwrap();
+        assert_eq!((width, height), (3, 2));
+        let gray: Vec<u8> = pixels.iter().map(|p| p[0]).collect();
+        assert_eq!(gray, vec![0, 128, 255, 10, 20, 30]);
+        assert!(pixels.iter().all(|p| p[0] == p[1] && p[1] == p[2]));
+    }
+
+    #[test]
+    fn fixed_huffman_with_every_filter() {
+        // RGB 3x5 comprimido por zlib nivel 9 (bloque de Huffman fijo con
+        // referencias hacia atrás); la fila y usa el filtro y (0 a 4)
+        let zlib = [
+            0x78, 0xda, 0x63, 0x60, 0x60, 0xff, 0xef, 0x26, 0x7d, 0xbd, 0x47, 0x7f, 0x3d, 0xa3, 0x9c, 0xdd, 0x33, 0x37,
+            0x91, 0x1b, 0x40, 0xc4, 0x24, 0x67, 0xfe, 0x1c, 0x82, 0x98, 0x6d, 0x8a, 0xfc, 0x8c, 0xd4, 0x1e, 0x00, 0x11,
+            0x0b, 0x48, 0x40, 0x04, 0x84, 0x00, 0xbb, 0x47, 0x11, 0xe7,
+        ];
+        assert_eq!((zlib[2] >> 1) & 3, 1);
+        let (width, height, pixels) = decode(&png(3, 5, 2, 8, &[], &zlib)).unwrap();
+        assert_eq!((width, height), (3, 5));
+        for y in 0..5 {
+            for x in 0..3 {
+                let expected = [(x * 70 + y * 30) % 256, (x * 20 + y * 55 + 7) % 256, 255 - x * 40 - y * 25];
+                assert_eq!(pixels[y * 3 + x], expected.map(|c| c as u8), "píxel {} {}", x, y);
+            }
+        }
+    }
+
+    #[test]
+    fn each_filter_type_on_its_own() {
+        // Gris de 8 bits 2x2: la primera fila sin filtro y la segunda con cada tipo
+        let expected = [10, 20, 30, 50];
+        let rows = [
+            [0, 30, 50],
+            [1, 30, 20],
+            [2, 20, 30],
+            [3, 25, 25],
+            [4, 20, 20],
+        ];
+        for row in rows {
+            let raw = [&[0, 10, 20][..], &row[..]].concat();
+            let (_, _, pixels) = decode(&png(2, 2, 0, 8, &[], &stored(&raw))).unwrap();
+            let gray: Vec<u8> = pixels.iter().map(|p| p[0]).collect();
+            assert_eq!(gray, expected, "filtro {}", row[0]);
+        }
+    }
+
+    #[test]
+    fn palettes_and_low_bit_depths() {
+        let palette = vec![255, 0, 0, 0, 255, 0, 0, 0, 255];
+        // 4 píxeles de 2 bits en un byte: índices 0, 1, 2, 1
+        let raw = [0, 0b0001_1001];
+        let (_, _, pixels) = decode(&png(4, 1, 3, 2, &[(b"PLTE", palette)], &stored(&raw))).unwrap();
+        assert_eq!(pixels, vec![[255, 0, 0], [0, 255, 0], [0, 0, 255], [0, 255, 0]]);
+
+        let raw = [0, 0b1010_0000];
+        let (_, _, pixels) = decode(&png(3, 1, 0, 1, &[], &stored(&raw))).unwrap();
+        assert_eq!(pixels, vec![[255; 3], [0; 3], [255; 3]]);
+    }
+
+    #[test]
+    fn broken_images_are_errors() {
+        let raw = [0, 1, 2];
+        assert!(decode(b"no es png").is_err());
+        assert!(decode(&png(2, 2, 0, 8, &[], &stored(&raw))).unwrap_err().contains("faltan"));
+        assert!(decode(&png(3, 1, 0, 8, &[], &stored(&[5, 1, 2, 3]))).unwrap_err().contains("filtro"));
+        assert!(decode(&png(3, 1, 2, 4, &[], &stored(&raw))).unwrap_err().contains("tipo de color"));
+        assert!(decode(&png(1, 1, 3, 8, &[], &stored(&[0, 0]))).unwrap_err().contains("PLTE"));
+        assert!(decode(&png(0, 1, 0, 8, &[], &stored(&[0]))).unwrap_err().contains("tamaño"));
+        // Bloque fijo que empieza copiando: código 257 (longitud 3), distancia 1
+        let bad = [0x78, 0x01, 0x03, 0x02, 0x00];
+        assert!(decode(&png(3, 1, 0, 8, &[], &bad)).unwrap_err().contains("antes del inicio"));
+        let mut truncated = png(3, 1, 0, 8, &[], &stored(&[0, 1, 2, 3]));
+        truncated.truncate(SIGNATURE.len() + 20);
+        assert!(decode(&truncated).is_err());
+    }
+}
@@ -1,8 +1,9 @@
 use nalgebra::{Matrix4, Vector3 as Vec3, Vector4};
-use std::f32::consts::PI;
 use crate::vector::Vector3;
-use crate::shaders::{ShaderColor, ShaderUniforms};
-use crate::framebuffer::{Framebuffer, rgb_to_u32};
+use crate::shaders::{ShaderColor, ShaderUniforms, PlanetShader};
+use crate::obj_loader::ObjModel;
+use crate::material::{Material, MaterialShader, Texture};
+use crate::framebuffer::{Framebuffer, rgb_to_u32, blend, blend_add};
 use crate::matrix::multiply_matrix_vector4;
 use crate::planet::Planet;
 
@@ -41,11 +42,36 @@ pub fn draw_triangle(
     framebuffer: &mut Framebuffer,
     v0: (i32, i32, f32), v1: (i32, i32, f32), v2: (i32, i32, f32),
     c0: ShaderColor, c1: ShaderColor, c2: ShaderColor,
+) {
+    rasterize_triangle(framebuffer, [v0, v1, v2], [c0, c1, c2], None);
+}
+
+/// Como `draw_triangle`, pero multiplica el color de cada píxel por la
+/// textura en la UV interpolada, para que el detalle no dependa de los vértices
+#[allow(clippy::too_many_arguments)]
+pub fn draw_textured_triangle(
+    framebuffer: &mut Framebuffer,
+    v0: (i32, i32, f32), v1: (i32, i32, f32), v2: (i32, i32, f32),
+    c0: ShaderColor, c1: ShaderColor, c2: ShaderColor,
+    uvs: [(f32, f32); 3],
+    texture: &Texture,
+) {
+    rasterize_triangle(framebuffer, [v0, v1, v2], [c0, c1, c2], Some((uvs, texture)));
+}
+
+fn rasterize_triangle(
+    framebuffer: &mut Framebuffer,
+    [v0, v1, v2]: [(i32, i32, f32); 3],
+    [c0, c1, c2]: [ShaderColor; 3],
+    texture: Option<([(f32, f32); 3], &Texture)>,
 ) {
     let min_x = v0.0.min(v1.0).min(v2.0).max(0);
     let max_x = v0.0.max(v1.0).max(v2.0).min(WIDTH as i32 - 1);
     let min_y = v0.1.min(v1.1).min(v2.1).max(0);
     let max_y = v0.1.max(v1.1).max(v2.1).min(HEIGHT as i32 - 1);
+    framebuffer.stats.triangles += 1;
+    // Lo translúcido se mezcla con lo de detrás sin tapar lo que se dibuje después
+    let opaque = c0.a >= 1.0 && c1.a >= 1.0 && c2.a >= 1.0;
 
     for y in min_y..=max_y {
         for x in min_x..=max_x {
@@ -70,151 +96,387 @@ pub fn draw_triangle(
                 
                 let idx = y as usize * WIDTH + x as usize;
                 if depth < framebuffer.depth_buffer[idx] {
-                    framebuffer.depth_buffer[idx] = depth;
+                    if opaque {
+                        framebuffer.depth_buffer[idx] = depth;
+                    }
+                    framebuffer.stats.pixels += 1;
                     
-                    let r = (c0.r * w0 + c1.r * w1 + c2.r * w2).clamp(0.0, 1.0);
-                    let g = (c0.g * w0 + c1.g * w1 + c2.g * w2).clamp(0.0, 1.0);
-                    let b = (c0.b * w0 + c1.b * w1 + c2.b * w2).clamp(0.0, 1.0);
+                    let mut r = c0.r * w0 + c1.r * w1 + c2.r * w2;
+                    let mut g = c0.g * w0 + c1.g * w1 + c2.g * w2;
+                    let mut b = c0.b * w0 + c1.b * w1 + c2.b * w2;
+                    if let Some((uvs, texture)) = texture {
+                        let u = uvs[0].0 * w0 + uvs[1].0 * w1 + uvs[2].0 * w2;
+                        let v = uvs[0].1 * w0 + uvs[1].1 * w1 + uvs[2].1 * w2;
+                        let [tr, tg, tb] = texture.sample((u, v));
+                        r *= tr;
+                        g *= tg;
+                        b *= tb;
+                    }
+                    let (r, g, b) = (r.clamp(0.0, 1.0), g.clamp(0.0, 1.0), b.clamp(0.0, 1.0));
                     
-                    framebuffer.buffer[idx] = rgb_to_u32(
+                    let color = rgb_to_u32(
                         (r * 255.0) as u8,
                         (g * 255.0) as u8,
                         (b * 255.0) as u8,
                     );
+                    framebuffer.buffer[idx] = if opaque {
+                        color
+                    } else {
+                        let alpha = (c0.a * w0 + c1.a * w1 + c2.a * w2).clamp(0.0, 1.0);
+                        blend(framebuffer.buffer[idx], color, alpha)
+                    };
                 }
             }
         }
     }
 }
 
-fn draw_line_3d(
-    framebuffer: &mut Framebuffer,
-    start: Vec3<f32>,
-    end: Vec3<f32>,
-    view_proj: &Matrix4<f32>,
-    color: u32,
-) {
-    if let (Some(p0), Some(p1)) = (
-        project_vertex(&start, view_proj),
-        project_vertex(&end, view_proj),
-    ) {
-        let mut x0 = p0.0;
-        let mut y0 = p0.1;
-        let x1 = p1.0;
-        let y1 = p1.1;
-        
-        let dx = (x1 - x0).abs();
-        let dy = -(y1 - y0).abs();
-        let sx = if x0 < x1 { 1 } else { -1 };
-        let sy = if y0 < y1 { 1 } else { -1 };
-        let mut err = dx + dy;
-        
-        loop {
-            if x0 >= 0 && x0 < WIDTH as i32 && y0 >= 0 && y0 < HEIGHT as i32 {
-                let idx = y0 as usize * WIDTH + x0 as usize;
-                
-                let t = if dx > -dy {
-                    (x0 - p0.0) as f32 / (x1 - p0.0) as f32
-                } else {
-                    (y0 - p0.1) as f32 / (y1 - p0.1) as f32
-                };
-                let depth = p0.2 + (p1.2 - p0.2) * t.clamp(0.0, 1.0);
-                
-                if depth < framebuffer.depth_buffer[idx] {
-                    framebuffer.buffer[idx] = color;
-                }
+/// Punto cuadrado de `size` píxeles centrado en `center`, con prueba y
+/// escritura de profundidad; sirve de sprite para objetos muy lejanos
+pub fn draw_point(framebuffer: &mut Framebuffer, center: (i32, i32, f32), size: i32, color: u32) {
+    let (cx, cy, depth) = center;
+    let start = -(size - 1) / 2;
+    framebuffer.stats.points += 1;
+    for y in cy + start..cy + start + size {
+        for x in cx + start..cx + start + size {
+            if x < 0 || x >= WIDTH as i32 || y < 0 || y >= HEIGHT as i32 {
+                continue;
             }
-            
-            if x0 == x1 && y0 == y1 {
-                break;
+            let idx = y as usize * WIDTH + x as usize;
+            if depth < framebuffer.depth_buffer[idx] {
+                framebuffer.depth_buffer[idx] = depth;
+                framebuffer.buffer[idx] = color;
             }
-            
-            let e2 = 2 * err;
-            if e2 >= dy {
-                err += dy;
-                x0 += sx;
+        }
+    }
+}
+
+/// Mancha de luz aditiva de radio `radius` píxeles que se apaga hacia el
+/// borde. Se oculta detrás de lo ya dibujado pero no escribe profundidad, así
+/// que muchas se acumulan sin taparse entre ellas. Las de menos de un píxel
+/// reparten su brillo en uno solo.
+pub fn draw_glow(framebuffer: &mut Framebuffer, center: (f32, f32, f32), radius: f32, color: ShaderColor, intensity: f32) {
+    let (cx, cy, depth) = center;
+    framebuffer.stats.points += 1;
+    let mut add = |x: i32, y: i32, amount: f32| {
+        if x < 0 || x >= WIDTH as i32 || y < 0 || y >= HEIGHT as i32 {
+            return;
+        }
+        let idx = y as usize * WIDTH + x as usize;
+        if depth < framebuffer.depth_buffer[idx] {
+            framebuffer.buffer[idx] = blend_add(
+                framebuffer.buffer[idx],
+                color.r * amount,
+                color.g * amount,
+                color.b * amount,
+            );
+        }
+    };
+
+    if radius < 1.0 {
+        add(cx.floor() as i32, cy.floor() as i32, intensity * radius * radius);
+        return;
+    }
+    let reach = radius.ceil() as i32;
+    for dy in -reach..=reach {
+        for dx in -reach..=reach {
+            let x = cx.floor() as i32 + dx;
+            let y = cy.floor() as i32 + dy;
+            let fx = x as f32 + 0.5 - cx;
+            let fy = y as f32 + 0.5 - cy;
+            let falloff = 1.0 - (fx * fx + fy * fy) / (radius * radius);
+            if falloff > 0.0 {
+                add(x, y, intensity * falloff * falloff);
             }
-            if e2 <= dx {
-                err += dx;
-                y0 += sy;
+        }
+    }
+}
+
+/// Liang-Barsky: tramo `[t0, t1]` de un segmento en el que se cumplen todas
+/// las restricciones, cada una expresada como `f0 + t * df >= 0`
+fn clip_interval(constraints: &[(f32, f32)]) -> Option<(f32, f32)> {
+    let mut t0: f32 = 0.0;
+    let mut t1: f32 = 1.0;
+    for &(f0, df) in constraints {
+        if df.abs() < f32::EPSILON {
+            if f0 < 0.0 {
+                return None;
             }
+            continue;
+        }
+        let t = -f0 / df;
+        if df > 0.0 {
+            t0 = t0.max(t);
+        } else {
+            t1 = t1.min(t);
+        }
+        if t0 > t1 {
+            return None;
         }
     }
+    Some((t0, t1))
+}
+
+/// Recorta un segmento en coordenadas de clip contra el frustum.
+/// Devuelve los parámetros t de entrada y salida sobre el segmento, si queda algo visible.
+fn clip_line_homogeneous(a: &Vector4<f32>, b: &Vector4<f32>) -> Option<(f32, f32)> {
+    let d = b - a;
+    clip_interval(&[
+        (a.w + a.x, d.w + d.x),
+        (a.w - a.x, d.w - d.x),
+        (a.w + a.y, d.w + d.y),
+        (a.w - a.y, d.w - d.y),
+        (a.w - NEAR_W, d.w),
+    ])
 }
 
-pub fn render_ecliptic_plane(
+/// Recorta un segmento de pantalla al rectángulo `(x0, y0, x1, y1)`
+pub fn clip_segment_2d(
+    start: (f32, f32),
+    end: (f32, f32),
+    bounds: (f32, f32, f32, f32),
+) -> Option<((f32, f32), (f32, f32))> {
+    let (dx, dy) = (end.0 - start.0, end.1 - start.1);
+    let (t0, t1) = clip_interval(&[
+        (start.0 - bounds.0, dx),
+        (bounds.2 - start.0, -dx),
+        (start.1 - bounds.1, dy),
+        (bounds.3 - start.1, -dy),
+    ])?;
+    Some((
+        (start.0 + dx * t0, start.1 + dy * t0),
+        (start.0 + dx * t1, start.1 + dy * t1),
+    ))
+}
+
+// Valor mínimo de w para considerar un punto delante de la cámara
+const NEAR_W: f32 = 0.01;
+
+fn clip_to_screen(v: &Vector4<f32>) -> (i32, i32, f32) {
+    let x = v.x / v.w;
+    let y = v.y / v.w;
+    let z = v.z / v.w;
+    (
+        ((x + 1.0) * 0.5 * WIDTH as f32) as i32,
+        ((1.0 - y) * 0.5 * HEIGHT as f32) as i32,
+        z,
+    )
+}
+
+/// Bresenham en pantalla. Si `depth_test` es verdadero solo pinta donde la línea
+/// queda delante de lo que ya hay en el z-buffer (sin escribirlo).
+fn rasterize_line(
     framebuffer: &mut Framebuffer,
-    view_proj: &Matrix4<f32>,
+    p0: (i32, i32, f32),
+    p1: (i32, i32, f32),
+    color: u32,
+    depth_test: bool,
 ) {
-    let orbit_color = rgb_to_u32(100, 140, 200);  
-    let orbit_radii = vec![6.0, 9.0, 12.0, 15.0, 18.0]; 
-    
-    for &radius in &orbit_radii {
-        let segments = 64;
-        for i in 0..segments {
-            let angle1 = (i as f32 / segments as f32) * 2.0 * PI;
-            let angle2 = ((i + 1) as f32 / segments as f32) * 2.0 * PI;
-            
-            let x1 = angle1.cos() * radius;
-            let z1 = angle1.sin() * radius;
-            let x2 = angle2.cos() * radius;
-            let z2 = angle2.sin() * radius;
-            
-            draw_line_3d(
-                framebuffer,
-                Vec3::new(x1, 0.0, z1),
-                Vec3::new(x2, 0.0, z2),
-                view_proj,
-                orbit_color,
-            );
+    let mut x0 = p0.0;
+    let mut y0 = p0.1;
+    let x1 = p1.0;
+    let y1 = p1.1;
+
+    let dx = (x1 - x0).abs();
+    let dy = -(y1 - y0).abs();
+    let sx = if x0 < x1 { 1 } else { -1 };
+    let sy = if y0 < y1 { 1 } else { -1 };
+    let mut err = dx + dy;
+
+    loop {
+        if x0 >= 0 && x0 < WIDTH as i32 && y0 >= 0 && y0 < HEIGHT as i32 {
+            let idx = y0 as usize * WIDTH + x0 as usize;
+
+            let t = if dx > -dy {
+                (x0 - p0.0) as f32 / (x1 - p0.0) as f32
+            } else {
+                (y0 - p0.1) as f32 / (y1 - p0.1) as f32
+            };
+            let depth = p0.2 + (p1.2 - p0.2) * t.clamp(0.0, 1.0);
+
+            if !depth_test || depth < framebuffer.depth_buffer[idx] {
+                framebuffer.buffer[idx] = color;
+            }
+        }
+
+        if x0 == x1 && y0 == y1 {
+            break;
+        }
+
+        let e2 = 2 * err;
+        if e2 >= dy {
+            err += dy;
+            x0 += sx;
+        }
+        if e2 <= dx {
+            err += dx;
+            y0 += sy;
         }
     }
 }
 
-pub fn render_planet(
+pub fn draw_line_3d(
     framebuffer: &mut Framebuffer,
-    planet: &Planet,
-    vertices: &[Vector3],
-    normals: &[Vector3],
-    uvs: &[(f32, f32)],
-    indices: &[(usize, usize, usize)],
+    start: Vec3<f32>,
+    end: Vec3<f32>,
+    view_proj: &Matrix4<f32>,
+    color: u32,
+) {
+    let a = multiply_matrix_vector4(view_proj, &Vector4::new(start.x, start.y, start.z, 1.0));
+    let b = multiply_matrix_vector4(view_proj, &Vector4::new(end.x, end.y, end.z, 1.0));
+
+    // Recortar antes de proyectar evita líneas que cruzan detrás de la cámara
+    // y recorridos de Bresenham enormes fuera de pantalla
+    if let Some((t0, t1)) = clip_line_homogeneous(&a, &b) {
+        let p0 = clip_to_screen(&(a + (b - a) * t0));
+        let p1 = clip_to_screen(&(a + (b - a) * t1));
+        rasterize_line(framebuffer, p0, p1, color, true);
+    }
+}
+
+/// Dibuja una línea en coordenadas de pantalla, por encima de la escena
+pub fn draw_line_2d(
+    framebuffer: &mut Framebuffer,
+    start: (f32, f32),
+    end: (f32, f32),
+    color: u32,
+) {
+    let window = (0.0, 0.0, (WIDTH - 1) as f32, (HEIGHT - 1) as f32);
+    draw_line_2d_within(framebuffer, start, end, window, color);
+}
+
+/// Como `draw_line_2d`, pero solo la parte dentro del rectángulo `bounds`,
+/// que debe caber en la ventana
+pub fn draw_line_2d_within(
+    framebuffer: &mut Framebuffer,
+    start: (f32, f32),
+    end: (f32, f32),
+    bounds: (f32, f32, f32, f32),
+    color: u32,
+) {
+    if let Some((a, b)) = clip_segment_2d(start, end, bounds) {
+        let p0 = (a.0.round() as i32, a.1.round() as i32, 0.0);
+        let p1 = (b.0.round() as i32, b.1.round() as i32, 0.0);
+        rasterize_line(framebuffer, p0, p1, color, false);
+    }
+}
+
+/// Dibuja una malla con su shader. Los shaders trabajan en el espacio del
+/// modelo y `model` lleva los vértices al mundo.
+pub fn render_mesh(
+    framebuffer: &mut Framebuffer,
+    mesh: &ObjModel,
+    shader: &dyn PlanetShader,
+    model: &Matrix4<f32>,
     view_proj: &Matrix4<f32>,
     uniforms: &ShaderUniforms,
 ) {
-    let model = planet.get_model_matrix();
-    let mvp = view_proj * model;
+    render_triangles(framebuffer, mesh, 0..mesh.indices.len(), shader, model, view_proj, uniforms);
+}
 
-    for &(i0, i1, i2) in indices {
-        let v0 = &vertices[i0];
-        let v1 = &vertices[i1];
-        let v2 = &vertices[i2];
+/// Dibuja cada grupo de la malla con su material del archivo MTL; los
+/// triángulos sin material usan el gris por defecto de MTL
+pub fn render_materials(
+    framebuffer: &mut Framebuffer,
+    mesh: &ObjModel,
+    shader_for: impl Fn(&Material) -> MaterialShader,
+    model: &Matrix4<f32>,
+    view_proj: &Matrix4<f32>,
+    uniforms: &ShaderUniforms,
+) {
+    let default_material = Material::new("");
+    for group in &mesh.groups {
+        let material = group.material.map_or(&default_material, |index| &mesh.materials[index]);
+        let shader = shader_for(material);
+        render_triangles(framebuffer, mesh, group.triangles.clone(), &shader, model, view_proj, uniforms);
+    }
+}
+
+fn render_triangles(
+    framebuffer: &mut Framebuffer,
+    mesh: &ObjModel,
+    triangles: std::ops::Range<usize>,
+    shader: &dyn PlanetShader,
+    model: &Matrix4<f32>,
+    view_proj: &Matrix4<f32>,
+    uniforms: &ShaderUniforms,
+) {
+    for &(i0, i1, i2) in &mesh.indices[triangles] {
+        let v0 = &mesh.vertices[i0];
+        let v1 = &mesh.vertices[i1];
+        let v2 = &mesh.vertices[i2];
         
-        let n0 = &normals[i0];
-        let n1 = &normals[i1];
-        let n2 = &normals[i2];
+        let n0 = &mesh.normals[i0];
+        let n1 = &mesh.normals[i1];
+        let n2 = &mesh.normals[i2];
         
-        let uv0 = uvs[i0];
-        let uv1 = uvs[i1];
-        let uv2 = uvs[i2];
+        let uv0 = mesh.uvs[i0];
+        let uv1 = mesh.uvs[i1];
+        let uv2 = mesh.uvs[i2];
 
-        let (pos0, norm0) = planet.shader.vertex_shader(*v0, *n0, uv0, uniforms);
-        let (pos1, norm1) = planet.shader.vertex_shader(*v1, *n1, uv1, uniforms);
-        let (pos2, norm2) = planet.shader.vertex_shader(*v2, *n2, uv2, uniforms);
+        let (pos0, norm0) = shader.vertex_shader(*v0, *n0, uv0, uniforms);
+        let (pos1, norm1) = shader.vertex_shader(*v1, *n1, uv1, uniforms);
+        let (pos2, norm2) = shader.vertex_shader(*v2, *n2, uv2, uniforms);
 
-        let world_v0 = transform_vertex(&pos0, &model);
-        let world_v1 = transform_vertex(&pos1, &model);
-        let world_v2 = transform_vertex(&pos2, &model);
+        let world_v0 = transform_vertex(&pos0, model);
+        let world_v1 = transform_vertex(&pos1, model);
+        let world_v2 = transform_vertex(&pos2, model);
 
         if let (Some(p0), Some(p1), Some(p2)) = (
-            project_vertex(&world_v0, &mvp),
-            project_vertex(&world_v1, &mvp),
-            project_vertex(&world_v2, &mvp),
+            project_vertex(&world_v0, view_proj),
+            project_vertex(&world_v1, view_proj),
+            project_vertex(&world_v2, view_proj),
         ) {
-            let c0 = planet.shader.fragment_shader(pos0, norm0, uv0, uniforms);
-            let c1 = planet.shader.fragment_shader(pos1, norm1, uv1, uniforms);
-            let c2 = planet.shader.fragment_shader(pos2, norm2, uv2, uniforms);
+            let c0 = shader.fragment_shader(pos0, norm0, uv0, uniforms);
+            let c1 = shader.fragment_shader(pos1, norm1, uv1, uniforms);
+            let c2 = shader.fragment_shader(pos2, norm2, uv2, uniforms);
 
-            draw_triangle(framebuffer, p0, p1, p2, c0, c1, c2);
+            match shader.texture() {
+                Some(texture) => draw_textured_triangle(framebuffer, p0, p1, p2, c0, c1, c2, [uv0, uv1, uv2], texture),
+                None => draw_triangle(framebuffer, p0, p1, p2, c0, c1, c2),
+            }
         }
     }
 }
+
+pub fn render_planet(
+    framebuffer: &mut Framebuffer,
+    planet: &Planet,
+    mesh: &ObjModel,
+    view_proj: &Matrix4<f32>,
+    uniforms: &ShaderUniforms,
+) {
+    let uniforms = ShaderUniforms {
+        model_position: Vector3::new(planet.position.x, planet.position.y, planet.position.z),
+        ..*uniforms
+    };
+    render_mesh(framebuffer, mesh, planet.shader.as_ref(), &planet.get_model_matrix(), view_proj, &uniforms);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BOX: (f32, f32, f32, f32) = (10.0, 20.0, 110.0, 70.0);
+
+    #[test]
+    fn segments_inside_are_kept_whole() {
+        assert_eq!(clip_segment_2d((20.0, 30.0), (100.0, 60.0), BOX), Some(((20.0, 30.0), (100.0, 60.0))));
+    }
+
+    #[test]
+    fn segments_are_cut_at_the_edges() {
+        assert_eq!(clip_segment_2d((0.0, 40.0), (200.0, 40.0), BOX), Some(((10.0, 40.0), (110.0, 40.0))));
+        assert_eq!(clip_segment_2d((60.0, 0.0), (60.0, 45.0), BOX), Some(((60.0, 20.0), (60.0, 45.0))));
+        // Diagonal que entra por la izquierda y sale por abajo
+        assert_eq!(clip_segment_2d((0.0, 40.0), (60.0, 100.0), BOX), Some(((10.0, 50.0), (30.0, 70.0))));
+    }
+
+    #[test]
+    fn segments_outside_are_dropped() {
+        assert_eq!(clip_segment_2d((0.0, 0.0), (200.0, 10.0), BOX), None);
+        assert_eq!(clip_segment_2d((120.0, 30.0), (130.0, 60.0), BOX), None);
+        // Cruza las prolongaciones de dos bordes sin pasar por el rectángulo
+        assert_eq!(clip_segment_2d((0.0, 30.0), (20.0, 0.0), BOX), None);
+    }
+}
@@ -0,0 +1,351 @@
+use nalgebra::{Matrix4, Vector3 as Vec3, Vector4, UnitQuaternion};
+use crate::vector::Vector3;
+use crate::shaders::{ShaderColor, ShaderUniforms};
+use crate::planets::*;
+use crate::obj_loader::ObjModel;
+use crate::lod::LodChain;
+use crate::framebuffer::Framebuffer;
+use crate::skybox::render_skybox;
+use crate::camera::Camera;
+use crate::planet::{Planet, BoundingSphere};
+use crate::render::{render_planet, render_mesh, render_materials, project_vertex};
+use crate::overlay::{OverlayLayers, render_overlays, render_selection};
+use crate::picking::{self, Ray};
+use crate::labels::render_labels;
+use crate::ship::{Ship, ShipControls};
+use crate::nbody::{NBodySystem, Integrator, Scenario};
+use crate::asteroids::AsteroidBelt;
+use crate::orbit::KeplerOrbit;
+use crate::comets::{CometTail, render_tails};
+use crate::particles::{self, Anchor, AnchorFrame, ParticleSystem};
+use crate::render::HEIGHT;
+
+/// Cuerpos del sistema solar, la nave del jugador y las mallas con que se dibujan
+pub struct Scene {
+    pub bodies: Vec<Planet>,
+    /// Esfera de los cuerpos en varios niveles de detalle
+    pub spheres: LodChain,
+    pub mesh_radius: f32,
+    pub ship: Ship,
+    pub ship_model: ObjModel,
+    pub asteroids: AsteroidBelt,
+    /// Protuberancias, erupciones y estela del motor
+    pub particles: ParticleSystem,
+    /// Cuerpo seleccionado con el ratón o con Tab
+    pub selected: Option<usize>,
+    /// Gravedad mutua entre los cuerpos; con `None` siguen sus órbitas fijas
+    pub nbody: Option<NBodySystem>,
+    // Cuerpos con órbita fija; los que vienen detrás solo existen en el modo
+    // de N cuerpos
+    kinematic_count: usize,
+}
+
+impl Scene {
+    pub fn solar_system(spheres: LodChain, ship_model: ObjModel, asteroid_count: usize) -> Self {
+        // El sol ocupa el índice 0; el resto son los planetas en órbita.
+        // El dibujo original aplicaba la matriz de modelo dos veces, así que
+        // un cuerpo de tamaño s en el radio r se veía de tamaño s² en el radio
+        // r·(s + 1). Con una sola transformación esos son los valores que se
+        // guardan: el sol pasó de 2.3 a 5.29 y el planeta rocoso de 1.2 en
+        // 2.7 a 1.44 en 5.94, y el sistema se ve igual que antes.
+        let mut bodies = vec![
+            Planet::new(
+                "Sol",
+                Box::new(SunShader),
+                0.0,
+                5.29,
+                0.1,
+                0.0,
+                0.0,
+            ),
+            Planet::new(
+                "Rocoso",
+                Box::new(RockyPlanetShader),
+                5.94,  // Radio orbital
+                1.44,  // Tamaño del planeta
+                0.2,
+                0.3,   // Velocidad orbital
+                0.0,   // Ángulo inicial
+            ),
+            Planet::new(
+                "Gigante gaseoso",
+                Box::new(GasPlanetShader),
+                9.01,
+                0.49,
+                0.15,
+                0.2,
+                std::f32::consts::PI / 2.5,
+            ),
+            Planet::new(
+                "Cristal",
+                Box::new(CrystalPlanetShader),
+                12.03,
+                0.72,
+                0.2,
+                0.15,
+                std::f32::consts::PI,
+            ),
+            Planet::new(
+                "Nebulosa",
+                Box::new(NebulaPlanetShader),
+                15.04,
+                0.36,
+                0.25,
+                0.12,
+                4.0 * std::f32::consts::PI / 3.0,
+            ),
+            Planet::new(
+                "Metálico",
+                Box::new(MetallicPlanetShader),
+                18.06,
+                1.21,
+                0.12,
+                0.1,
+                3.0 * std::f32::consts::PI / 2.0,
+            ),
+        ];
+        // Del sol conviene mantenerse más lejos que de los planetas
+        bodies[0].min_altitude = 1.5;
+
+        let mesh_radius = spheres.finest().bounding_radius();
+        // Cometas en órbitas muy excéntricas: uno llega al perihelio poco
+        // después de arrancar y el otro acaba de pasarlo
+        let sun_mu = bodies[0].gravitational_parameter(mesh_radius);
+        let comets = [
+            ("Cometa Ícaro", KeplerOrbit::new(4.5, 0.7, 0.6, 0.25, -0.35, sun_mu), 0.3, CometTail::default()),
+            (
+                "Cometa Vela",
+                KeplerOrbit::new(6.5, 0.6, 3.4, -0.15, 0.5, sun_mu),
+                0.25,
+                CometTail {
+                    length: 10.0,
+                    ion_color: ShaderColor::new(0.45, 0.75, 1.0, 1.0),
+                    ..CometTail::default()
+                },
+            ),
+        ];
+        for (name, orbit, scale, tail) in comets {
+            let mut comet = Planet::on_kepler_orbit(name, Box::new(CometShader), orbit, scale);
+            comet.tail = Some(tail);
+            bodies.push(comet);
+        }
+        let kinematic_count = bodies.len();
+        // La nave arranca fuera de la última órbita, mirando al sol
+        let ship = Ship::new(Vec3::new(0.0, 2.0, 24.0), Vec3::zeros());
+
+        let mut particles = ParticleSystem::default();
+        particles.emitters.extend(particles::solar_prominences(0, bodies[0].scale * mesh_radius));
+        // El planeta de lava escupe magma a ráfagas
+        if let Some(index) = bodies.iter().position(|b| b.name == "Nebulosa") {
+            particles.emitters.push(particles::lava_eruptions(index, bodies[index].scale * mesh_radius));
+        }
+        particles.emitters.push(particles::engine_exhaust(ship.nozzle_offset()));
+        Scene {
+            bodies,
+            spheres,
+            mesh_radius,
+            ship,
+            ship_model,
+            // Cinturón entre las órbitas del gigante gaseoso y el cristal
+            asteroids: AsteroidBelt::new(asteroid_count, 10.0, 11.2),
+            particles,
+            selected: None,
+            nbody: None,
+            kinematic_count,
+        }
+    }
+
+    /// Radio de la esfera que envuelve a la nave en coordenadas de mundo
+    pub fn ship_radius(&self) -> f32 {
+        self.ship_model.bounding_radius() * self.ship.scale
+    }
+
+    /// Avanza la física de la nave con los cuerpos en su posición actual
+    pub fn step_ship(&mut self, dt: f32, controls: &ShipControls) {
+        let ship_radius = self.ship_radius();
+        self.ship.step(dt, controls, &self.bodies, self.mesh_radius, ship_radius);
+    }
+
+    /// Avanza las partículas con los cuerpos y la nave en su posición actual
+    pub fn step_particles(&mut self, dt: f32) {
+        let throttle = self.ship.throttle();
+        for emitter in self.particles.emitters.iter_mut().filter(|e| e.anchor == Anchor::Ship) {
+            emitter.strength = throttle;
+        }
+        let (bodies, ship) = (&self.bodies, &self.ship);
+        self.particles.step(dt, |anchor| anchor_frame(bodies, ship, anchor, false));
+    }
+
+    /// Mueve todos los cuerpos al instante `time` de la simulación
+    pub fn set_time(&mut self, time: f32) {
+        // Las rocas no pesan: siguen su órbita también con gravedad mutua
+        self.asteroids.set_time(time);
+        match self.nbody.as_mut() {
+            Some(system) => system.apply(time, &mut self.bodies),
+            None => {
+                for planet in &mut self.bodies {
+                    planet.set_time(time);
+                }
+            }
+        }
+    }
+
+    /// Pasa al modo de N cuerpos desde las posiciones actuales, que deben
+    /// corresponder al instante `time`
+    pub fn enable_nbody(&mut self, scenario: Scenario, integrator: Integrator, time: f32) {
+        self.disable_nbody();
+        if scenario == Scenario::Flyby {
+            let mut intruder = Planet::new("Errante", Box::new(GasPlanetShader), 0.0, 1.8, 0.2, 0.0, 0.0);
+            intruder.set_time(time);
+            self.bodies.push(intruder);
+        }
+        self.nbody = Some(NBodySystem::new(&self.bodies, self.mesh_radius, scenario, integrator, time));
+    }
+
+    /// Vuelve a las órbitas fijas y quita los cuerpos propios del escenario
+    pub fn disable_nbody(&mut self) {
+        self.nbody = None;
+        self.bodies.truncate(self.kinematic_count);
+        if self.selected.is_some_and(|i| i >= self.bodies.len()) {
+            self.selected = None;
+        }
+    }
+
+    /// Esferas de colisión de todos los cuerpos, que la cámara no atraviesa
+    pub fn obstacles(&self) -> Vec<BoundingSphere> {
+        self.bodies
+            .iter()
+            .map(|body| body.collision_sphere(self.mesh_radius))
+            .collect()
+    }
+
+    /// Selecciona el siguiente cuerpo (o el anterior si `backwards`)
+    pub fn cycle_selection(&mut self, backwards: bool) {
+        let count = self.bodies.len();
+        self.selected = Some(match (self.selected, backwards) {
+            (None, false) => 0,
+            (None, true) => count - 1,
+            (Some(i), false) => (i + 1) % count,
+            (Some(i), true) => (i + count - 1) % count,
+        });
+    }
+
+    /// Selecciona el cuerpo que atraviesa el rayo; si no hay ninguno, deselecciona
+    pub fn pick(&mut self, ray: &Ray) -> Option<usize> {
+        let spheres: Vec<BoundingSphere> = self
+            .bodies
+            .iter()
+            .map(|body| body.bounding_sphere(self.mesh_radius))
+            .collect();
+        self.selected = picking::pick(ray, &spheres);
+        self.selected
+    }
+
+    /// Cuerpo que sigue la cámara: el seleccionado, o el sol si no hay selección
+    pub fn focus_body(&self) -> &Planet {
+        &self.bodies[self.selected.unwrap_or(0)]
+    }
+
+    /// Elige la malla de cada cuerpo según el radio que ocupa en pantalla.
+    /// Se llama antes de `render`; los cuerpos detrás de la cámara conservan
+    /// su nivel.
+    pub fn update_lod(&mut self, camera: &Camera, projection: &Matrix4<f32>) {
+        let view_proj = projection * camera.get_view_matrix();
+        let focal_length = projection[(1, 1)] * HEIGHT as f32 / 2.0;
+        for body in &mut self.bodies {
+            let p = body.position;
+            let clip = view_proj * Vector4::new(p.x, p.y, p.z, 1.0);
+            if clip.w <= 0.0 {
+                continue;
+            }
+            let screen_radius = body.bounding_radius(self.mesh_radius) * focal_length / clip.w;
+            body.lod_level = self.spheres.select(body.lod_level, screen_radius);
+        }
+    }
+
+    /// Dibuja un cuadro completo: fondo, cuerpos, nave y capas de ayuda
+    pub fn render(
+        &self,
+        framebuffer: &mut Framebuffer,
+        camera: &Camera,
+        projection: &Matrix4<f32>,
+        overlays: &OverlayLayers,
+        time: f32,
+    ) {
+        framebuffer.clear();
+
+        let view = camera.get_view_matrix();
+        let view_proj = projection * view;
+        let sky_view_proj = projection * camera.get_rotation_view_matrix();
+
+        let sun = self.bodies[0].position;
+        let uniforms = ShaderUniforms {
+            time,
+            light_direction: Vector3::new(0.0, 0.0, 1.0).normalize(),
+            camera_position: Vector3::new(camera.position.x, camera.position.y, camera.position.z),
+            sun_position: Vector3::new(sun.x, sun.y, sun.z),
+            model_position: Vector3::new(0.0, 0.0, 0.0),
+        };
+
+        render_skybox(framebuffer, &sky_view_proj, time, project_vertex);
+        for body in &self.bodies {
+            render_planet(framebuffer, body, self.spheres.mesh(body.lod_level), &view_proj, &uniforms);
+        }
+        let light = uniforms.light_direction;
+        let focal_length = projection[(1, 1)] * HEIGHT as f32 / 2.0;
+        self.asteroids.render(framebuffer, &view_proj, focal_length, Vec3::new(light.x, light.y, light.z));
+        // Con biblioteca MTL la nave usa sus materiales; sin ella, el shader propio
+        let ship_position = self.ship.render_position;
+        let ship_uniforms = ShaderUniforms {
+            model_position: Vector3::new(ship_position.x, ship_position.y, ship_position.z),
+            ..uniforms
+        };
+        let ship_matrix = self.ship.get_model_matrix();
+        if self.ship_model.materials.is_empty() {
+            render_mesh(framebuffer, &self.ship_model, &self.ship.shader(), &ship_matrix, &view_proj, &ship_uniforms);
+        } else {
+            render_materials(
+                framebuffer,
+                &self.ship_model,
+                |material| self.ship.material_shader(material),
+                &ship_matrix,
+                &view_proj,
+                &ship_uniforms,
+            );
+        }
+        render_tails(framebuffer, &self.bodies, sun, self.mesh_radius, &view_proj, focal_length, time);
+        self.particles.render(framebuffer, &view_proj, focal_length, |anchor| {
+            anchor_frame(&self.bodies, &self.ship, anchor, true)
+        });
+        // Las órbitas fijas no describen el movimiento con gravedad mutua
+        let overlays = OverlayLayers { orbits: overlays.orbits && self.nbody.is_none(), ..*overlays };
+        render_overlays(framebuffer, &overlays, &self.bodies, self.mesh_radius, &view_proj);
+
+        let forward = (camera.target - camera.position).normalize();
+        let right = forward.cross(&camera.up).normalize();
+        let up = right.cross(&forward);
+        if let Some(body) = self.selected.and_then(|i| self.bodies.get(i)) {
+            render_selection(framebuffer, body, self.mesh_radius, right, up, &view_proj);
+        }
+        if overlays.labels {
+            render_labels(framebuffer, &self.bodies, self.mesh_radius, camera.position, right, &view_proj);
+        }
+    }
+}
+
+/// Pose de un ancla de partículas. Con `interpolated` la nave se toma en la
+/// pose con que se dibuja y no en la del último paso de física.
+fn anchor_frame(bodies: &[Planet], ship: &Ship, anchor: Anchor, interpolated: bool) -> Option<AnchorFrame> {
+    match anchor {
+        Anchor::Body(index) => bodies.get(index).map(|body| AnchorFrame {
+            position: body.position,
+            orientation: UnitQuaternion::identity(),
+            velocity: body.velocity,
+        }),
+        Anchor::Ship => Some(AnchorFrame {
+            position: if interpolated { ship.render_position } else { ship.position },
+            orientation: if interpolated { ship.render_orientation } else { ship.orientation },
+            velocity: ship.velocity,
+        }),
+    }
+}
@@ -1,4 +1,6 @@
 use crate::vector::Vector3;
+use crate::framebuffer::rgb_to_u32;
+use crate::material::Texture;
 
 #[derive(Debug, Clone, Copy)]
 pub struct ShaderColor {
@@ -21,17 +23,48 @@ impl ShaderColor {
             a: 1.0,
         }
     }
+
+    pub fn to_u32(self) -> u32 {
+        rgb_to_u32(
+            (self.r.clamp(0.0, 1.0) * 255.0) as u8,
+            (self.g.clamp(0.0, 1.0) * 255.0) as u8,
+            (self.b.clamp(0.0, 1.0) * 255.0) as u8,
+        )
+    }
 }
 
+#[derive(Clone, Copy)]
 pub struct ShaderUniforms {
     pub time: f32,
     pub light_direction: Vector3,
     pub camera_position: Vector3,
+    /// Centro del sol en coordenadas de mundo
+    pub sun_position: Vector3,
+    /// Centro en el mundo del cuerpo que se está dibujando, para saber desde
+    /// dónde le llega la luz del sol
+    pub model_position: Vector3,
 }
 
 pub trait PlanetShader {
     fn vertex_shader(&self, position: Vector3, normal: Vector3, uv: (f32, f32), uniforms: &ShaderUniforms) -> (Vector3, Vector3);
     fn fragment_shader(&self, position: Vector3, normal: Vector3, uv: (f32, f32), uniforms: &ShaderUniforms) -> ShaderColor;
+
+    /// Cuánto puede mover el vertex shader un vértice hacia afuera, en unidades
+    /// del modelo. Se suma al radio de la malla en las esferas envolventes.
+    fn max_displacement(&self) -> f32 {
+        0.0
+    }
+
+    /// Color plano que representa al cuerpo en vistas esquemáticas como el minimapa
+    fn map_color(&self) -> ShaderColor {
+        ShaderColor::from_rgb(200, 200, 200)
+    }
+
+    /// Textura que el rasterizador multiplica por el color en cada píxel; el
+    /// fragment shader solo se evalúa en los vértices
+    fn texture(&self) -> Option<&Texture> {
+        None
+    }
 }
 
 
@@ -78,7 +111,7 @@ pub fn perlin_noise(x: f32, y: f32, z: f32) -> f32 {
 
 // Funciones de ruido mejoradas para efectos procedurales
 pub fn simple_noise(x: f32, y: f32) -> f32 {
-    let seed = ((x * 12.9898 + y * 78.233) * 43758.5453).sin().abs();
+    let seed = ((x * 12.9898 + y * 78.233) * 43758.547).sin().abs();
     (seed * 1000.0).fract()
 }
 
@@ -0,0 +1,261 @@
+use nalgebra::{Matrix4, Vector3 as Vec3, UnitQuaternion};
+use crate::vector::Vector3;
+use crate::shaders::{ShaderColor, ShaderUniforms, PlanetShader, mix_color};
+use crate::planet::Planet;
+use crate::material::{Material, MaterialShader};
+use crate::obj_loader::{ObjError, ObjMode, ObjModel};
+
+// Aceleraciones en unidades por segundo al cuadrado: el motor principal
+// empuja hacia adelante y los propulsores de maniobra (RCS) en el resto de ejes
+const MAIN_ENGINE_ACCELERATION: f32 = 12.0;
+const RCS_ACCELERATION: f32 = 4.0;
+// Aceleración angular de los propulsores de maniobra (rad/s²)
+const RCS_ANGULAR_ACCELERATION: f32 = 2.5;
+// Con la asistencia de estabilidad, ritmo al que se frena el giro sin mandos
+const STABILITY_DAMPING: f32 = 3.0;
+// Paso máximo de integración, para que las escalas de tiempo altas no
+// rompan la física
+const MAX_SUBSTEP: f32 = 1.0 / 60.0;
+
+const SHIP_SCALE: f32 = 0.6;
+// Copia de ship.obj y ship.mtl dentro del programa, para poder arrancar desde
+// cualquier directorio
+const BUILTIN_FILES: [(&str, &str); 2] = [
+    ("ship.obj", include_str!("../ship.obj")),
+    ("ship.mtl", include_str!("../ship.mtl")),
+];
+// Profundidad de la tobera en el modelo (ship.obj), en la cara trasera
+const NOZZLE_Z: f32 = 0.78;
+
+/// Modelo de la nave incluido en el programa, con sus materiales
+pub fn builtin_model() -> ObjModel {
+    let read = |filename: &str| {
+        BUILTIN_FILES
+            .iter()
+            .find(|(name, _)| *name == filename)
+            .map(|(_, text)| text.to_string())
+            .ok_or_else(|| ObjError::Io { file: filename.to_string(), error: std::io::ErrorKind::NotFound.into() })
+    };
+    let (model, _) = ObjModel::load_with("ship.obj", ObjMode::Strict, &read).expect("ship.obj incluido no válido");
+    model
+}
+
+/// Mandos de la nave en un paso, con componentes entre -1 y 1
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ShipControls {
+    /// Empuje en ejes locales (x = derecha, y = arriba, z = adelante)
+    pub thrust: Vec3<f32>,
+    /// Giro deseado: x = cabeceo (arriba), y = guiñada (izquierda), z = alabeo (izquierda)
+    pub rotation: Vec3<f32>,
+}
+
+/// Nave del jugador con física newtoniana: conserva la velocidad y el giro
+/// mientras no se use un propulsor que los cambie
+pub struct Ship {
+    pub position: Vec3<f32>,
+    pub velocity: Vec3<f32>,
+    pub orientation: UnitQuaternion<f32>,
+    /// Velocidad angular en ejes locales, en rad/s
+    angular_velocity: Vec3<f32>,
+    previous_position: Vec3<f32>,
+    previous_orientation: UnitQuaternion<f32>,
+    /// Pose interpolada entre los dos últimos pasos, con la que se dibuja
+    pub render_position: Vec3<f32>,
+    pub render_orientation: UnitQuaternion<f32>,
+    // Potencia del motor principal en el último paso, para el brillo de la tobera
+    throttle: f32,
+    pub scale: f32,
+    /// Los cuerpos atraen a la nave
+    pub gravity: bool,
+    /// Frena el giro cuando no se tocan los mandos de rotación
+    pub stability_assist: bool,
+}
+
+impl Ship {
+    /// Nave quieta en `position` con la nariz hacia `target`
+    pub fn new(position: Vec3<f32>, target: Vec3<f32>) -> Self {
+        let orientation = UnitQuaternion::face_towards(&(position - target), &Vec3::y());
+        Ship {
+            position,
+            velocity: Vec3::zeros(),
+            orientation,
+            angular_velocity: Vec3::zeros(),
+            previous_position: position,
+            previous_orientation: orientation,
+            render_position: position,
+            render_orientation: orientation,
+            throttle: 0.0,
+            scale: SHIP_SCALE,
+            gravity: false,
+            stability_assist: true,
+        }
+    }
+
+    pub fn speed(&self) -> f32 {
+        self.velocity.norm()
+    }
+
+    /// Potencia del motor principal en el último paso (0 a 1)
+    pub fn throttle(&self) -> f32 {
+        self.throttle
+    }
+
+    /// Centro de la tobera respecto de la nave, en unidades de mundo
+    pub fn nozzle_offset(&self) -> Vec3<f32> {
+        Vec3::new(0.0, 0.0, NOZZLE_Z * self.scale)
+    }
+
+    /// Avanza la física `dt` segundos de simulación. Con el tiempo detenido o
+    /// hacia atrás la nave se queda quieta: su movimiento no es reversible.
+    pub fn step(&mut self, dt: f32, controls: &ShipControls, bodies: &[Planet], mesh_radius: f32, ship_radius: f32) {
+        self.previous_position = self.position;
+        self.previous_orientation = self.orientation;
+        self.throttle = controls.thrust.z.max(0.0);
+        if dt <= 0.0 {
+            return;
+        }
+
+        let substeps = (dt / MAX_SUBSTEP).ceil().max(1.0) as usize;
+        let h = dt / substeps as f32;
+        for _ in 0..substeps {
+            self.integrate(h, controls, bodies, mesh_radius);
+            self.resolve_collisions(bodies, mesh_radius, ship_radius);
+        }
+    }
+
+    fn integrate(&mut self, h: f32, controls: &ShipControls, bodies: &[Planet], mesh_radius: f32) {
+        self.angular_velocity += controls.rotation * RCS_ANGULAR_ACCELERATION * h;
+        if self.stability_assist && controls.rotation == Vec3::zeros() {
+            self.angular_velocity *= (-STABILITY_DAMPING * h).exp();
+        }
+        self.orientation *= UnitQuaternion::from_scaled_axis(self.angular_velocity * h);
+        self.orientation.renormalize_fast();
+
+        // La nave mira hacia -Z en su espacio local; solo el motor principal
+        // empuja con toda la potencia
+        let forward = if controls.thrust.z > 0.0 { MAIN_ENGINE_ACCELERATION } else { RCS_ACCELERATION };
+        let local = Vec3::new(
+            controls.thrust.x * RCS_ACCELERATION,
+            controls.thrust.y * RCS_ACCELERATION,
+            -controls.thrust.z * forward,
+        );
+        let mut acceleration = self.orientation * local;
+        if self.gravity {
+            acceleration += gravity_at(self.position, bodies, mesh_radius);
+        }
+
+        // Euler semiimplícito: estable para órbitas con pasos pequeños
+        self.velocity += acceleration * h;
+        self.position += self.velocity * h;
+    }
+
+    /// Saca la nave de los cuerpos y anula la velocidad hacia su superficie
+    fn resolve_collisions(&mut self, bodies: &[Planet], mesh_radius: f32, ship_radius: f32) {
+        for body in bodies {
+            let sphere = body.bounding_sphere(mesh_radius);
+            let radius = sphere.radius + ship_radius;
+            let offset = self.position - sphere.center;
+            let distance = offset.norm();
+            if distance >= radius {
+                continue;
+            }
+            let normal = if distance > 1e-5 { offset / distance } else { Vec3::y() };
+            self.position = sphere.center + normal * radius;
+            let into_surface = self.velocity.dot(&normal);
+            if into_surface < 0.0 {
+                self.velocity -= normal * into_surface;
+            }
+        }
+    }
+
+    /// Coloca la pose de dibujo entre el paso anterior y el actual
+    /// (`alpha` = 0 en el anterior, 1 en el actual)
+    pub fn interpolate(&mut self, alpha: f32) {
+        let alpha = alpha.clamp(0.0, 1.0);
+        self.render_position = self.previous_position.lerp(&self.position, alpha);
+        self.render_orientation = self.previous_orientation.slerp(&self.orientation, alpha);
+    }
+
+    pub fn get_model_matrix(&self) -> Matrix4<f32> {
+        Matrix4::new_translation(&self.render_position)
+            * self.render_orientation.to_homogeneous()
+            * Matrix4::new_scaling(self.scale)
+    }
+
+    /// Shader con el estado actual de la nave
+    pub fn shader(&self) -> ShipShader {
+        ShipShader {
+            throttle: self.throttle,
+            orientation: self.render_orientation,
+        }
+    }
+
+    /// Shader para un material del modelo; la luz propia de la tobera sube
+    /// con el acelerador
+    pub fn material_shader<'a>(&self, material: &'a Material) -> MaterialShader<'a> {
+        MaterialShader {
+            material,
+            orientation: self.render_orientation,
+            emission_scale: 0.25 + 0.75 * self.throttle,
+        }
+    }
+}
+
+/// Aceleración gravitatoria que producen los cuerpos en `position`. Cada
+/// cuerpo pesa en proporción a su volumen; dentro de él la atracción no crece.
+fn gravity_at(position: Vec3<f32>, bodies: &[Planet], mesh_radius: f32) -> Vec3<f32> {
+    bodies.iter().fold(Vec3::zeros(), |total, body| {
+        let radius = body.scale * mesh_radius;
+        let offset = body.position - position;
+        let distance = offset.norm();
+        if distance < 1e-5 {
+            return total;
+        }
+        let falloff = distance.max(radius);
+        total + offset / distance * (body.gravitational_parameter(mesh_radius) / (falloff * falloff))
+    })
+}
+
+/// Casco metálico con cabina, franjas en las alas y tobera que brilla al
+/// acelerar. Trabaja en el espacio del modelo, así que lleva la orientación
+/// de la nave para iluminarla desde la dirección correcta.
+pub struct ShipShader {
+    throttle: f32,
+    orientation: UnitQuaternion<f32>,
+}
+
+impl PlanetShader for ShipShader {
+    fn map_color(&self) -> ShaderColor {
+        ShaderColor::from_rgb(120, 230, 255)
+    }
+
+    fn vertex_shader(&self, position: Vector3, normal: Vector3, _uv: (f32, f32), _uniforms: &ShaderUniforms) -> (Vector3, Vector3) {
+        (position, normal)
+    }
+
+    fn fragment_shader(&self, position: Vector3, normal: Vector3, _uv: (f32, f32), uniforms: &ShaderUniforms) -> ShaderColor {
+        // La tobera emite luz propia
+        if normal.z > 0.9 {
+            let cold = ShaderColor::from_rgb(70, 45, 35);
+            let hot = ShaderColor::from_rgb(255, 180, 70);
+            return mix_color(cold, hot, 0.25 + 0.75 * self.throttle);
+        }
+
+        let base = if normal.y > 0.3 && position.z < 0.15 {
+            ShaderColor::from_rgb(70, 150, 230)
+        } else if position.x.abs() > 0.6 || position.y > 0.4 {
+            ShaderColor::from_rgb(200, 60, 50)
+        } else if normal.y < -0.3 {
+            ShaderColor::from_rgb(80, 88, 100)
+        } else {
+            ShaderColor::from_rgb(175, 182, 195)
+        };
+
+        // Luz desde el sol, llevada a la orientación de la nave
+        let light = (uniforms.sun_position - uniforms.model_position).normalize();
+        let local_light = self.orientation.inverse() * Vec3::new(light.x, light.y, light.z);
+        let diffuse = (normal.x * local_light.x + normal.y * local_light.y + normal.z * local_light.z).max(0.0);
+        let intensity = 0.45 + 0.55 * diffuse;
+        ShaderColor::new(base.r * intensity, base.g * intensity, base.b * intensity, 1.0)
+    }
+}
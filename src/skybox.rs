@@ -1,5 +1,5 @@
 use nalgebra::Vector3 as Vec3;
-use std::f32::consts::PI;
+use std::f32::consts::{E, PI};
 use crate::framebuffer::{Framebuffer, rgb_to_u32};
 
 const WIDTH: usize = 800;
@@ -12,8 +12,8 @@ pub fn generate_star_positions(count: usize, seed: f32) -> Vec<Vec3<f32>> {
     for i in 0..count {
         let t = i as f32 * seed;
         
-        let hash_x = ((t * 12.9898 + 78.233).sin() * 43758.5453).fract();
-        let hash_y = ((t * 93.9898 + 67.345).sin() * 28371.4573).fract();
+        let hash_x = ((t * 12.9898 + 78.233).sin() * 43758.547).fract();
+        let hash_y = ((t * 93.9898 + 67.345).sin() * 28371.457).fract();
         
         // Convertir a coordenadas esféricas para distribución uniforme
         let theta = hash_x * 2.0 * PI; // Ángulo horizontal (0 a 2π)
@@ -37,7 +37,7 @@ pub fn render_skybox(
     time: f32,
     project_fn: impl Fn(&Vec3<f32>, &nalgebra::Matrix4<f32>) -> Option<(i32, i32, f32)>,
 ) {
-    let stars = generate_star_positions(800, 2.71828);
+    let stars = generate_star_positions(800, E);
     
     for (i, star_pos) in stars.iter().enumerate() {
         if let Some((sx, sy, sz)) = project_fn(star_pos, view_proj) {
@@ -0,0 +1,329 @@
+use nalgebra::Vector3 as Vec3;
+use std::collections::HashMap;
+use std::f32::consts::PI;
+use crate::obj_loader::{MaterialGroup, ObjModel, spherical_uv};
+use crate::vector::Vector3;
+
+/// Radio de las esferas generadas: el de sphere.obj, para que los tamaños, la
+/// gravedad y el ruido de los shaders no cambien sin el archivo
+pub const SPHERE_RADIUS: f32 = 0.524;
+/// Nivel de detalle máximo; en el icosaedro son 20·4⁷ = 327 680 triángulos
+pub const MAX_DETAIL: usize = 7;
+// Lo que se aleja de |y| = 1 un vértice que se considera polo
+const POLE_EPSILON: f32 = 1e-5;
+
+/// Forma de partida de la malla de los cuerpos
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SphereKind {
+    /// Meridianos y paralelos; los triángulos se juntan en los polos
+    Uv,
+    /// Icosaedro subdividido; triángulos casi iguales en toda la esfera
+    Ico,
+    /// Cubo subdividido e inflado; cuadrículas regulares en cada cara
+    Cube,
+}
+
+impl SphereKind {
+    pub const ALL: [SphereKind; 3] = [SphereKind::Uv, SphereKind::Ico, SphereKind::Cube];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            SphereKind::Uv => "uv",
+            SphereKind::Ico => "ico",
+            SphereKind::Cube => "cube",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        SphereKind::ALL.iter().copied().find(|k| k.name() == name)
+    }
+}
+
+/// Esfera de radio `SPHERE_RADIUS` con normales exactas y UV de latitud y
+/// longitud. El nivel 0 es un octaedro, un icosaedro o un cubo; cada nivel
+/// de `detail` multiplica sus triángulos por 4 (la UV dobla meridianos y
+/// paralelos y queda en 2·segmentos·(anillos − 1)).
+pub fn generate(kind: SphereKind, detail: usize) -> ObjModel {
+    let detail = detail.min(MAX_DETAIL);
+    match kind {
+        SphereKind::Uv => uv_sphere(4 << detail, 2 << detail),
+        SphereKind::Ico => {
+            let (directions, triangles) = icosphere(detail);
+            unwrap_seam(&directions, triangles)
+        }
+        SphereKind::Cube => {
+            let (directions, triangles) = cube_sphere(1 << detail);
+            unwrap_seam(&directions, triangles)
+        }
+    }
+}
+
+/// Icosaedro de radio 1 subdividido `subdivisions` veces, con los triángulos
+/// en sentido antihorario vistos desde fuera
+pub fn icosphere(subdivisions: usize) -> (Vec<Vec3<f32>>, Vec<[usize; 3]>) {
+    let t = (1.0 + 5.0_f32.sqrt()) / 2.0;
+    let mut vertices: Vec<Vec3<f32>> = [
+        (-1.0, t, 0.0), (1.0, t, 0.0), (-1.0, -t, 0.0), (1.0, -t, 0.0),
+        (0.0, -1.0, t), (0.0, 1.0, t), (0.0, -1.0, -t), (0.0, 1.0, -t),
+        (t, 0.0, -1.0), (t, 0.0, 1.0), (-t, 0.0, -1.0), (-t, 0.0, 1.0),
+    ]
+    .iter()
+    .map(|&(x, y, z)| Vec3::new(x, y, z).normalize())
+    .collect();
+    let mut triangles: Vec<[usize; 3]> = vec![
+        [0, 11, 5], [0, 5, 1], [0, 1, 7], [0, 7, 10], [0, 10, 11],
+        [1, 5, 9], [5, 11, 4], [11, 10, 2], [10, 7, 6], [7, 1, 8],
+        [3, 9, 4], [3, 4, 2], [3, 2, 6], [3, 6, 8], [3, 8, 9],
+        [4, 9, 5], [2, 4, 11], [6, 2, 10], [8, 6, 7], [9, 8, 1],
+    ];
+
+    for _ in 0..subdivisions {
+        let mut midpoints = HashMap::new();
+        let mut midpoint = |a: usize, b: usize, vertices: &mut Vec<Vec3<f32>>| -> usize {
+            *midpoints.entry((a.min(b), a.max(b))).or_insert_with(|| {
+                vertices.push(((vertices[a] + vertices[b]) * 0.5).normalize());
+                vertices.len() - 1
+            })
+        };
+        triangles = triangles
+            .iter()
+            .flat_map(|&[a, b, c]| {
+                let ab = midpoint(a, b, &mut vertices);
+                let bc = midpoint(b, c, &mut vertices);
+                let ca = midpoint(c, a, &mut vertices);
+                [[a, ab, ca], [b, bc, ab], [c, ca, bc], [ab, bc, ca]]
+            })
+            .collect();
+    }
+    (vertices, triangles)
+}
+
+/// Cubo con cada cara partida en `grid`×`grid` cuadrados y llevado a la
+/// esfera con la proyección que reparte el área mejor que normalizar
+fn cube_sphere(grid: usize) -> (Vec<Vec3<f32>>, Vec<[usize; 3]>) {
+    let faces = [
+        (Vec3::x(), Vec3::z(), Vec3::y()),
+        (-Vec3::x(), -Vec3::z(), Vec3::y()),
+        (Vec3::y(), Vec3::x(), Vec3::z()),
+        (-Vec3::y(), Vec3::x(), -Vec3::z()),
+        (Vec3::z(), -Vec3::x(), Vec3::y()),
+        (-Vec3::z(), Vec3::x(), Vec3::y()),
+    ];
+    let mut vertices = Vec::with_capacity(6 * (grid + 1) * (grid + 1));
+    let mut triangles = Vec::with_capacity(12 * grid * grid);
+    for (normal, right, up) in faces {
+        let base = vertices.len();
+        for j in 0..=grid {
+            for i in 0..=grid {
+                let s = 2.0 * i as f32 / grid as f32 - 1.0;
+                let t = 2.0 * j as f32 / grid as f32 - 1.0;
+                let p = normal + right * s + up * t;
+                let (x2, y2, z2) = (p.x * p.x, p.y * p.y, p.z * p.z);
+                vertices.push(Vec3::new(
+                    p.x * (1.0 - y2 / 2.0 - z2 / 2.0 + y2 * z2 / 3.0).sqrt(),
+                    p.y * (1.0 - z2 / 2.0 - x2 / 2.0 + z2 * x2 / 3.0).sqrt(),
+                    p.z * (1.0 - x2 / 2.0 - y2 / 2.0 + x2 * y2 / 3.0).sqrt(),
+                ));
+            }
+        }
+        let index = |i: usize, j: usize| base + j * (grid + 1) + i;
+        for j in 0..grid {
+            for i in 0..grid {
+                triangles.push([index(i, j), index(i + 1, j), index(i + 1, j + 1)]);
+                triangles.push([index(i, j), index(i + 1, j + 1), index(i, j + 1)]);
+            }
+        }
+    }
+    (vertices, triangles)
+}
+
+/// Esfera de meridianos y paralelos. La columna de la costura se repite con
+/// u = 0 y u = 1, y cada triángulo de los polos tiene su propio vértice polar
+/// con la u del centro de su gajo.
+fn uv_sphere(segments: usize, rings: usize) -> ObjModel {
+    let mut directions = Vec::new();
+    let mut uvs = Vec::new();
+    let mut push = |u: f32, v: f32| {
+        // Inversa de `spherical_uv`: v va de polo a polo y u da la vuelta
+        let (polar, azimuth) = (v * PI, (u - 0.5) * 2.0 * PI);
+        directions.push(Vec3::new(polar.sin() * azimuth.sin(), polar.cos(), polar.sin() * azimuth.cos()));
+        uvs.push((u, v));
+        directions.len() - 1
+    };
+
+    let north: Vec<usize> = (0..segments).map(|j| push((j as f32 + 0.5) / segments as f32, 0.0)).collect();
+    let mut rows = Vec::new();
+    for ring in 1..rings {
+        let v = ring as f32 / rings as f32;
+        rows.push((0..=segments).map(|j| push(j as f32 / segments as f32, v)).collect::<Vec<usize>>());
+    }
+    let south: Vec<usize> = (0..segments).map(|j| push((j as f32 + 0.5) / segments as f32, 1.0)).collect();
+
+    let mut triangles = Vec::new();
+    for j in 0..segments {
+        triangles.push([north[j], rows[0][j], rows[0][j + 1]]);
+        for pair in rows.windows(2) {
+            let (upper, lower) = (&pair[0], &pair[1]);
+            triangles.push([upper[j], lower[j], lower[j + 1]]);
+            triangles.push([upper[j], lower[j + 1], upper[j + 1]]);
+        }
+        let last = &rows[rows.len() - 1];
+        triangles.push([south[j], last[j + 1], last[j]]);
+    }
+    build(&directions, uvs, triangles)
+}
+
+/// Da UV a una esfera sin costura: los triángulos que cruzan el meridiano de
+/// u = 0 usan copias de sus vértices con u + 1, y los vértices de los polos
+/// se copian para cada triángulo con la u media de sus otras dos esquinas
+fn unwrap_seam(directions: &[Vec3<f32>], triangles: Vec<[usize; 3]>) -> ObjModel {
+    let mut directions = directions.to_vec();
+    let mut uvs: Vec<(f32, f32)> = directions
+        .iter()
+        .map(|d| spherical_uv(Vector3::new(d.x, d.y, d.z)))
+        .collect();
+    let mut copies: HashMap<(usize, u32), usize> = HashMap::new();
+
+    let triangles = triangles
+        .into_iter()
+        .map(|triangle| {
+            let is_pole = |i: usize| directions[i].y.abs() > 1.0 - POLE_EPSILON;
+            let mut u = triangle.map(|i| uvs[i].0);
+            let around: Vec<usize> = (0..3).filter(|&k| !is_pole(triangle[k])).collect();
+            let lowest = around.iter().map(|&k| u[k]).fold(f32::MAX, f32::min);
+            let highest = around.iter().map(|&k| u[k]).fold(f32::MIN, f32::max);
+            if highest - lowest > 0.5 {
+                for &k in &around {
+                    if u[k] < 0.5 {
+                        u[k] += 1.0;
+                    }
+                }
+            }
+            let middle = around.iter().map(|&k| u[k]).sum::<f32>() / around.len().max(1) as f32;
+            for k in 0..3 {
+                if is_pole(triangle[k]) {
+                    u[k] = middle;
+                }
+            }
+
+            let mut result = triangle;
+            for k in 0..3 {
+                let original = triangle[k];
+                if u[k] != uvs[original].0 {
+                    result[k] = *copies.entry((original, u[k].to_bits())).or_insert_with(|| {
+                        directions.push(directions[original]);
+                        uvs.push((u[k], uvs[original].1));
+                        directions.len() - 1
+                    });
+                }
+            }
+            result
+        })
+        .collect();
+    build(&directions, uvs, triangles)
+}
+
+/// Arma el modelo desde direcciones unitarias: la normal es la dirección y
+/// los triángulos se giran si hace falta para quedar antihorarios desde fuera
+fn build(directions: &[Vec3<f32>], uvs: Vec<(f32, f32)>, triangles: Vec<[usize; 3]>) -> ObjModel {
+    let indices: Vec<(usize, usize, usize)> = triangles
+        .into_iter()
+        .map(|[a, b, c]| {
+            let (pa, pb, pc) = (directions[a], directions[b], directions[c]);
+            if (pb - pa).cross(&(pc - pa)).dot(&(pa + pb + pc)) < 0.0 {
+                (a, c, b)
+            } else {
+                (a, b, c)
+            }
+        })
+        .collect();
+    ObjModel {
+        vertices: directions.iter().map(|d| Vector3::new(d.x, d.y, d.z) * SPHERE_RADIUS).collect(),
+        normals: directions.iter().map(|d| Vector3::new(d.x, d.y, d.z)).collect(),
+        uvs,
+        groups: vec![MaterialGroup { material: None, triangles: 0..indices.len() }],
+        indices,
+        materials: Vec::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DETAILS: [usize; 3] = [0, 1, 3];
+
+    fn expected_triangles(kind: SphereKind, detail: usize) -> usize {
+        match kind {
+            SphereKind::Ico => 20 * 4usize.pow(detail as u32),
+            SphereKind::Cube => {
+                let grid = 1 << detail;
+                12 * grid * grid
+            }
+            SphereKind::Uv => {
+                let (segments, rings) = (4 << detail, 2 << detail);
+                2 * segments * (rings - 1)
+            }
+        }
+    }
+
+    #[test]
+    fn triangle_counts_follow_the_detail_level() {
+        for kind in SphereKind::ALL {
+            for detail in DETAILS {
+                let model = generate(kind, detail);
+                assert_eq!(model.indices.len(), expected_triangles(kind, detail), "{} {}", kind.name(), detail);
+                assert_eq!(model.groups[0].triangles, 0..model.indices.len());
+            }
+        }
+        // Icosaedro y cubo multiplican los triángulos por 4 en cada nivel
+        for kind in [SphereKind::Ico, SphereKind::Cube] {
+            assert_eq!(expected_triangles(kind, 1), 4 * expected_triangles(kind, 0));
+        }
+    }
+
+    #[test]
+    fn normals_are_unit_and_point_outward() {
+        for kind in SphereKind::ALL {
+            for detail in DETAILS {
+                let model = generate(kind, detail);
+                assert_eq!(model.normals.len(), model.vertices.len());
+                for (vertex, normal) in model.vertices.iter().zip(&model.normals) {
+                    assert!((normal.length() - 1.0).abs() < 1e-5, "{} {}: {:?}", kind.name(), detail, normal);
+                    assert!((vertex.length() - SPHERE_RADIUS).abs() < 1e-5);
+                    // En una esfera centrada la normal es la dirección del vértice
+                    assert!(vertex.normalize().dot(normal) > 1.0 - 1e-5, "{} {}: {:?}", kind.name(), detail, vertex);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn triangles_wind_counter_clockwise_from_outside() {
+        for kind in SphereKind::ALL {
+            for detail in DETAILS {
+                let model = generate(kind, detail);
+                for &(a, b, c) in &model.indices {
+                    let (pa, pb, pc) = (model.vertices[a], model.vertices[b], model.vertices[c]);
+                    let face_normal = (pb - pa).cross(&(pc - pa));
+                    let centroid = pa + pb + pc;
+                    assert!(face_normal.dot(&centroid) > 0.0, "{} {}: {:?}", kind.name(), detail, (a, b, c));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn no_triangle_wraps_around_the_seam() {
+        for kind in SphereKind::ALL {
+            for detail in DETAILS {
+                let model = generate(kind, detail);
+                for &(a, b, c) in &model.indices {
+                    let u = [model.uvs[a].0, model.uvs[b].0, model.uvs[c].0];
+                    let span = u.iter().copied().fold(f32::MIN, f32::max) - u.iter().copied().fold(f32::MAX, f32::min);
+                    assert!(span <= 0.5, "{} {}: u = {:?}", kind.name(), detail, u);
+                }
+            }
+        }
+    }
+}
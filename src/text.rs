@@ -0,0 +1,266 @@
+use crate::framebuffer::{Framebuffer, blend};
+use crate::render::{WIDTH, HEIGHT};
+
+// Fuente de mapa de bits de 5x7 píxeles para ASCII 32..=126. Cada glifo son
+// siete filas de arriba abajo; el bit 4 de cada fila es la columna izquierda.
+pub const GLYPH_WIDTH: usize = 5;
+pub const GLYPH_HEIGHT: usize = 7;
+const GLYPH_ADVANCE: usize = GLYPH_WIDTH + 1;
+const LINE_SPACING: usize = GLYPH_HEIGHT + 3;
+
+const FONT: [[u8; GLYPH_HEIGHT]; 95] = [
+    [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00], // ' '
+    [0x04, 0x04, 0x04, 0x04, 0x04, 0x00, 0x04], // !
+    [0x0A, 0x0A, 0x0A, 0x00, 0x00, 0x00, 0x00], // "
+    [0x0A, 0x0A, 0x1F, 0x0A, 0x1F, 0x0A, 0x0A], // #
+    [0x04, 0x0F, 0x14, 0x0E, 0x05, 0x1E, 0x04], // $
+    [0x18, 0x19, 0x02, 0x04, 0x08, 0x13, 0x03], // %
+    [0x0C, 0x12, 0x14, 0x08, 0x15, 0x12, 0x0D], // &
+    [0x0C, 0x04, 0x08, 0x00, 0x00, 0x00, 0x00], // '
+    [0x02, 0x04, 0x08, 0x08, 0x08, 0x04, 0x02], // (
+    [0x08, 0x04, 0x02, 0x02, 0x02, 0x04, 0x08], // )
+    [0x00, 0x04, 0x15, 0x0E, 0x15, 0x04, 0x00], // *
+    [0x00, 0x04, 0x04, 0x1F, 0x04, 0x04, 0x00], // +
+    [0x00, 0x00, 0x00, 0x00, 0x0C, 0x04, 0x08], // ,
+    [0x00, 0x00, 0x00, 0x1F, 0x00, 0x00, 0x00], // -
+    [0x00, 0x00, 0x00, 0x00, 0x00, 0x0C, 0x0C], // .
+    [0x00, 0x01, 0x02, 0x04, 0x08, 0x10, 0x00], // /
+    [0x0E, 0x11, 0x13, 0x15, 0x19, 0x11, 0x0E], // 0
+    [0x04, 0x0C, 0x04, 0x04, 0x04, 0x04, 0x0E], // 1
+    [0x0E, 0x11, 0x01, 0x02, 0x04, 0x08, 0x1F], // 2
+    [0x1F, 0x02, 0x04, 0x02, 0x01, 0x11, 0x0E], // 3
+    [0x02, 0x06, 0x0A, 0x12, 0x1F, 0x02, 0x02], // 4
+    [0x1F, 0x10, 0x1E, 0x01, 0x01, 0x11, 0x0E], // 5
+    [0x06, 0x08, 0x10, 0x1E, 0x11, 0x11, 0x0E], // 6
+    [0x1F, 0x01, 0x02, 0x04, 0x08, 0x08, 0x08], // 7
+    [0x0E, 0x11, 0x11, 0x0E, 0x11, 0x11, 0x0E], // 8
+    [0x0E, 0x11, 0x11, 0x0F, 0x01, 0x02, 0x0C], // 9
+    [0x00, 0x0C, 0x0C, 0x00, 0x0C, 0x0C, 0x00], // :
+    [0x00, 0x0C, 0x0C, 0x00, 0x0C, 0x04, 0x08], // ;
+    [0x02, 0x04, 0x08, 0x10, 0x08, 0x04, 0x02], // <
+    [0x00, 0x00, 0x1F, 0x00, 0x1F, 0x00, 0x00], // =
+    [0x08, 0x04, 0x02, 0x01, 0x02, 0x04, 0x08], // >
+    [0x0E, 0x11, 0x01, 0x02, 0x04, 0x00, 0x04], // ?
+    [0x0E, 0x11, 0x01, 0x0D, 0x15, 0x15, 0x0E], // @
+    [0x0E, 0x11, 0x11, 0x11, 0x1F, 0x11, 0x11], // A
+    [0x1E, 0x11, 0x11, 0x1E, 0x11, 0x11, 0x1E], // B
+    [0x0E, 0x11, 0x10, 0x10, 0x10, 0x11, 0x0E], // C
+    [0x1C, 0x12, 0x11, 0x11, 0x11, 0x12, 0x1C], // D
+    [0x1F, 0x10, 0x10, 0x1E, 0x10, 0x10, 0x1F], // E
+    [0x1F, 0x10, 0x10, 0x1E, 0x10, 0x10, 0x10], // F
+    [0x0E, 0x11, 0x10, 0x17, 0x11, 0x11, 0x0F], // G
+    [0x11, 0x11, 0x11, 0x1F, 0x11, 0x11, 0x11], // H
+    [0x0E, 0x04, 0x04, 0x04, 0x04, 0x04, 0x0E], // I
+    [0x07, 0x02, 0x02, 0x02, 0x02, 0x12, 0x0C], // J
+    [0x11, 0x12, 0x14, 0x18, 0x14, 0x12, 0x11], // K
+    [0x10, 0x10, 0x10, 0x10, 0x10, 0x10, 0x1F], // L
+    [0x11, 0x1B, 0x15, 0x15, 0x11, 0x11, 0x11], // M
+    [0x11, 0x11, 0x19, 0x15, 0x13, 0x11, 0x11], // N
+    [0x0E, 0x11, 0x11, 0x11, 0x11, 0x11, 0x0E], // O
+    [0x1E, 0x11, 0x11, 0x1E, 0x10, 0x10, 0x10], // P
+    [0x0E, 0x11, 0x11, 0x11, 0x15, 0x12, 0x0D], // Q
+    [0x1E, 0x11, 0x11, 0x1E, 0x14, 0x12, 0x11], // R
+    [0x0F, 0x10, 0x10, 0x0E, 0x01, 0x01, 0x1E], // S
+    [0x1F, 0x04, 0x04, 0x04, 0x04, 0x04, 0x04], // T
+    [0x11, 0x11, 0x11, 0x11, 0x11, 0x11, 0x0E], // U
+    [0x11, 0x11, 0x11, 0x11, 0x11, 0x0A, 0x04], // V
+    [0x11, 0x11, 0x11, 0x15, 0x15, 0x15, 0x0A], // W
+    [0x11, 0x11, 0x0A, 0x04, 0x0A, 0x11, 0x11], // X
+    [0x11, 0x11, 0x11, 0x0A, 0x04, 0x04, 0x04], // Y
+    [0x1F, 0x01, 0x02, 0x04, 0x08, 0x10, 0x1F], // Z
+    [0x0E, 0x08, 0x08, 0x08, 0x08, 0x08, 0x0E], // [
+    [0x00, 0x10, 0x08, 0x04, 0x02, 0x01, 0x00], // \
+    [0x0E, 0x02, 0x02, 0x02, 0x02, 0x02, 0x0E], // ]
+    [0x04, 0x0A, 0x11, 0x00, 0x00, 0x00, 0x00], // ^
+    [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x1F], // _
+    [0x08, 0x04, 0x02, 0x00, 0x00, 0x00, 0x00], // `
+    [0x00, 0x00, 0x0E, 0x01, 0x0F, 0x11, 0x0F], // a
+    [0x10, 0x10, 0x16, 0x19, 0x11, 0x11, 0x1E], // b
+    [0x00, 0x00, 0x0E, 0x10, 0x10, 0x11, 0x0E], // c
+    [0x01, 0x01, 0x0D, 0x13, 0x11, 0x11, 0x0F], // d
+    [0x00, 0x00, 0x0E, 0x11, 0x1F, 0x10, 0x0E], // e
+    [0x06, 0x09, 0x08, 0x1C, 0x08, 0x08, 0x08], // f
+    [0x00, 0x0F, 0x11, 0x11, 0x0F, 0x01, 0x0E], // g
+    [0x10, 0x10, 0x16, 0x19, 0x11, 0x11, 0x11], // h
+    [0x04, 0x00, 0x0C, 0x04, 0x04, 0x04, 0x0E], // i
+    [0x02, 0x00, 0x06, 0x02, 0x02, 0x12, 0x0C], // j
+    [0x10, 0x10, 0x12, 0x14, 0x18, 0x14, 0x12], // k
+    [0x0C, 0x04, 0x04, 0x04, 0x04, 0x04, 0x0E], // l
+    [0x00, 0x00, 0x1A, 0x15, 0x15, 0x11, 0x11], // m
+    [0x00, 0x00, 0x16, 0x19, 0x11, 0x11, 0x11], // n
+    [0x00, 0x00, 0x0E, 0x11, 0x11, 0x11, 0x0E], // o
+    [0x00, 0x00, 0x1E, 0x11, 0x1E, 0x10, 0x10], // p
+    [0x00, 0x00, 0x0D, 0x13, 0x0F, 0x01, 0x01], // q
+    [0x00, 0x00, 0x16, 0x19, 0x10, 0x10, 0x10], // r
+    [0x00, 0x00, 0x0E, 0x10, 0x0E, 0x01, 0x1E], // s
+    [0x08, 0x08, 0x1C, 0x08, 0x08, 0x09, 0x06], // t
+    [0x00, 0x00, 0x11, 0x11, 0x11, 0x13, 0x0D], // u
+    [0x00, 0x00, 0x11, 0x11, 0x11, 0x0A, 0x04], // v
+    [0x00, 0x00, 0x11, 0x11, 0x15, 0x15, 0x0A], // w
+    [0x00, 0x00, 0x11, 0x0A, 0x04, 0x0A, 0x11], // x
+    [0x00, 0x00, 0x11, 0x11, 0x0F, 0x01, 0x0E], // y
+    [0x00, 0x00, 0x1F, 0x02, 0x04, 0x08, 0x1F], // z
+    [0x02, 0x04, 0x04, 0x08, 0x04, 0x04, 0x02], // {
+    [0x04, 0x04, 0x04, 0x04, 0x04, 0x04, 0x04], // |
+    [0x08, 0x04, 0x04, 0x02, 0x04, 0x04, 0x08], // }
+    [0x00, 0x00, 0x08, 0x15, 0x02, 0x00, 0x00], // ~
+];
+
+/// Alineación horizontal respecto a la `x` de `draw_text`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Align {
+    Left,
+    Center,
+    Right,
+}
+
+/// Apariencia y disposición del texto
+#[derive(Debug, Clone, Copy)]
+pub struct TextStyle {
+    pub color: u32,
+    /// Opacidad entre 0 (invisible) y 1 (opaco)
+    pub alpha: f32,
+    /// Píxeles de pantalla por píxel de la fuente
+    pub scale: usize,
+    pub align: Align,
+    /// Ancho máximo en píxeles; las líneas más largas se parten por palabras
+    pub max_width: Option<usize>,
+}
+
+impl Default for TextStyle {
+    fn default() -> Self {
+        TextStyle {
+            color: 0xFFFFFF,
+            alpha: 1.0,
+            scale: 1,
+            align: Align::Left,
+            max_width: None,
+        }
+    }
+}
+
+impl TextStyle {
+    pub fn line_height(&self) -> usize {
+        LINE_SPACING * self.scale
+    }
+}
+
+// Las vocales acentuadas y la ñ se dibujan con su letra base
+fn fold_accent(c: char) -> char {
+    match c {
+        'á' | 'à' | 'ä' => 'a',
+        'é' | 'è' | 'ë' => 'e',
+        'í' | 'ì' | 'ï' => 'i',
+        'ó' | 'ò' | 'ö' => 'o',
+        'ú' | 'ù' | 'ü' => 'u',
+        'ñ' => 'n',
+        'Á' | 'À' | 'Ä' => 'A',
+        'É' | 'È' | 'Ë' => 'E',
+        'Í' | 'Ì' | 'Ï' => 'I',
+        'Ó' | 'Ò' | 'Ö' => 'O',
+        'Ú' | 'Ù' | 'Ü' => 'U',
+        'Ñ' => 'N',
+        '¿' | '¡' => ' ',
+        _ => c,
+    }
+}
+
+fn glyph(c: char) -> &'static [u8; GLYPH_HEIGHT] {
+    let c = fold_accent(c);
+    let index = if (' '..='~').contains(&c) { c as usize - 32 } else { '?' as usize - 32 };
+    &FONT[index]
+}
+
+/// Ancho en píxeles de una sola línea
+pub fn line_width(line: &str, scale: usize) -> usize {
+    let count = line.chars().count();
+    if count == 0 {
+        return 0;
+    }
+    ((count - 1) * GLYPH_ADVANCE + GLYPH_WIDTH) * scale
+}
+
+/// Parte el texto en líneas: respeta los saltos de línea y, si hay ancho
+/// máximo, corta entre palabras (una palabra más larga que el ancho se
+/// queda sola en su línea)
+pub fn layout_lines(text: &str, style: &TextStyle) -> Vec<String> {
+    let mut lines = Vec::new();
+    for paragraph in text.split('\n') {
+        let Some(max_width) = style.max_width else {
+            lines.push(paragraph.to_string());
+            continue;
+        };
+
+        let mut current = String::new();
+        for word in paragraph.split_whitespace() {
+            let candidate = if current.is_empty() {
+                word.to_string()
+            } else {
+                format!("{} {}", current, word)
+            };
+            if line_width(&candidate, style.scale) <= max_width || current.is_empty() {
+                current = candidate;
+            } else {
+                lines.push(std::mem::replace(&mut current, word.to_string()));
+            }
+        }
+        lines.push(current);
+    }
+    lines
+}
+
+/// Ancho y alto en píxeles del bloque de texto ya dispuesto
+pub fn measure(text: &str, style: &TextStyle) -> (usize, usize) {
+    let lines = layout_lines(text, style);
+    let width = lines.iter().map(|l| line_width(l, style.scale)).max().unwrap_or(0);
+    let height = match lines.len() {
+        0 => 0,
+        n => (n - 1) * style.line_height() + GLYPH_HEIGHT * style.scale,
+    };
+    (width, height)
+}
+
+fn draw_glyph(framebuffer: &mut Framebuffer, c: char, x: i32, y: i32, style: &TextStyle) {
+    let scale = style.scale as i32;
+    for (row, bits) in glyph(c).iter().enumerate() {
+        for column in 0..GLYPH_WIDTH {
+            if bits & (0x10 >> column) != 0 {
+                let left = x + column as i32 * scale;
+                let top = y + row as i32 * scale;
+                fill_rect(framebuffer, left, top, style.scale, style.scale, style.color, style.alpha);
+            }
+        }
+    }
+}
+
+/// Dibuja texto en pantalla sin prueba de profundidad. `y` es el borde
+/// superior y `x` el borde izquierdo, el centro o el borde derecho de cada
+/// línea según `style.align`. Devuelve la altura ocupada en píxeles.
+pub fn draw_text(framebuffer: &mut Framebuffer, text: &str, x: i32, y: i32, style: &TextStyle) -> usize {
+    let lines = layout_lines(text, style);
+    for (i, line) in lines.iter().enumerate() {
+        let width = line_width(line, style.scale) as i32;
+        let left = match style.align {
+            Align::Left => x,
+            Align::Center => x - width / 2,
+            Align::Right => x - width,
+        };
+        let top = y + (i * style.line_height()) as i32;
+
+        for (j, c) in line.chars().enumerate() {
+            draw_glyph(framebuffer, c, left + (j * GLYPH_ADVANCE * style.scale) as i32, top, style);
+        }
+    }
+    lines.len() * style.line_height()
+}
+
+/// Rellena un rectángulo mezclando `color` con lo que ya hay en pantalla,
+/// para dar fondo legible a paneles de texto
+pub fn fill_rect(framebuffer: &mut Framebuffer, x: i32, y: i32, width: usize, height: usize, color: u32, alpha: f32) {
+    for py in y.max(0)..(y + height as i32).min(HEIGHT as i32) {
+        for px in x.max(0)..(x + width as i32).min(WIDTH as i32) {
+            let index = py as usize * WIDTH + px as usize;
+            framebuffer.buffer[index] = blend(framebuffer.buffer[index], color, alpha);
+        }
+    }
+}
@@ -0,0 +1,151 @@
+use minifb::Key;
+
+// Caracteres que admite el campo, suficientes para cualquier f32 razonable
+const MAX_LENGTH: usize = 16;
+
+/// Lo que pidió el usuario con las teclas de un cuadro
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PromptEvent {
+    /// Sigue escribiendo (o no hay campo abierto)
+    Editing,
+    /// Confirmó con Enter un instante válido
+    Jump(f32),
+    /// Cerró el campo con Escape
+    Cancel,
+}
+
+/// Campo de texto para saltar a un instante de la simulación. Mientras está
+/// abierto las teclas escriben en él en lugar de disparar acciones.
+#[derive(Debug, Default)]
+pub struct TimePrompt {
+    text: Option<String>,
+    // El último Enter no se pudo leer como número
+    invalid: bool,
+}
+
+fn key_char(key: Key) -> Option<char> {
+    let c = match key {
+        Key::Key0 | Key::NumPad0 => '0',
+        Key::Key1 | Key::NumPad1 => '1',
+        Key::Key2 | Key::NumPad2 => '2',
+        Key::Key3 | Key::NumPad3 => '3',
+        Key::Key4 | Key::NumPad4 => '4',
+        Key::Key5 | Key::NumPad5 => '5',
+        Key::Key6 | Key::NumPad6 => '6',
+        Key::Key7 | Key::NumPad7 => '7',
+        Key::Key8 | Key::NumPad8 => '8',
+        Key::Key9 | Key::NumPad9 => '9',
+        Key::Period | Key::Comma | Key::NumPadDot => '.',
+        Key::Minus | Key::NumPadMinus => '-',
+        _ => return None,
+    };
+    Some(c)
+}
+
+impl TimePrompt {
+    pub fn is_active(&self) -> bool {
+        self.text.is_some()
+    }
+
+    /// Abre el campo vacío
+    pub fn open(&mut self) {
+        self.text = Some(String::new());
+        self.invalid = false;
+    }
+
+    fn close(&mut self) {
+        self.text = None;
+        self.invalid = false;
+    }
+
+    /// Aplica las teclas pulsadas en este cuadro, en orden
+    pub fn handle_keys(&mut self, keys: &[Key]) -> PromptEvent {
+        for &key in keys {
+            let Some(text) = self.text.as_mut() else {
+                return PromptEvent::Editing;
+            };
+            match key {
+                Key::Enter | Key::NumPadEnter => match text.parse::<f32>() {
+                    Ok(time) if time.is_finite() => {
+                        self.close();
+                        return PromptEvent::Jump(time);
+                    }
+                    _ => self.invalid = true,
+                },
+                Key::Escape => {
+                    self.close();
+                    return PromptEvent::Cancel;
+                }
+                Key::Backspace => {
+                    text.pop();
+                    self.invalid = false;
+                }
+                _ => {
+                    if let Some(c) = key_char(key).filter(|_| text.len() < MAX_LENGTH) {
+                        text.push(c);
+                        self.invalid = false;
+                    }
+                }
+            }
+        }
+        PromptEvent::Editing
+    }
+
+    /// Texto que se muestra en pantalla, `None` si el campo está cerrado
+    pub fn label(&self) -> Option<String> {
+        self.text.as_ref().map(|text| {
+            let hint = if self.invalid { "  (número no válido)" } else { "" };
+            format!("Ir al instante: {}_{}", text, hint)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn typed(keys: &[Key]) -> (TimePrompt, PromptEvent) {
+        let mut prompt = TimePrompt::default();
+        prompt.open();
+        let event = prompt.handle_keys(keys);
+        (prompt, event)
+    }
+
+    #[test]
+    fn enter_jumps_to_the_typed_time() {
+        let keys = [Key::Minus, Key::Key1, Key::Key2, Key::Period, Key::NumPad5, Key::Enter];
+        let (prompt, event) = typed(&keys);
+        assert_eq!(event, PromptEvent::Jump(-12.5));
+        assert!(!prompt.is_active());
+    }
+
+    #[test]
+    fn backspace_and_escape_edit_and_close() {
+        let (prompt, event) = typed(&[Key::Key4, Key::Key2, Key::Backspace, Key::Key7]);
+        assert_eq!(event, PromptEvent::Editing);
+        assert_eq!(prompt.label().unwrap(), "Ir al instante: 47_");
+
+        let (prompt, event) = typed(&[Key::Key3, Key::Escape, Key::Key1]);
+        assert_eq!(event, PromptEvent::Cancel);
+        assert_eq!(prompt.label(), None);
+    }
+
+    #[test]
+    fn invalid_numbers_keep_the_prompt_open() {
+        for keys in [&[Key::Enter][..], &[Key::Minus, Key::Enter], &[Key::Key1, Key::Period, Key::Period, Key::Enter]] {
+            let (prompt, event) = typed(keys);
+            assert_eq!(event, PromptEvent::Editing, "{:?}", keys);
+            assert!(prompt.label().unwrap().contains("no válido"));
+        }
+        // Letras y teclas sin carácter no se escriben
+        let (prompt, _) = typed(&[Key::A, Key::Space, Key::Key9]);
+        assert_eq!(prompt.label().unwrap(), "Ir al instante: 9_");
+    }
+
+    #[test]
+    fn closed_prompt_ignores_keys() {
+        let mut prompt = TimePrompt::default();
+        assert_eq!(prompt.handle_keys(&[Key::Key1, Key::Enter]), PromptEvent::Editing);
+        assert!(!prompt.is_active());
+    }
+}